@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
-use crate::utils::error::Result;
+use crate::providers::metrics::MAX_WINDOW_SECS;
+use crate::utils::error::{AppError, Result};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppConfig {
@@ -11,6 +13,40 @@ pub struct AppConfig {
     pub token_scheduler: SchedulerConfig,
     pub telegram_alert: TelegramAlertConfig,
     pub logger: LoggerConfig,
+    #[serde(default)]
+    pub admin: AdminConfig,
+    #[serde(default)]
+    pub alerting: AlertingConfig,
+    #[serde(default)]
+    pub alert_template: AlertTemplateConfig,
+    #[serde(default)]
+    pub audit: AuditConfig,
+    #[serde(default)]
+    pub access_log: AccessLogConfig,
+    #[serde(default)]
+    pub otlp: OtlpConfig,
+    #[serde(default)]
+    pub flow_reply: FlowReplyConfig,
+    #[serde(default)]
+    pub fan_out: FanOutConfig,
+    #[serde(default)]
+    pub status_order: StatusOrderConfig,
+    #[serde(default)]
+    pub status_coalesce: StatusCoalesceConfig,
+    #[serde(default)]
+    pub dispatch: DispatchConfig,
+    #[serde(default)]
+    pub backpressure: BackpressureConfig,
+    #[serde(default)]
+    pub ingress: IngressConfig,
+    #[serde(default)]
+    pub outbound: OutboundConfig,
+    #[serde(default)]
+    pub http_client: HttpClientConfig,
+    #[serde(default)]
+    pub permata_response: PermataResponseConfig,
+    #[serde(default)]
+    pub meta_response: MetaResponseConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,6 +54,50 @@ pub struct ServerConfig {
     pub listen_host: String,
     pub listen_port: u16,
     pub webhook_path: String,
+    #[serde(default)]
+    pub tls: TlsConfig,
+}
+
+/// HTTPS termination on the listener. Certificate, key and client CA files
+/// are checked every `reload_interval_secs` and reloaded when they change.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TlsConfig {
+    pub enabled: bool,
+    pub cert_path: String,
+    pub key_path: String,
+    pub min_version: TlsVersion,
+    /// Offer HTTP/2 through ALPN next to HTTP/1.1
+    pub alpn_h2: bool,
+    /// CA bundle for client certificates, empty disables mTLS
+    pub client_ca_path: String,
+    /// Reject clients without a certificate; when false one is verified only if sent
+    pub require_client_cert: bool,
+    pub reload_interval_secs: u64,
+}
+
+impl Default for TlsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            cert_path: String::new(),
+            key_path: String::new(),
+            min_version: TlsVersion::Tls12,
+            alpn_h2: true,
+            client_ca_path: String::new(),
+            require_client_cert: true,
+            reload_interval_secs: 30,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TlsVersion {
+    #[default]
+    #[serde(rename = "1.2")]
+    Tls12,
+    #[serde(rename = "1.3")]
+    Tls13,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -65,6 +145,699 @@ pub struct LoggerConfig {
     pub max_age: u32,
     pub compress: bool,
     pub local_time: bool,
+    /// error, warn, info, debug or trace
+    #[serde(default = "default_log_level")]
+    pub level: String,
+    #[serde(default)]
+    pub writer: LogWriterConfig,
+}
+
+fn default_log_level() -> String {
+    "info".to_string()
+}
+
+/// What to do when the background log queue is full
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogOverflowPolicy {
+    /// Wait for space in the queue, nothing is lost
+    #[default]
+    Block,
+    /// Drop the record and count it
+    Drop,
+    /// Keep one of every `sample_rate` overflowing records, drop the rest
+    Sample,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LogWriterConfig {
+    pub buffer_capacity: usize,
+    pub batch_size: usize,
+    pub overflow: LogOverflowPolicy,
+    pub sample_rate: u32,
+}
+
+impl Default for LogWriterConfig {
+    fn default() -> Self {
+        Self {
+            buffer_capacity: 8192,
+            batch_size: 256,
+            overflow: LogOverflowPolicy::Block,
+            sample_rate: 10,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdminConfig {
+    pub enabled: bool,
+    pub path_prefix: String,
+    /// Expected value of the `x-admin-key` header, every admin request is rejected when unset
+    pub api_key: Option<String>,
+}
+
+impl Default for AdminConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path_prefix: "/admin".to_string(),
+            api_key: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertingConfig {
+    pub evaluation_interval_secs: u64,
+    /// Added to the built-in rules, a rule with the same name replaces the built-in one
+    #[serde(default)]
+    pub rules: Vec<AlertRuleConfig>,
+    /// Set to false to evaluate only `rules`
+    #[serde(default = "default_include_default_rules")]
+    pub include_default_rules: bool,
+    #[serde(default)]
+    pub delivery: AlertDeliveryConfig,
+}
+
+impl Default for AlertingConfig {
+    fn default() -> Self {
+        Self {
+            evaluation_interval_secs: 15,
+            rules: Vec::new(),
+            include_default_rules: true,
+            delivery: AlertDeliveryConfig::default(),
+        }
+    }
+}
+
+fn default_include_default_rules() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertDeliveryConfig {
+    pub queue_capacity: usize,
+    pub max_attempts: u32,
+    pub initial_backoff_ms: u64,
+    pub max_backoff_secs: u64,
+    /// How long graceful shutdown waits for queued alerts
+    pub shutdown_deadline_secs: u64,
+}
+
+impl Default for AlertDeliveryConfig {
+    fn default() -> Self {
+        Self {
+            queue_capacity: 256,
+            max_attempts: 5,
+            initial_backoff_ms: 500,
+            max_backoff_secs: 30,
+            shutdown_deadline_secs: 10,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertRuleKind {
+    /// `counter` / `total_counter` in percent is above `threshold`
+    ErrorRate,
+    /// `counter` is above `threshold`
+    Count,
+    /// `counter` has not been seen for the whole window
+    Absence,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertRuleConfig {
+    pub name: String,
+    pub title: Option<String>,
+    pub kind: AlertRuleKind,
+    pub counter: String,
+    pub total_counter: Option<String>,
+    #[serde(default)]
+    pub threshold: f64,
+    pub window_secs: u64,
+    /// Minimum `total_counter` events before an error rate is evaluated
+    #[serde(default)]
+    pub min_events: u64,
+    pub business_hours: Option<BusinessHoursConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BusinessHoursConfig {
+    pub start_hour: u32,
+    pub end_hour: u32,
+    pub days: Vec<chrono::Weekday>,
+    pub utc_offset_hours: i32,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertMessageFormat {
+    #[default]
+    Plain,
+    MarkdownV2,
+    Html,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AlertTemplateConfig {
+    #[serde(default)]
+    pub telegram_format: AlertMessageFormat,
+    /// Template with `{{variable}}` placeholders, the built-in one is used when unset
+    pub telegram_template: Option<String>,
+    pub slack: Option<SlackAlertConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlackAlertConfig {
+    pub webhook_url: String,
+    pub template: Option<String>,
+}
+
+/// Payload audit stream, written next to the error log with the same rotation settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AuditConfig {
+    pub enabled: bool,
+    pub file_name: String,
+    /// Prepended to values before hashing, so hashes cannot be matched across
+    /// deployments. Required when a masking rule hashes.
+    pub hash_salt: Option<String>,
+    /// Added to the built-in rules (phone numbers, names, free text), a rule
+    /// with the same path replaces the built-in one
+    pub masking: Vec<MaskRuleConfig>,
+    /// Set to false to apply only `masking`
+    pub include_default_masking: bool,
+}
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            file_name: "webhook-audit".to_string(),
+            hash_salt: None,
+            masking: Vec::new(),
+            include_default_masking: true,
+        }
+    }
+}
+
+/// Decoding of `interactive.nfm_reply.response_json` before forwarding
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FlowReplyConfig {
+    pub decode: FlowReplyDecodeMode,
+    /// Field added next to `response_json` in `sibling` mode
+    pub decoded_field: String,
+    /// Schemas keyed by `flow_token`, replies of other flows are only decoded
+    pub schemas: HashMap<String, FlowSchemaConfig>,
+}
+
+impl Default for FlowReplyConfig {
+    fn default() -> Self {
+        Self {
+            decode: FlowReplyDecodeMode::Off,
+            decoded_field: "response_data".to_string(),
+            schemas: HashMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FlowReplyDecodeMode {
+    /// Forward `response_json` as the string WhatsApp sent
+    #[default]
+    Off,
+    /// Replace the string with the decoded object
+    Replace,
+    /// Keep the string and add the decoded object as `decoded_field`
+    Sibling,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FlowSchemaConfig {
+    pub required: Vec<String>,
+    pub properties: HashMap<String, FlowFieldType>,
+    /// Reject fields not listed in `properties`
+    pub deny_unknown: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FlowFieldType {
+    String,
+    Number,
+    Boolean,
+    Object,
+    Array,
+}
+
+/// Split batched webhooks into one Permata callback per status or message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FanOutConfig {
+    pub enabled: bool,
+    /// How long a delivered event id is remembered
+    pub dedup_ttl_secs: u64,
+    /// Oldest ids are forgotten first once this many are remembered
+    pub dedup_capacity: usize,
+    /// Events that failed every retry are appended here as JSON lines, empty disables
+    pub dead_letter_file: String,
+}
+
+impl Default for FanOutConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            dedup_ttl_secs: 86400,
+            dedup_capacity: 100_000,
+            dead_letter_file: "webhook-dead-letter".to_string(),
+        }
+    }
+}
+
+/// Guard against `sent`/`delivered`/`read` statuses arriving out of order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StatusOrderConfig {
+    pub enabled: bool,
+    pub mode: StatusRegressionMode,
+    /// How long the highest status of a message is remembered
+    pub ttl_secs: u64,
+    pub capacity: usize,
+    /// Field set on a regressed status in `tag` mode
+    pub tag_field: String,
+}
+
+impl Default for StatusOrderConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            mode: StatusRegressionMode::Tag,
+            ttl_secs: 86400,
+            capacity: 100_000,
+            tag_field: "status_regression".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StatusRegressionMode {
+    /// Drop the status, the webhook is acknowledged without a callback when nothing is left
+    Suppress,
+    /// Forward the status with `tag_field` set
+    #[default]
+    Tag,
+}
+
+/// Hold `sent`/`delivered`/`read` receipts per message and forward only the
+/// most advanced one at the end of the window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StatusCoalesceConfig {
+    pub enabled: bool,
+    pub hold_window_ms: u64,
+    /// Receipts are forwarded right away once this many messages are held
+    pub max_pending: usize,
+    /// Held receipts that could not be delivered after they were acknowledged are
+    /// appended here as JSON lines. Empty disables holding, since a failure
+    /// could not be kept.
+    pub dead_letter_file: String,
+}
+
+impl Default for StatusCoalesceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            hold_window_ms: 5000,
+            max_pending: 10_000,
+            dead_letter_file: "status-coalesce-undelivered".to_string(),
+        }
+    }
+}
+
+/// Partitioned forwarding: webhooks of the same customer are sent to Permata
+/// one after another, different customers in parallel
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DispatchConfig {
+    pub enabled: bool,
+    pub partitions: usize,
+    /// Queued webhooks per partition before new ones are rejected with 503
+    pub partition_backlog: usize,
+    /// How long shutdown waits for the queued webhooks to be forwarded
+    pub shutdown_deadline_secs: u64,
+}
+
+impl Default for DispatchConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            partitions: 8,
+            partition_backlog: 100,
+            shutdown_deadline_secs: 30,
+        }
+    }
+}
+
+/// Concurrency limits of the webhook endpoint; requests over a limit get 503
+/// with `Retry-After` so Meta redelivers them later
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackpressureConfig {
+    pub enabled: bool,
+    /// Webhooks processed at the same time, over all tenants
+    pub max_in_flight: usize,
+    pub tenant_max_in_flight: usize,
+    /// Webhooks of one tenant waiting for a slot
+    pub tenant_queue_depth: usize,
+    /// Longest wait for a tenant slot
+    pub queue_timeout_ms: u64,
+    pub retry_after_secs: u64,
+    /// Idle tenants are forgotten once this many are tracked
+    pub max_tenants: usize,
+}
+
+impl Default for BackpressureConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_in_flight: 256,
+            tenant_max_in_flight: 32,
+            tenant_queue_depth: 64,
+            queue_timeout_ms: 2000,
+            retry_after_secs: 5,
+            max_tenants: 10_000,
+        }
+    }
+}
+
+/// Source restrictions of the webhook route
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IngressConfig {
+    /// CIDRs or addresses allowed to call the webhook route, empty allows all
+    pub allowlist: Vec<String>,
+    /// Load balancers whose `X-Forwarded-For` header is trusted
+    pub trusted_proxies: Vec<String>,
+    pub ip_rate_limit: RateLimitConfig,
+    /// Keyed by `metadata.phone_number_id`
+    pub tenant_rate_limit: RateLimitConfig,
+    /// Larger webhook bodies are rejected with 400 without being buffered
+    pub max_body_bytes: usize,
+}
+
+impl Default for IngressConfig {
+    fn default() -> Self {
+        Self {
+            allowlist: Vec::new(),
+            trusted_proxies: Vec::new(),
+            ip_rate_limit: RateLimitConfig::default(),
+            tenant_rate_limit: RateLimitConfig::default(),
+            max_body_bytes: 1024 * 1024,
+        }
+    }
+}
+
+/// Token bucket: `burst` requests at once, refilled at `per_second`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RateLimitConfig {
+    pub enabled: bool,
+    pub per_second: f64,
+    pub burst: u32,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            per_second: 50.0,
+            burst: 100,
+        }
+    }
+}
+
+/// How a Permata callback response counts: by `StatusCode` in the body
+/// first, then by HTTP status
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PermataResponseConfig {
+    /// Permata `StatusCode` -> outcome, wins over the HTTP status
+    pub status_codes: HashMap<String, ResponseOutcome>,
+    /// 2xx whose `StatusCode` is not in the table
+    pub unknown_status_code: ResponseOutcome,
+    /// 2xx without a `StatusCode` (empty or non-JSON body)
+    pub missing_status_code: ResponseOutcome,
+    /// Non-2xx statuses worth another attempt, every other one is permanent
+    pub retryable_http_statuses: Vec<u16>,
+}
+
+impl Default for PermataResponseConfig {
+    fn default() -> Self {
+        Self {
+            status_codes: HashMap::from([("00".to_string(), ResponseOutcome::Success)]),
+            unknown_status_code: ResponseOutcome::PermanentFailure,
+            missing_status_code: ResponseOutcome::Success,
+            retryable_http_statuses: vec![408, 429, 500, 502, 503, 504],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResponseOutcome {
+    Success,
+    /// Retried up to `webclient.max_retries`, then handled as a failure
+    RetryableFailure,
+    /// Not retried; alerted, counted and dead-lettered right away
+    PermanentFailure,
+}
+
+/// What the webhook route answers Meta once Permata has been called
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MetaResponseConfig {
+    pub mode: MetaResponseMode,
+    /// `mapped` mode: downstream outcome -> response, unmapped outcomes pass through
+    pub mappings: MetaResponseMappings,
+    /// Failed deliveries answered with 2xx are appended here first, empty disables.
+    /// Without it a failure is never acknowledged and passes through instead.
+    pub dead_letter_file: String,
+}
+
+impl Default for MetaResponseConfig {
+    fn default() -> Self {
+        Self {
+            mode: MetaResponseMode::Passthrough,
+            mappings: MetaResponseMappings::default(),
+            dead_letter_file: "webhook-undelivered".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MetaResponseMode {
+    /// Mirror Permata's HTTP status and body
+    #[default]
+    Passthrough,
+    /// 200 once the event is delivered or dead-lettered
+    AlwaysOk,
+    /// Look the outcome up in `mappings`
+    Mapped,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MetaResponseMappings {
+    pub success: Option<MetaResponseRule>,
+    pub retryable_failure: Option<MetaResponseRule>,
+    pub permanent_failure: Option<MetaResponseRule>,
+    /// No usable response from Permata (network, login or gateway error)
+    pub error: Option<MetaResponseRule>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetaResponseRule {
+    pub status: u16,
+    /// Fixed response body, Permata's body is passed through when absent
+    #[serde(default)]
+    pub body: Option<MetaResponseBody>,
+}
+
+/// Sent as `{"StatusCode": ..., "StatusDesc": ...}`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MetaResponseBody {
+    pub status_code: String,
+    #[serde(default)]
+    pub status_desc: String,
+}
+
+/// Connection pool and timeouts shared by all outbound clients
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HttpClientConfig {
+    pub pool_max_idle_per_host: usize,
+    pub pool_idle_timeout_secs: u64,
+    /// TCP keepalive probe interval, 0 disables it
+    pub tcp_keepalive_secs: u64,
+    /// Negotiate HTTP/2 through ALPN; false keeps every destination on HTTP/1.1
+    pub http2: bool,
+    /// HTTP/2 PING interval on idle connections, 0 disables it
+    pub http2_keepalive_secs: u64,
+    pub connect_timeout_ms: u64,
+    /// Longest wait between two reads of a response, 0 leaves only `webclient.timeout`
+    pub read_timeout_secs: u64,
+}
+
+impl Default for HttpClientConfig {
+    fn default() -> Self {
+        Self {
+            pool_max_idle_per_host: 32,
+            pool_idle_timeout_secs: 90,
+            tcp_keepalive_secs: 60,
+            http2: true,
+            http2_keepalive_secs: 0,
+            connect_timeout_ms: 2000,
+            read_timeout_secs: 0,
+        }
+    }
+}
+
+/// Connection settings of outbound calls, per destination
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OutboundConfig {
+    /// Login and callback status calls to Permata
+    pub permata: OutboundTargetConfig,
+    pub telegram: OutboundTargetConfig,
+    /// Slack incoming webhook of `alert_template.slack`
+    pub slack: OutboundTargetConfig,
+}
+
+/// Empty strings leave the reqwest default in place
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OutboundTargetConfig {
+    /// PEM certificate chain and private key presented to the server (mTLS)
+    pub client_cert_path: String,
+    pub client_key_path: String,
+    /// Extra root CAs (PEM) trusted next to the system roots
+    pub ca_bundle_path: String,
+    /// Accept only this server certificate (PEM), roots and hostname are not checked
+    pub pinned_cert_path: String,
+    pub min_tls_version: TlsVersion,
+    /// Source IP of outbound connections, e.g. the whitelisted egress address
+    pub local_address: String,
+    /// Network interface to bind to (Linux only)
+    pub interface: String,
+    pub proxy: OutboundProxyConfig,
+    /// Static DNS overrides, host -> IP (the port of the URL is kept)
+    pub resolve: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OutboundProxyConfig {
+    /// `http://` or `https://` proxy for all requests of the destination
+    pub url: String,
+    pub username: String,
+    pub password: String,
+}
+
+/// OpenTelemetry trace export over OTLP/HTTP (JSON encoding)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OtlpConfig {
+    pub enabled: bool,
+    /// Full traces URL of the collector, e.g. `http://localhost:4318/v1/traces`
+    pub endpoint: String,
+    pub service_name: String,
+    pub queue_capacity: usize,
+    pub batch_size: usize,
+    pub export_interval_ms: u64,
+    pub timeout_secs: u64,
+}
+
+impl Default for OtlpConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            endpoint: "http://localhost:4318/v1/traces".to_string(),
+            service_name: "webhook-gateway".to_string(),
+            queue_capacity: 4096,
+            batch_size: 256,
+            export_interval_ms: 2000,
+            timeout_secs: 5,
+        }
+    }
+}
+
+/// One line per HTTP request, written to `logger.dir` with its own rotation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AccessLogConfig {
+    pub enabled: bool,
+    pub format: AccessLogFormat,
+    pub file_name: String,
+    pub max_backups: u32,
+    pub max_size: u32,
+    pub max_age: u32,
+    pub compress: bool,
+}
+
+impl Default for AccessLogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            format: AccessLogFormat::Json,
+            file_name: "webhook-access".to_string(),
+            max_backups: 0,
+            max_size: 10,
+            max_age: 30,
+            compress: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccessLogFormat {
+    #[default]
+    Json,
+    /// Apache/nginx combined log format with latency, classification and request id appended
+    Combined,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MaskStrategy {
+    /// Salted SHA-256, stable so the same customer can still be correlated
+    Hash,
+    /// Keep only the last `keep_last` characters
+    Partial,
+    /// Replace the whole value
+    Redact,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaskRuleConfig {
+    /// Dot separated path, `[*]` for every array item and `**` for any depth,
+    /// e.g. `**.contacts.[*].wa_id`
+    pub path: String,
+    pub strategy: MaskStrategy,
+    #[serde(default = "default_keep_last")]
+    pub keep_last: usize,
+}
+
+fn default_keep_last() -> usize {
+    4
 }
 
 impl AppConfig {
@@ -74,6 +847,39 @@ impl AppConfig {
             .add_source(config::Environment::with_prefix("APP"))
             .build()?;
 
-        Ok(settings.try_deserialize()?)
+        let config: Self = settings.try_deserialize()?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Reject values that would only fail later on the request path
+    pub fn validate(&self) -> Result<()> {
+        // Counter metrics hanya menyimpan satu jam terakhir, window lebih panjang tidak bisa dihitung
+        for rule in &self.alerting.rules {
+            if rule.window_secs > MAX_WINDOW_SECS {
+                return Err(AppError::configuration(format!(
+                    "alerting rule '{}': window_secs {} is longer than the {}s kept by the metrics",
+                    rule.name, rule.window_secs, MAX_WINDOW_SECS
+                )));
+            }
+        }
+
+        let rate_limits = [
+            ("ingress.ip_rate_limit", &self.ingress.ip_rate_limit),
+            ("ingress.tenant_rate_limit", &self.ingress.tenant_rate_limit),
+        ];
+        for (name, rate_limit) in rate_limits {
+            // NaN juga ditolak
+            if rate_limit.enabled && !(rate_limit.per_second > 0.0 && rate_limit.per_second.is_finite()) {
+                return Err(AppError::configuration(format!(
+                    "{}.per_second must be greater than 0, got {}",
+                    name, rate_limit.per_second
+                )));
+            }
+        }
+        if self.ingress.max_body_bytes == 0 {
+            return Err(AppError::configuration("ingress.max_body_bytes must be greater than 0"));
+        }
+        Ok(())
     }
 }
\ No newline at end of file
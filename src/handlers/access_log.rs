@@ -0,0 +1,111 @@
+use std::future::Future;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Instant;
+
+use axum::body::HttpBody;
+use axum::extract::{ConnectInfo, Request};
+use axum::http::header;
+use axum::response::Response;
+use tower::{Layer, Service};
+
+use crate::providers::access_log::{AccessLogEntry, AccessLogger, RequestClassification};
+use crate::utils::request_id::RequestId;
+
+/// Tower layer that writes one access log line per request
+#[derive(Clone)]
+pub struct AccessLogLayer {
+    logger: &'static AccessLogger,
+}
+
+impl AccessLogLayer {
+    pub fn new(logger: &'static AccessLogger) -> Self {
+        Self { logger }
+    }
+}
+
+impl<S> Layer<S> for AccessLogLayer {
+    type Service = AccessLogService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        AccessLogService {
+            inner,
+            logger: self.logger,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct AccessLogService<S> {
+    inner: S,
+    logger: &'static AccessLogger,
+}
+
+impl<S> Service<Request> for AccessLogService<S>
+where
+    S: Service<Request, Response = Response> + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        let started = Instant::now();
+        let logger = self.logger;
+
+        let header_value = |name: header::HeaderName| {
+            request
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(|value| value.to_string())
+        };
+        let mut entry = AccessLogEntry {
+            remote_addr: request
+                .extensions()
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|ConnectInfo(addr)| addr.ip().to_string()),
+            method: request.method().to_string(),
+            path: request.uri().path().to_string(),
+            version: format!("{:?}", request.version()),
+            status: 0,
+            body_size: None,
+            latency: Default::default(),
+            request_id: request.extensions().get::<RequestId>().map(|id| id.0.clone()),
+            classification: None,
+            referer: header_value(header::REFERER),
+            user_agent: header_value(header::USER_AGENT),
+        };
+
+        let future = self.inner.call(request);
+        Box::pin(async move {
+            let response = future.await?;
+
+            entry.status = response.status().as_u16();
+            entry.latency = started.elapsed();
+            entry.body_size = response.body().size_hint().exact().or_else(|| {
+                response
+                    .headers()
+                    .get(header::CONTENT_LENGTH)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|value| value.parse().ok())
+            });
+            // Handler webhook mengganti request id dengan id dari body
+            if let Some(RequestId(request_id)) = response.extensions().get::<RequestId>() {
+                entry.request_id = Some(request_id.clone());
+            }
+            if let Some(RequestClassification(classification)) = response.extensions().get::<RequestClassification>() {
+                entry.classification = Some(classification.clone());
+            }
+
+            logger.record(&entry);
+            Ok(response)
+        })
+    }
+}
@@ -0,0 +1,158 @@
+use axum::{
+    extract::{Request, State},
+    http::StatusCode,
+    middleware::{self, Next},
+    response::{IntoResponse, Json, Response},
+    routing::get,
+    Router,
+};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+use std::time::Duration;
+use tracing::level_filters::LevelFilter;
+
+use crate::handlers::webhook_server::AppState;
+use crate::providers::logging::StructuredLogger;
+use crate::providers::{LogLevelControl, LogScope, Metrics};
+use crate::services::IncidentTracker;
+
+/// Header yang dipakai untuk autentikasi admin API
+pub const ADMIN_KEY_HEADER: &str = "x-admin-key";
+
+/// TTL log level override kalau request tidak menyebutkan `ttl_secs`
+pub const DEFAULT_LOG_LEVEL_TTL_SECS: u64 = 600;
+pub const MAX_LOG_LEVEL_TTL_SECS: u64 = 3600;
+
+/// Routes for the admin API, nested under `admin.path_prefix`
+pub fn admin_router(state: AppState) -> Router<AppState> {
+    Router::new()
+        .route("/incidents", get(incidents_handler))
+        .route("/metrics", get(metrics_handler))
+        .route(
+            "/log-level",
+            get(get_log_level_handler)
+                .put(set_log_level_handler)
+                .delete(reset_log_level_handler),
+        )
+        .route_layer(middleware::from_fn_with_state(state, require_admin_key))
+}
+
+async fn require_admin_key(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    // Tanpa api_key admin API tertutup, bukan terbuka
+    let Some(expected_key) = state.app_config.admin.api_key.as_deref().filter(|key| !key.is_empty()) else {
+        StructuredLogger::log_warning(
+            &format!("Rejected admin request to {}: admin.api_key is not configured", request.uri().path()),
+            None,
+            None,
+        );
+        return (
+            StatusCode::FORBIDDEN,
+            Json(serde_json::json!({
+                "status": "error",
+                "message": "Admin API key is not configured"
+            })),
+        )
+            .into_response();
+    };
+
+    let provided_key = request
+        .headers()
+        .get(ADMIN_KEY_HEADER)
+        .and_then(|value| value.to_str().ok());
+
+    if !provided_key.is_some_and(|key| keys_match(key, expected_key)) {
+        StructuredLogger::log_warning(
+            &format!("Rejected admin request to {}: invalid admin key", request.uri().path()),
+            None,
+            None,
+        );
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(serde_json::json!({
+                "status": "error",
+                "message": "Invalid admin key"
+            })),
+        )
+            .into_response();
+    }
+
+    next.run(request).await
+}
+
+/// Constant-time comparison of the key digests, so neither the content nor the
+/// length of the configured key leaks through response timing
+fn keys_match(provided: &str, expected: &str) -> bool {
+    Sha256::digest(provided.as_bytes())
+        .ct_eq(&Sha256::digest(expected.as_bytes()))
+        .into()
+}
+
+pub async fn incidents_handler(State(state): State<AppState>) -> impl IntoResponse {
+    let snapshot = IncidentTracker::global(&state.app_config).snapshot();
+
+    (StatusCode::OK, Json(snapshot))
+}
+
+pub async fn metrics_handler() -> impl IntoResponse {
+    (StatusCode::OK, Json(Metrics::global().snapshot()))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetLogLevelRequest {
+    pub level: String,
+    #[serde(flatten)]
+    pub scope: LogScope,
+    pub ttl_secs: Option<u64>,
+}
+
+pub async fn get_log_level_handler() -> impl IntoResponse {
+    (StatusCode::OK, Json(LogLevelControl::global().snapshot()))
+}
+
+/// Raise verbosity globally, per module, per request id or per tenant until the TTL expires
+pub async fn set_log_level_handler(Json(request): Json<SetLogLevelRequest>) -> Response {
+    let level = match request.level.parse::<LevelFilter>() {
+        Ok(level) => level,
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({
+                    "status": "error",
+                    "message": format!("Invalid log level: {}", request.level)
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let ttl_secs = request
+        .ttl_secs
+        .unwrap_or(DEFAULT_LOG_LEVEL_TTL_SECS)
+        .min(MAX_LOG_LEVEL_TTL_SECS);
+    let entry = LogLevelControl::global().set_override(request.scope, level, Duration::from_secs(ttl_secs));
+
+    StructuredLogger::log_warning(
+        &format!(
+            "Log level override {} set to {} until {}",
+            serde_json::to_string(&entry.scope).unwrap_or_default(),
+            entry.level,
+            entry.expires_at
+        ),
+        None,
+        None,
+    );
+
+    (StatusCode::OK, Json(entry)).into_response()
+}
+
+pub async fn reset_log_level_handler() -> impl IntoResponse {
+    let control = LogLevelControl::global();
+    control.clear_all();
+
+    (StatusCode::OK, Json(control.snapshot()))
+}
@@ -0,0 +1,246 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::{IpAddr, SocketAddr};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use axum::extract::{ConnectInfo, Request};
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Json, Response};
+use ipnet::IpNet;
+use tower::{Layer, Service};
+
+use crate::config::IngressConfig;
+use crate::models::InboundWebhook;
+use crate::providers::metrics::{self, Metrics};
+use crate::providers::StructuredLogger;
+use crate::utils::error::{AppError, Result};
+use crate::utils::rate_limit::KeyedRateLimiter;
+use crate::utils::request_id::RequestId;
+
+pub const X_FORWARDED_FOR: &str = "x-forwarded-for";
+
+/// Parsed CIDR list, plain addresses are single-host networks
+#[derive(Debug, Clone, Default)]
+pub struct IpNetList(Vec<IpNet>);
+
+impl IpNetList {
+    pub fn parse(entries: &[String]) -> Result<Self> {
+        entries
+            .iter()
+            .map(|entry| {
+                let entry = entry.trim();
+                entry
+                    .parse::<IpNet>()
+                    .or_else(|_| entry.parse::<IpAddr>().map(IpNet::from))
+                    .map_err(|_| AppError::configuration(format!("Invalid CIDR or IP address '{}'", entry)))
+            })
+            .collect::<Result<Vec<_>>>()
+            .map(Self)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn contains(&self, ip: &IpAddr) -> bool {
+        self.0.iter().any(|net| net.contains(ip))
+    }
+}
+
+/// Address of the caller. When the peer is a trusted proxy, `X-Forwarded-For`
+/// is read from the right and the first hop that is not a trusted proxy wins.
+pub fn client_ip(peer: IpAddr, headers: &HeaderMap, trusted_proxies: &IpNetList) -> IpAddr {
+    if !trusted_proxies.contains(&peer) {
+        return peer;
+    }
+
+    let hops: Vec<IpAddr> = headers
+        .get_all(X_FORWARDED_FOR)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|hop| hop.trim().parse().ok())
+        .collect();
+
+    hops.iter()
+        .rev()
+        .find(|hop| !trusted_proxies.contains(hop))
+        .or_else(|| hops.first())
+        .copied()
+        .unwrap_or(peer)
+}
+
+/// Headers as the string map kept on `InboundWebhook`
+pub fn header_pairs(headers: &HeaderMap) -> HashMap<String, String> {
+    headers
+        .iter()
+        .map(|(k, v)| (k.as_str().to_string(), v.to_str().unwrap_or("").to_string()))
+        .collect()
+}
+
+/// Why the ingress layers turned a request away
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IngressRejection {
+    IpNotAllowed,
+    IpRateLimited,
+    TenantRateLimited,
+}
+
+impl IngressRejection {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::IpNotAllowed => "ip_not_allowed",
+            Self::IpRateLimited => "ip_rate_limited",
+            Self::TenantRateLimited => "tenant_rate_limited",
+        }
+    }
+
+    fn counter(&self) -> &'static str {
+        match self {
+            Self::IpNotAllowed => metrics::INGRESS_IP_DENIED,
+            Self::IpRateLimited | Self::TenantRateLimited => metrics::INGRESS_RATE_LIMITED,
+        }
+    }
+
+    fn status(&self) -> StatusCode {
+        match self {
+            Self::IpNotAllowed => StatusCode::FORBIDDEN,
+            Self::IpRateLimited | Self::TenantRateLimited => StatusCode::TOO_MANY_REQUESTS,
+        }
+    }
+}
+
+/// Log, count and build the response of a rejected request
+fn reject(request_id: Option<&str>, rejection: IngressRejection, key: &str, retry_after: Option<Duration>) -> Response {
+    StructuredLogger::log_warning(
+        &format!("Rejected webhook request from {}: {}", key, rejection.as_str()),
+        request_id,
+        request_id,
+    );
+    Metrics::global().increment(rejection.counter());
+
+    let mut response = (
+        rejection.status(),
+        Json(serde_json::json!({
+            "StatusCode": "06",
+            "StatusDesc": rejection.as_str()
+        })),
+    )
+        .into_response();
+    if let Some(retry_after) = retry_after {
+        let secs = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+        response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(secs.max(1)));
+    }
+    response
+}
+
+struct IpFilter {
+    allowlist: IpNetList,
+    trusted_proxies: IpNetList,
+    rate_limiter: Option<KeyedRateLimiter>,
+}
+
+/// Tower layer that applies the CIDR allowlist and the per source IP token
+/// bucket before the request reaches the webhook handler
+#[derive(Clone)]
+pub struct IpFilterLayer {
+    filter: Arc<IpFilter>,
+}
+
+impl IpFilterLayer {
+    pub fn new(config: &IngressConfig) -> Result<Self> {
+        Ok(Self {
+            filter: Arc::new(IpFilter {
+                allowlist: IpNetList::parse(&config.allowlist)?,
+                trusted_proxies: IpNetList::parse(&config.trusted_proxies)?,
+                rate_limiter: config.ip_rate_limit.enabled.then(|| KeyedRateLimiter::new(&config.ip_rate_limit)),
+            }),
+        })
+    }
+}
+
+impl<S> Layer<S> for IpFilterLayer {
+    type Service = IpFilterService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        IpFilterService {
+            inner,
+            filter: Arc::clone(&self.filter),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct IpFilterService<S> {
+    inner: S,
+    filter: Arc<IpFilter>,
+}
+
+impl<S> Service<Request> for IpFilterService<S>
+where
+    S: Service<Request, Response = Response> + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = std::result::Result<Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<std::result::Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        let request_id = request.extensions().get::<RequestId>().map(|id| id.0.clone());
+        let request_id = request_id.as_deref();
+        let peer = request.extensions().get::<ConnectInfo<SocketAddr>>().map(|ConnectInfo(addr)| addr.ip());
+        // Tanpa ConnectInfo alamat asal tidak diketahui, hanya lolos bila allowlist kosong
+        let Some(peer) = peer else {
+            if self.filter.allowlist.is_empty() {
+                return Box::pin(self.inner.call(request));
+            }
+            let response = reject(request_id, IngressRejection::IpNotAllowed, "unknown", None);
+            return Box::pin(async move { Ok(response) });
+        };
+
+        let ip = client_ip(peer, request.headers(), &self.filter.trusted_proxies);
+        let key = ip.to_string();
+
+        if !self.filter.allowlist.is_empty() && !self.filter.allowlist.contains(&ip) {
+            let response = reject(request_id, IngressRejection::IpNotAllowed, &key, None);
+            return Box::pin(async move { Ok(response) });
+        }
+        if let Some(limiter) = &self.filter.rate_limiter {
+            if let Err(retry_after) = limiter.check(&key) {
+                let response = reject(request_id, IngressRejection::IpRateLimited, &key, Some(retry_after));
+                return Box::pin(async move { Ok(response) });
+            }
+        }
+
+        Box::pin(self.inner.call(request))
+    }
+}
+
+/// Token bucket per tenant. Checked by the webhook handler after it took the
+/// global backpressure slot and parsed the body, so requests over the global
+/// limit never have their body read.
+pub struct TenantRateLimiter {
+    limiter: KeyedRateLimiter,
+}
+
+impl TenantRateLimiter {
+    pub fn new(config: &IngressConfig) -> Self {
+        Self {
+            limiter: KeyedRateLimiter::new(&config.tenant_rate_limit),
+        }
+    }
+
+    /// The 429 response when the tenant of `webhook` is over its rate
+    pub fn check(&self, webhook: &InboundWebhook) -> Option<Response> {
+        let tenant = webhook.tenant.as_deref()?;
+        let retry_after = self.limiter.check(tenant).err()?;
+        Some(reject(Some(&webhook.request_id), IngressRejection::TenantRateLimited, tenant, Some(retry_after)))
+    }
+}
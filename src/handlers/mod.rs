@@ -1,3 +1,7 @@
 pub mod webhook_server;
+pub mod admin;
+pub mod access_log;
+pub mod ingress;
+pub mod tls;
 
 pub use webhook_server::{WebhookServer, WebhookServerTrait};
\ No newline at end of file
@@ -0,0 +1,235 @@
+use std::fs;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, SystemTime};
+
+use axum::extract::ConnectInfo;
+use axum::Router;
+use hyper::body::Incoming;
+use hyper::service::service_fn;
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use hyper_util::server::conn::auto;
+use rustls::server::WebPkiClientVerifier;
+use rustls::RootCertStore;
+use tokio::net::TcpListener;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tokio::time::{interval, MissedTickBehavior};
+use tokio_rustls::TlsAcceptor;
+use tower::Service;
+
+use crate::config::{TlsConfig, TlsVersion};
+use crate::providers::metrics::{self, Metrics};
+use crate::providers::StructuredLogger;
+use crate::utils::error::{AppError, Result};
+use crate::utils::pem::{load_certs, load_key};
+
+/// Clients that do not finish the handshake in time are dropped
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Pause after an accept error such as too many open files, as `axum::serve` does
+const ACCEPT_ERROR_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Build the rustls config from the certificate, key and client CA files
+pub fn build_server_config(config: &TlsConfig) -> Result<rustls::ServerConfig> {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let versions = match config.min_version {
+        TlsVersion::Tls12 => vec![&rustls::version::TLS13, &rustls::version::TLS12],
+        TlsVersion::Tls13 => vec![&rustls::version::TLS13],
+    };
+    let builder = rustls::ServerConfig::builder_with_provider(Arc::clone(&provider))
+        .with_protocol_versions(&versions)
+        .map_err(|e| AppError::configuration(format!("Invalid TLS settings: {}", e)))?;
+
+    let builder = if config.client_ca_path.is_empty() {
+        builder.with_no_client_auth()
+    } else {
+        let mut roots = RootCertStore::empty();
+        for cert in load_certs(&config.client_ca_path)? {
+            roots
+                .add(cert)
+                .map_err(|e| AppError::configuration(format!("Invalid client CA in {}: {}", config.client_ca_path, e)))?;
+        }
+        let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider);
+        let verifier = if config.require_client_cert {
+            verifier
+        } else {
+            verifier.allow_unauthenticated()
+        };
+        builder.with_client_cert_verifier(
+            verifier
+                .build()
+                .map_err(|e| AppError::configuration(format!("Invalid client certificate settings: {}", e)))?,
+        )
+    };
+
+    let mut server = builder
+        .with_single_cert(load_certs(&config.cert_path)?, load_key(&config.key_path)?)
+        .map_err(|e| AppError::configuration(format!("Invalid TLS certificate or key: {}", e)))?;
+    server.alpn_protocols = if config.alpn_h2 {
+        vec![b"h2".to_vec(), b"http/1.1".to_vec()]
+    } else {
+        vec![b"http/1.1".to_vec()]
+    };
+    Ok(server)
+}
+
+/// Current rustls config of the listener. New connections pick up a reloaded
+/// certificate; connections already open keep the one they started with.
+pub struct TlsReloader {
+    config: TlsConfig,
+    current: RwLock<Arc<rustls::ServerConfig>>,
+    stamps: Mutex<Vec<Option<SystemTime>>>,
+}
+
+impl TlsReloader {
+    pub fn new(config: &TlsConfig) -> Result<Self> {
+        let reloader = Self {
+            config: config.clone(),
+            current: RwLock::new(Arc::new(build_server_config(config)?)),
+            stamps: Mutex::new(Vec::new()),
+        };
+        *reloader.stamps.lock().unwrap_or_else(|e| e.into_inner()) = reloader.file_stamps();
+        Ok(reloader)
+    }
+
+    pub fn acceptor(&self) -> TlsAcceptor {
+        TlsAcceptor::from(self.server_config())
+    }
+
+    pub fn server_config(&self) -> Arc<rustls::ServerConfig> {
+        Arc::clone(&self.current.read().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    /// Rebuild the config when a file changed. On error the previous config
+    /// stays active and the change is retried on the next call.
+    pub fn reload_if_changed(&self) -> Result<bool> {
+        let stamps = self.file_stamps();
+        let mut seen = self.stamps.lock().unwrap_or_else(|e| e.into_inner());
+        if *seen == stamps {
+            return Ok(false);
+        }
+
+        let server = build_server_config(&self.config)?;
+        *self.current.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(server);
+        *seen = stamps;
+        Ok(true)
+    }
+
+    /// Check the files every `reload_interval_secs`
+    pub fn spawn_watcher(self: &Arc<Self>) -> JoinHandle<()> {
+        let reloader = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut timer = interval(Duration::from_secs(reloader.config.reload_interval_secs.max(1)));
+            timer.set_missed_tick_behavior(MissedTickBehavior::Skip);
+            timer.tick().await;
+
+            loop {
+                timer.tick().await;
+                match reloader.reload_if_changed() {
+                    Ok(true) => {
+                        Metrics::global().increment(metrics::TLS_CERT_RELOADED);
+                        StructuredLogger::log_info(
+                            "TLS certificate reloaded",
+                            None,
+                            None,
+                            Some(serde_json::json!({ "cert_path": reloader.config.cert_path })),
+                        );
+                    }
+                    Ok(false) => {}
+                    Err(e) => {
+                        Metrics::global().increment(metrics::TLS_RELOAD_FAILED);
+                        StructuredLogger::log_error(
+                            &format!("TLS reload failed, keeping the previous certificate: {}", e),
+                            None,
+                            None,
+                        );
+                    }
+                }
+            }
+        })
+    }
+
+    fn file_stamps(&self) -> Vec<Option<SystemTime>> {
+        [&self.config.cert_path, &self.config.key_path, &self.config.client_ca_path]
+            .into_iter()
+            .filter(|path| !path.is_empty())
+            .map(|path| fs::metadata(path).and_then(|m| m.modified()).ok())
+            .collect()
+    }
+}
+
+/// Accept TLS connections and serve `app` over HTTP/1.1 or HTTP/2 (ALPN)
+/// until `shutdown` resolves, then wait for open connections to finish
+pub async fn serve_tls<F>(listener: TcpListener, app: Router, tls: Arc<TlsReloader>, shutdown: F) -> Result<()>
+where
+    F: Future<Output = ()> + Send,
+{
+    let (close_tx, close_rx) = watch::channel(());
+    tokio::pin!(shutdown);
+
+    loop {
+        let (stream, peer) = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok(accepted) => accepted,
+                // Koneksi yang putus sebelum di-accept tidak perlu ditunggu
+                Err(e) if is_connection_error(&e) => continue,
+                Err(e) => {
+                    StructuredLogger::log_error(&format!("Failed to accept connection: {}", e), None, None);
+                    tokio::select! {
+                        _ = tokio::time::sleep(ACCEPT_ERROR_BACKOFF) => continue,
+                        _ = &mut shutdown => break,
+                    }
+                }
+            },
+            _ = &mut shutdown => break,
+        };
+
+        let acceptor = tls.acceptor();
+        let app = app.clone();
+        let mut close_rx = close_rx.clone();
+
+        tokio::spawn(async move {
+            let stream = match tokio::time::timeout(HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+                Ok(Ok(stream)) => stream,
+                Ok(Err(e)) => return handshake_failed(peer, &e.to_string()),
+                Err(_) => return handshake_failed(peer, "timed out"),
+            };
+
+            let service = service_fn(move |mut request: hyper::Request<Incoming>| {
+                request.extensions_mut().insert(ConnectInfo(peer));
+                app.clone().call(request)
+            });
+            let builder = auto::Builder::new(TokioExecutor::new());
+            let connection = builder.serve_connection_with_upgrades(TokioIo::new(stream), service);
+            tokio::pin!(connection);
+
+            tokio::select! {
+                _ = connection.as_mut() => {}
+                _ = close_rx.changed() => {
+                    connection.as_mut().graceful_shutdown();
+                    let _ = connection.await;
+                }
+            }
+        });
+    }
+
+    // Request yang sedang berjalan diselesaikan dulu
+    let _ = close_tx.send(());
+    drop(close_rx);
+    close_tx.closed().await;
+    Ok(())
+}
+
+fn is_connection_error(error: &std::io::Error) -> bool {
+    matches!(
+        error.kind(),
+        std::io::ErrorKind::ConnectionRefused | std::io::ErrorKind::ConnectionAborted | std::io::ErrorKind::ConnectionReset
+    )
+}
+
+fn handshake_failed(peer: SocketAddr, error: &str) {
+    Metrics::global().increment(metrics::TLS_HANDSHAKE_FAILED);
+    StructuredLogger::log_warning(&format!("TLS handshake with {} failed: {}", peer, error), None, None);
+}
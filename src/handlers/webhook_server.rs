@@ -1,24 +1,42 @@
 use async_trait::async_trait;
 use axum::{
     extract::{Request, State},
-    http::{HeaderMap, StatusCode},
-    response::{IntoResponse, Json},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Json, Response},
     routing::{get, post},
-    Router,
+    Extension, Router,
 };
 use serde_json::Value;
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::future::Future;
+use std::time::{Duration, Instant};
 use tokio::signal;
-use tracing::info;
-use uuid::Uuid;
+use tokio::sync::watch;
+use tracing::{field, info, Instrument};
 
 use crate::config::ServerConfig;
-use crate::services::{WebhookProcessorTrait, TelegramAlertService};
+use crate::services::alert_template::{record_alert_sample, AlertContext};
+use crate::services::backpressure::Backpressure;
+use crate::services::status_coalescer::CoalesceDecision;
+use crate::services::{FlowReplyDecoder, GuardedWebhook, ResponsePolicy, StatusCoalescer, StatusOrderGuard, WebhookProcessorTrait};
 use crate::utils::error::{AppError, Result};
-use crate::utils::request_id::extract_request_id;
-use crate::utils::json::{is_dr_payload, is_inbound_flow_payload};
+use crate::models::{InboundWebhook, PayloadClassification};
+use crate::utils::request_id::RequestId;
+use crate::providers::access_log::{AccessLogger, RequestClassification};
+use crate::providers::audit_log::AuditLogger;
+use crate::providers::dead_letter::DeadLetterLog;
 use crate::providers::logging::StructuredLogger;
+use crate::providers::metrics::{self, Metrics};
+use crate::providers::otlp::{set_remote_parent, TraceContext, TRACEPARENT_HEADER};
+use crate::handlers::access_log::AccessLogLayer;
+use crate::handlers::ingress::{header_pairs, IpFilterLayer, TenantRateLimiter};
+use crate::handlers::tls::{serve_tls, TlsReloader};
+use crate::handlers::admin;
+
+/// Backpressure key of webhooks without `metadata.phone_number_id`
+const UNKNOWN_TENANT: &str = "unknown";
 
 #[async_trait]
 pub trait WebhookServerTrait {
@@ -31,99 +49,195 @@ pub struct AppState {
     pub processor: Arc<dyn WebhookProcessorTrait + Send + Sync>,
     pub app_config: crate::config::AppConfig,
     pub server_config: ServerConfig,
+    pub flow_reply: Arc<FlowReplyDecoder>,
+    pub status_order: Arc<StatusOrderGuard>,
+    pub coalescer: Arc<StatusCoalescer>,
+    pub backpressure: Arc<Backpressure>,
+    pub response_policy: Arc<ResponsePolicy>,
+    pub tenant_rate_limit: Option<Arc<TenantRateLimiter>>,
 }
 
+/// Longest wait for in-flight requests once `shutdown` is called
+const SERVER_DRAIN_TIMEOUT: Duration = Duration::from_secs(30);
+
 #[derive(Clone)]
 pub struct WebhookServer {
     config: ServerConfig,
     processor: Arc<dyn WebhookProcessorTrait + Send + Sync>,
     app_config: crate::config::AppConfig,
+    coalescer: Arc<StatusCoalescer>,
+    /// Set by `shutdown`, stops the listener
+    stop: Arc<watch::Sender<bool>>,
+    /// True while `start` is serving
+    serving: Arc<watch::Sender<bool>>,
 }
 
 impl WebhookServer {
-    pub fn new(config: ServerConfig, processor: Arc<dyn WebhookProcessorTrait + Send + Sync>, app_config: crate::config::AppConfig) -> Self {
-        Self { 
+    pub fn new(config: ServerConfig, processor: Arc<dyn WebhookProcessorTrait + Send + Sync>, app_config: crate::config::AppConfig) -> Result<Self> {
+        let coalesce = &app_config.status_coalesce;
+        let dead_letter = if coalesce.enabled && !coalesce.dead_letter_file.is_empty() {
+            Some(DeadLetterLog::with_file(&app_config, &coalesce.dead_letter_file)?)
+        } else {
+            None
+        };
+        let coalescer = Arc::new(StatusCoalescer::new(coalesce.clone(), processor.clone(), dead_letter));
+        Ok(Self { 
             config, 
             processor, 
             app_config,
+            coalescer,
+            stop: Arc::new(watch::channel(false).0),
+            serving: Arc::new(watch::channel(false).0),
+        })
+    }
+
+    /// Resolves once `shutdown` was called
+    fn stop_requested(&self) -> impl Future<Output = ()> + Send + 'static {
+        let mut stop = self.stop.subscribe();
+        async move {
+            let _ = stop.wait_for(|stop| *stop).await;
         }
     }
 
-    fn create_router(&self) -> Router {
+    fn create_router(&self) -> Result<Router> {
         let app_state = AppState {
             processor: self.processor.clone(),
             app_config: self.app_config.clone(),
             server_config: self.config.clone(),
+            flow_reply: Arc::new(FlowReplyDecoder::new(self.app_config.flow_reply.clone())),
+            status_order: Arc::new(StatusOrderGuard::new(self.app_config.status_order.clone())),
+            coalescer: self.coalescer.clone(),
+            backpressure: Arc::new(Backpressure::new(self.app_config.backpressure.clone())),
+            response_policy: Arc::new(ResponsePolicy::new(&self.app_config)?),
+            tenant_rate_limit: self
+                .app_config
+                .ingress
+                .tenant_rate_limit
+                .enabled
+                .then(|| Arc::new(TenantRateLimiter::new(&self.app_config.ingress))),
         };
 
-        Router::new()
+        let mut router = Router::new()
             .route(&self.config.webhook_path, post(webhook_handler))
-            .route(&self.config.webhook_path, get(health_check_handler))
-            .with_state(app_state)
+            .route(&self.config.webhook_path, get(health_check_handler));
+
+        // Allowlist dan rate limit IP hanya untuk route webhook, admin punya aturan sendiri
+        let ingress = &self.app_config.ingress;
+        if !ingress.allowlist.is_empty() || ingress.ip_rate_limit.enabled {
+            router = router.route_layer(IpFilterLayer::new(ingress)?);
+        }
+
+        if self.app_config.admin.enabled {
+            router = router.nest(&self.app_config.admin.path_prefix, admin::admin_router(app_state.clone()));
+        }
+
+        // Access log di dalam request_span supaya request id sudah tersedia
+        if let Some(access_logger) = AccessLogger::global() {
+            router = router.layer(AccessLogLayer::new(access_logger));
+        }
+
+        Ok(router
+            .layer(middleware::from_fn(request_span))
+            .with_state(app_state))
     }
 
-    fn should_process_payload(&self, body: &str, request_id: &str) -> bool {
-        match serde_json::from_str::<serde_json::Value>(body) {
-            Ok(json) => {              
-                 // Check for DR (Delivery Receipt) payload
-                if is_dr_payload(&json) {
-                    StructuredLogger::log_info(
-                        "Detected DR payload",
-                        Some(request_id),
-                        Some(request_id),
-                        None,
-                    );
-                    return true;
-                }
-                  
-                // Check for Inbound Flow payload
-                if is_inbound_flow_payload(&json) {
-                    StructuredLogger::log_info(
-                        "Detected Inbound Flow payload",
-                        Some(request_id),
-                        Some(request_id),
-                        None,
-                    );
-                    return true;
-                }
-                
+    fn should_process_payload(&self, webhook: &InboundWebhook) -> bool {
+        let request_id = webhook.request_id.as_str();
+        match webhook.classification {
+            PayloadClassification::DeliveryReceipt => {
+                StructuredLogger::log_info(
+                    "Detected DR payload",
+                    Some(request_id),
+                    Some(request_id),
+                    None,
+                );
+            }
+            PayloadClassification::InboundFlow => {
+                StructuredLogger::log_info(
+                    "Detected Inbound Flow payload",
+                    Some(request_id),
+                    Some(request_id),
+                    None,
+                );
+            }
+            PayloadClassification::Unsupported => {
                 StructuredLogger::log_info(
                     "Payload does not match DR or Inbound Flow criteria",
                     Some(request_id),
                     Some(request_id),
                     None,
                 );
-                false
             }
-            Err(e) => {
-                let error_message = format!("Failed to parse JSON payload: {}", e);
-                
+            PayloadClassification::InvalidJson => {
+                let error_message = format!(
+                    "Failed to parse JSON payload: {}",
+                    webhook.parse_error.as_deref().unwrap_or_default()
+                );
+
                 StructuredLogger::log_error(
                     &error_message,
                     Some(request_id),
                     Some(request_id),
                 );
-                
-                // Send telegram alert for JSON parse failure
-                if let Ok(telegram_service) = TelegramAlertService::new(self.app_config.clone()) {
-                    telegram_service.send_error_alert(
-                        "Failed to parse JSON payload",
-                        Some(request_id)
-                    );
-                }
-                false
+
+                Metrics::global().increment(metrics::JSON_PARSE_ERROR);
+                record_alert_sample(
+                    metrics::JSON_PARSE_ERROR,
+                    AlertContext::new("Failed to parse JSON payload")
+                        .with_error(error_message)
+                        .with_request_id(Some(request_id)),
+                );
             }
         }
+
+        webhook.classification.should_forward()
     }
 }
 
+/// Run every request inside its own span so all logs carry its request id
+pub async fn request_span(mut request: Request, next: Next) -> Response {
+    let request_id = RequestId::generate();
+    let span = StructuredLogger::request_span(&request_id.0);
+    span.record("http.method", request.method().as_str());
+    span.record("http.route", request.uri().path());
+
+    // Lanjutkan trace dari Meta/pemanggil jika ada traceparent
+    if let Some(parent) = request
+        .headers()
+        .get(TRACEPARENT_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(TraceContext::parse_traceparent)
+    {
+        set_remote_parent(&span, &parent);
+    }
+    request.extensions_mut().insert(request_id);
+
+    let response = next.run(request).instrument(span.clone()).await;
+    span.record("http.status_code", response.status().as_u16());
+    if response.status().is_server_error() {
+        span.record("otel.status_code", "error");
+    }
+    response
+}
+
 // Axum handler functions
 pub async fn webhook_handler(
     State(state): State<AppState>,
+    Extension(RequestId(request_id)): Extension<RequestId>,
     headers: HeaderMap,
     request: Request,
-) -> impl IntoResponse {
-    let request_id = format!("req-{}", Uuid::new_v4());
+) -> Response {
+    Metrics::global().increment(metrics::WEBHOOK_RECEIVED);
+    let started = Instant::now();
+
+    // Slot global diambil sebelum body dibaca, request berlebih langsung ditolak
+    let _global_permit = match state.backpressure.try_acquire_global() {
+        Ok(permit) => permit,
+        Err(reason) => {
+            state.backpressure.record_rejection(reason, None, &request_id);
+            return overloaded_response(request_id, state.backpressure.retry_after_secs(), reason.as_str());
+        }
+    };
 
     StructuredLogger::log_info(
         "Received webhook request",
@@ -136,8 +250,8 @@ pub async fn webhook_handler(
         })),
     );
 
-    // Extract the body
-    let body = match axum::body::to_bytes(request.into_body(), usize::MAX).await {
+    // Body di-parse sekali, semua tahap berikutnya memakai context yang sama
+    let body = match axum::body::to_bytes(request.into_body(), state.app_config.ingress.max_body_bytes).await {
         Ok(bytes) => bytes,
         Err(e) => {
             StructuredLogger::log_error(
@@ -147,93 +261,192 @@ pub async fn webhook_handler(
             );
             return (
                 StatusCode::BAD_REQUEST,
+                Extension(RequestId(request_id)),
                 Json(serde_json::json!({
                     "StatusCode": "06",
                     "StatusDesc": "Bad Request"
                 }))
-            );
+            ).into_response();
         }
     };
+    let webhook = InboundWebhook::new(header_pairs(&headers), body);
+    let extracted_request_id = webhook.request_id.clone();
+    StructuredLogger::record_request_id(&extracted_request_id);
+    if let Some(tenant) = &webhook.tenant {
+        StructuredLogger::record_tenant(tenant);
+    }
+
+    if let Some(response) = state.tenant_rate_limit.as_ref().and_then(|limiter| limiter.check(&webhook)) {
+        audit_webhook(&webhook, "rate_limited", response.status(), &Value::Null, started);
+        return classified(response, &webhook, "rate_limited");
+    }
 
-    let body_str = String::from_utf8_lossy(&body);
-    let extracted_request_id = extract_request_id(&body_str);
+    let _tenant_permit = match state.backpressure.acquire_tenant(webhook.tenant.as_deref().unwrap_or(UNKNOWN_TENANT)).await {
+        Ok(permit) => permit,
+        Err(reason) => {
+            state.backpressure.record_rejection(reason, webhook.tenant.as_deref(), &extracted_request_id);
+            let retry_after = state.backpressure.retry_after_secs();
+            let response = overloaded_response(extracted_request_id, retry_after, reason.as_str());
+            audit_webhook(&webhook, "rejected", StatusCode::SERVICE_UNAVAILABLE, &Value::Null, started);
+            return classified(response, &webhook, "rejected");
+        }
+    };
 
     // Check if payload should be processed
     let server = WebhookServer {
         config: state.server_config.clone(),
         processor: state.processor.clone(),
         app_config: state.app_config.clone(),
+        coalescer: state.coalescer.clone(),
+        stop: Arc::new(watch::channel(false).0),
+        serving: Arc::new(watch::channel(false).0),
+    };
+
+    let classify_span = tracing::info_span!(
+        "classify_payload",
+        classification = webhook.classification.as_str(),
+        forward = field::Empty
+    );
+    let should_process = classify_span.in_scope(|| server.should_process_payload(&webhook));
+    classify_span.record("forward", should_process);
+    drop(classify_span);
+
+    // Flow reply di-decode dan status DR dicek urutannya sebelum signing, supaya body yang dikirim sama dengan yang ditandatangani
+    let webhook = match webhook.classification {
+        PayloadClassification::InboundFlow => state.flow_reply.apply(webhook),
+        PayloadClassification::DeliveryReceipt => match state.status_order.apply(webhook) {
+            GuardedWebhook::Forward(webhook) => webhook,
+            GuardedWebhook::Suppressed(webhook) => {
+                StructuredLogger::log_info(
+                    "Suppress out-of-order status, not sent to client",
+                    Some(&extracted_request_id),
+                    Some(&extracted_request_id),
+                    None,
+                );
+                let response = serde_json::json!({
+                    "StatusCode": "00",
+                    "StatusDesc": "Success"
+                });
+                audit_webhook(&webhook, "suppressed", StatusCode::OK, &response, started);
+                let response = (StatusCode::OK, Extension(RequestId(extracted_request_id)), Json(response)).into_response();
+                return classified(response, &webhook, "suppressed");
+            }
+        },
+        _ => webhook,
     };
 
-    if !server.should_process_payload(&body_str, &extracted_request_id) {
+    // DR yang ditahan coalescer di-ack sekarang, status terakhir dikirim saat window selesai
+    let webhook = match state.coalescer.offer(webhook) {
+        CoalesceDecision::Forward(webhook) => webhook,
+        CoalesceDecision::Held(webhook) => {
+            let response = serde_json::json!({
+                "StatusCode": "00",
+                "StatusDesc": "Success"
+            });
+            audit_webhook(&webhook, "held", StatusCode::OK, &response, started);
+            let response = (StatusCode::OK, Extension(RequestId(extracted_request_id)), Json(response)).into_response();
+            return classified(response, &webhook, "held");
+        }
+    };
+
+    if !should_process {
         StructuredLogger::log_info(
             "Ignore send payload to client",
             Some(&extracted_request_id),
             Some(&extracted_request_id),
             None,
         );
-        return (
-            StatusCode::OK,
-            Json(serde_json::json!({
-                "StatusCode": "00",
-                "StatusDesc": "Success"
-            }))
-        );
+        let response = serde_json::json!({
+            "StatusCode": "00",
+            "StatusDesc": "Success"
+        });
+        audit_webhook(&webhook, "ignored", StatusCode::OK, &response, started);
+        let response = (StatusCode::OK, Extension(RequestId(extracted_request_id)), Json(response)).into_response();
+        return classified(response, &webhook, "ignored");
     }
 
-    // Create webhook message for processing
-    let webhook_data = crate::models::WebhookMessage {
-        headers: headers.iter()
-            .map(|(k, v)| (k.as_str().to_string(), v.to_str().unwrap_or("").to_string()))
-            .collect(),
-        body: body_str.to_string(),
-    };
-
     // Process the webhook
-    match state.processor.process_webhook(webhook_data, &extracted_request_id).await {
-        Ok(webhook_response) => {
-            let http_status = StatusCode::from_u16(webhook_response.http_status)
-                .unwrap_or(StatusCode::BAD_GATEWAY);
-            
-            StructuredLogger::log_info(
-                &format!("Webhook processed with HTTP status {}", webhook_response.http_status),
-                Some(&extracted_request_id),
-                Some(&extracted_request_id),
-                None,
-            );
+    let result = state.processor.process_webhook(&webhook).await;
+    match &result {
+        Ok(webhook_response) => StructuredLogger::log_info(
+            &format!("Webhook processed with HTTP status {}", webhook_response.http_status),
+            Some(&extracted_request_id),
+            Some(&extracted_request_id),
+            None,
+        ),
+        Err(e) => StructuredLogger::log_error(
+            &format!("Failed to process webhook: {}", e),
+            Some(&extracted_request_id),
+            Some(&extracted_request_id),
+        ),
+    }
 
-            // Parse the response body as JSON if possible
-            let response_json: Value = serde_json::from_str(&webhook_response.body)
-                .unwrap_or_else(|_| serde_json::json!({
-                    "StatusCode": "06",
-                    "StatusDesc": webhook_response.body
-                }));
+    // Status dan body untuk Meta ditentukan meta_response, bukan langsung dari Permata
+    let reply = state.response_policy.respond(&webhook, &result);
+    let http_status = StatusCode::from_u16(reply.http_status).unwrap_or(StatusCode::BAD_GATEWAY);
 
-            (http_status, Json(response_json))
-        }
-        Err(e) => {
-            StructuredLogger::log_error(
-                &format!("Failed to process webhook: {}", e),
-                Some(&extracted_request_id),
-                Some(&extracted_request_id),
-            );
+    audit_webhook(&webhook, reply.outcome, http_status, &reply.body, started);
+    let mut response = (http_status, Extension(RequestId(extracted_request_id)), Json(reply.body)).into_response();
+    if reply.retry_after {
+        set_retry_after(&mut response, state.backpressure.retry_after_secs());
+    }
+    classified(response, &webhook, reply.outcome)
+}
 
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(serde_json::json!({
-                    "StatusCode": "06",
-                    "StatusDesc": e.to_string()
-                }))
-            )
-        }
+/// 503 dengan Retry-After supaya Meta mundur dan mengirim ulang nanti
+fn overloaded_response(request_id: String, retry_after_secs: u64, reason: &str) -> Response {
+    let mut response = (
+        StatusCode::SERVICE_UNAVAILABLE,
+        Extension(RequestId(request_id)),
+        Json(serde_json::json!({
+            "StatusCode": "06",
+            "StatusDesc": format!("Service Unavailable: {}", reason)
+        })),
+    )
+        .into_response();
+    set_retry_after(&mut response, retry_after_secs);
+    response
+}
+
+fn set_retry_after(response: &mut Response, retry_after_secs: u64) {
+    response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(retry_after_secs));
+}
+
+/// Label the access log line with the payload type and outcome
+fn classified(mut response: Response, webhook: &InboundWebhook, outcome: &str) -> Response {
+    response.extensions_mut().insert(RequestClassification(format!(
+        "{}:{}",
+        webhook.classification.as_str(),
+        outcome
+    )));
+    response
+}
+
+/// Catat payload masuk dan response ke audit log jika diaktifkan
+fn audit_webhook(
+    webhook: &InboundWebhook,
+    outcome: &str,
+    http_status: StatusCode,
+    response: &Value,
+    started: Instant,
+) {
+    if let Some(audit) = AuditLogger::global() {
+        audit.record_webhook(
+            &webhook.request_id,
+            &webhook.body_str(),
+            outcome,
+            http_status.as_u16(),
+            response,
+            started.elapsed(),
+        );
     }
 }
 
 pub async fn health_check_handler(
     State(_state): State<AppState>,
+    Extension(RequestId(request_id)): Extension<RequestId>,
 ) -> impl IntoResponse {
-    let request_id = format!("req-{}", Uuid::new_v4());
-    
+
     StructuredLogger::log_info(
         "Health check request",
         Some(&request_id),
@@ -257,7 +470,13 @@ impl WebhookServerTrait for WebhookServer {
             .parse()
             .map_err(|e| AppError::configuration(format!("Invalid server address: {}", e)))?;
 
-        let app = self.create_router();
+        let app = self.create_router()?;
+        // Sertifikat yang rusak harus gagal saat startup, bukan saat handshake pertama
+        let tls = if self.config.tls.enabled {
+            Some(Arc::new(TlsReloader::new(&self.config.tls)?))
+        } else {
+            None
+        };
 
         info!("Webhook server listening on {}", addr);
         StructuredLogger::log_info(
@@ -266,7 +485,8 @@ impl WebhookServerTrait for WebhookServer {
             None,
             Some(serde_json::json!({
                 "address": addr.to_string(),
-                "webhook_path": self.config.webhook_path
+                "webhook_path": self.config.webhook_path,
+                "tls": tls.is_some()
             })),
         );
 
@@ -274,12 +494,21 @@ impl WebhookServerTrait for WebhookServer {
             .await
             .map_err(|e| AppError::configuration(format!("Failed to bind to address {}: {}", addr, e)))?;
 
-        axum::serve(listener, app)
-            .with_graceful_shutdown(shutdown_signal())
-            .await
-            .map_err(|e| AppError::error(format!("Server error: {}", e)))?;
+        self.serving.send_replace(true);
+        let served = if let Some(tls) = tls {
+            let watcher = tls.spawn_watcher();
+            let served = serve_tls(listener, app, tls, self.stop_requested()).await;
+            watcher.abort();
+            served
+        } else {
+            axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+                .with_graceful_shutdown(self.stop_requested())
+                .await
+                .map_err(|e| AppError::error(format!("Server error: {}", e)))
+        };
+        self.serving.send_replace(false);
 
-        Ok(())
+        served
     }
 
     async fn shutdown(&self) -> Result<()> {
@@ -289,11 +518,26 @@ impl WebhookServerTrait for WebhookServer {
             None,
             None,
         );
+        self.stop.send_replace(true);
+
+        // Request yang masih berjalan diselesaikan dulu, receipt yang mereka tahan ikut di-flush
+        let mut serving = self.serving.subscribe();
+        if tokio::time::timeout(SERVER_DRAIN_TIMEOUT, serving.wait_for(|serving| !*serving)).await.is_err() {
+            StructuredLogger::log_warning(
+                &format!("In-flight requests not finished within {}s", SERVER_DRAIN_TIMEOUT.as_secs()),
+                None,
+                None,
+            );
+        }
+
+        // Status yang masih ditahan dikirim sebelum processor dimatikan
+        self.coalescer.flush().await;
         Ok(())
     }
 }
 
-async fn shutdown_signal() {
+/// Ctrl+C or SIGTERM, the signal `main` waits for before the shutdown sequence
+pub async fn shutdown_signal() {
     let ctrl_c = async {
         signal::ctrl_c()
             .await
@@ -1,26 +1,39 @@
 use anyhow::Result;
 use std::sync::Arc;
-use tokio::signal;
+use std::time::Duration;
 use tracing::info;
 
 use webhook_gateway::{
     config::AppConfig,
-    services::{WebhookProcessor, WebhookProcessorTrait},
-    handlers::{WebhookServer, WebhookServerTrait},
-    providers::StructuredLogger,
+    services::{AlertRuleEngine, IncidentTracker, PartitionedDispatcher, WebhookProcessor, WebhookProcessorTrait},
+    handlers::{webhook_server::shutdown_signal, WebhookServer, WebhookServerTrait},
+    providers::{AccessLogger, AuditLogger, HttpClientFactory, StructuredLogger},
 };
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let config = AppConfig::load()?;
     
-    StructuredLogger::init("info", Some(config.logger.clone()))?;
+    StructuredLogger::init_with_otlp(&config.logger.level, Some(config.logger.clone()), &config.otlp)?;
+    AuditLogger::init(&config)?;
+    AccessLogger::init(&config)?;
     
     info!("Starting Webhook Gateway Application");
     
-    let webhook_processor = WebhookProcessor::new(config.clone())?;
-    let webhook_processor_arc: Arc<dyn WebhookProcessorTrait + Send + Sync> = Arc::new(webhook_processor.clone());
-    let webhook_server = WebhookServer::new(config.server.clone(), webhook_processor_arc, config.clone());
+    // Satu client dan connection pool per tujuan untuk semua service
+    let http_clients = HttpClientFactory::global(&config);
+    let webhook_processor = WebhookProcessor::with_http_clients(config.clone(), http_clients)?;
+    let mut webhook_processor_arc: Arc<dyn WebhookProcessorTrait + Send + Sync> = Arc::new(webhook_processor.clone());
+    let mut dispatcher = None;
+    if config.dispatch.enabled {
+        let partitioned = Arc::new(PartitionedDispatcher::start(&config.dispatch, webhook_processor_arc));
+        webhook_processor_arc = partitioned.clone();
+        dispatcher = Some(partitioned);
+    }
+    let webhook_server = WebhookServer::new(config.server.clone(), webhook_processor_arc, config.clone())?;
+
+    let alert_rule_engine = AlertRuleEngine::new(&config);
+    alert_rule_engine.start();
 
     StructuredLogger::log_info(
         "Webhook Gateway Application started successfully",
@@ -46,23 +59,8 @@ async fn main() -> Result<()> {
         }
     });
 
-    match signal::ctrl_c().await {
-        Ok(()) => {
-            StructuredLogger::log_info(
-                "Shutdown signal received, initiating graceful shutdown",
-                None,
-                None,
-                None,
-            );
-        }
-        Err(e) => {
-            StructuredLogger::log_error(
-                &format!("Failed to listen for shutdown signal: {}", e),
-                None,
-                None,
-            );
-        }
-    }
+    // Ctrl+C dan SIGTERM sama-sama menjalankan urutan shutdown di bawah
+    shutdown_signal().await;
 
     // Graceful shutdown sequence
     StructuredLogger::log_info(
@@ -72,7 +70,7 @@ async fn main() -> Result<()> {
         None,
     );
 
-    // Stop the webhook server
+    // Stop the webhook server, waits for in-flight requests
     if let Err(e) = webhook_server.shutdown().await {
         StructuredLogger::log_error(
             &format!("Error during webhook server shutdown: {}", e),
@@ -81,9 +79,33 @@ async fn main() -> Result<()> {
         );
     }
 
+    // Webhook yang masih antri di partisi sudah dijawab ke Meta, kirim dulu
+    if let Some(dispatcher) = &dispatcher {
+        let deadline = Duration::from_secs(config.dispatch.shutdown_deadline_secs);
+        if !dispatcher.shutdown(deadline).await {
+            StructuredLogger::log_warning(
+                "Some queued webhooks were not forwarded before shutdown",
+                None,
+                None,
+            );
+        }
+    }
+
     // Stop the webhook processor (including token scheduler)
     webhook_processor.shutdown().await;
 
+    alert_rule_engine.shutdown();
+
+    // Kirim alert yang masih di antrian sebelum exit
+    let deadline = Duration::from_secs(config.alerting.delivery.shutdown_deadline_secs);
+    if !IncidentTracker::global(&config).flush_alerts(deadline).await {
+        StructuredLogger::log_warning(
+            "Some alerts were not delivered before shutdown",
+            None,
+            None,
+        );
+    }
+
     // Cancel the server task
     server_handle.abort();
 
@@ -94,5 +116,14 @@ async fn main() -> Result<()> {
         None,
     );
 
+    // Tulis sisa log di antrian sebelum exit
+    if let Some(audit) = AuditLogger::global() {
+        audit.shutdown(Duration::from_secs(5));
+    }
+    if let Some(access_log) = AccessLogger::global() {
+        access_log.shutdown(Duration::from_secs(5));
+    }
+    StructuredLogger::shutdown();
+
     Ok(())
 }
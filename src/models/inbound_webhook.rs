@@ -0,0 +1,152 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::models::{WebhookMessage, WhatsAppWebhook};
+use crate::utils::json::{compact_json_str, extract_phone_number_id, is_dr_payload, is_inbound_flow_payload};
+use crate::utils::request_id::request_id_from_json;
+
+/// What kind of WhatsApp payload the gateway received
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PayloadClassification {
+    /// Delivery receipt, `statuses` or `error`
+    DeliveryReceipt,
+    /// Flow reply, `interactive.type = nfm_reply`
+    InboundFlow,
+    /// Valid JSON the gateway does not forward
+    Unsupported,
+    InvalidJson,
+}
+
+impl PayloadClassification {
+    pub fn classify(json: Option<&Value>) -> Self {
+        match json {
+            Some(json) if is_dr_payload(json) => Self::DeliveryReceipt,
+            Some(json) if is_inbound_flow_payload(json) => Self::InboundFlow,
+            Some(_) => Self::Unsupported,
+            None => Self::InvalidJson,
+        }
+    }
+
+    /// Same rules on the typed view
+    pub fn classify_whatsapp(webhook: &WhatsAppWebhook) -> Self {
+        if webhook.is_delivery_receipt() {
+            Self::DeliveryReceipt
+        } else if webhook.is_inbound_flow() {
+            Self::InboundFlow
+        } else {
+            Self::Unsupported
+        }
+    }
+
+    /// Only delivery receipts and flow replies are sent to Permata
+    pub fn should_forward(&self) -> bool {
+        matches!(self, Self::DeliveryReceipt | Self::InboundFlow)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::DeliveryReceipt => "delivery_receipt",
+            Self::InboundFlow => "inbound_flow",
+            Self::Unsupported => "unsupported",
+            Self::InvalidJson => "invalid_json",
+        }
+    }
+}
+
+/// One received webhook, parsed once in `webhook_handler` and shared by every
+/// later stage (classification, audit, forwarding, signing)
+#[derive(Debug, Clone)]
+pub struct InboundWebhook {
+    pub headers: HashMap<String, String>,
+    /// Body exactly as received
+    pub raw: Bytes,
+    /// `None` when the body is not valid JSON, see `parse_error`
+    pub json: Option<Value>,
+    /// Typed view of `json`, `None` when a known field has an unexpected type
+    pub whatsapp: Option<WhatsAppWebhook>,
+    pub parse_error: Option<String>,
+    /// `req-{xid}`, `req-{id}` or a generated id
+    pub request_id: String,
+    /// WhatsApp phone number id (metadata.phone_number_id)
+    pub tenant: Option<String>,
+    pub classification: PayloadClassification,
+    /// Body without whitespace, the input of the Permata signature
+    pub compact_body: String,
+    pub received_at: DateTime<Utc>,
+}
+
+impl InboundWebhook {
+    pub fn new(headers: HashMap<String, String>, raw: Bytes) -> Self {
+        let (json, parse_error) = match serde_json::from_slice::<Value>(&raw) {
+            Ok(json) => (Some(json), None),
+            Err(e) => (None, Some(e.to_string())),
+        };
+
+        // Tampilan typed dipakai bila bisa, JSON path hanya untuk payload yang tidak cocok skema
+        let whatsapp = json.as_ref().and_then(WhatsAppWebhook::from_json);
+        let (request_id, tenant, classification) = match &whatsapp {
+            Some(webhook) => (
+                webhook
+                    .correlation_id()
+                    .map(|id| format!("req-{}", id))
+                    .unwrap_or_else(|| request_id_from_json(None)),
+                webhook.phone_number_id().map(|id| id.to_string()),
+                PayloadClassification::classify_whatsapp(webhook),
+            ),
+            None => (
+                request_id_from_json(json.as_ref()),
+                json.as_ref().and_then(extract_phone_number_id),
+                PayloadClassification::classify(json.as_ref()),
+            ),
+        };
+        let compact_body = compact_json_str(&String::from_utf8_lossy(&raw));
+
+        Self {
+            headers,
+            raw,
+            json,
+            whatsapp,
+            parse_error,
+            request_id,
+            tenant,
+            classification,
+            compact_body,
+            received_at: Utc::now(),
+        }
+    }
+
+    /// Same webhook with a rewritten payload, e.g. after flow reply decoding.
+    /// Body and signature input follow the new JSON, request id and tenant stay.
+    pub fn with_json(&self, json: Value) -> Self {
+        let raw = Bytes::from(serde_json::to_vec(&json).unwrap_or_else(|_| self.raw.to_vec()));
+        Self {
+            headers: self.headers.clone(),
+            compact_body: compact_json_str(&String::from_utf8_lossy(&raw)),
+            raw,
+            whatsapp: WhatsAppWebhook::from_json(&json),
+            json: Some(json),
+            parse_error: None,
+            request_id: self.request_id.clone(),
+            tenant: self.tenant.clone(),
+            classification: self.classification,
+            received_at: self.received_at,
+        }
+    }
+
+    /// Body as text, invalid UTF-8 is replaced
+    pub fn body_str(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(&self.raw)
+    }
+}
+
+impl From<WebhookMessage> for InboundWebhook {
+    fn from(message: WebhookMessage) -> Self {
+        Self::new(message.headers, Bytes::from(message.body))
+    }
+}
@@ -1,6 +1,12 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+pub mod inbound_webhook;
+pub mod whatsapp;
+
+pub use inbound_webhook::{InboundWebhook, PayloadClassification};
+pub use whatsapp::WhatsAppWebhook;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WebhookMessage {
     pub headers: HashMap<String, String>,
@@ -54,7 +60,7 @@ pub struct ProcessingResult {
 pub struct PermataWebhookResponse {
     #[serde(rename = "StatusCode")]
     pub status_code: String,
-    #[serde(rename = "StatusDesc")]
+    #[serde(rename = "StatusDesc", default)]
     pub status_desc: String,
 }
 
@@ -0,0 +1,422 @@
+//! Typed view of WhatsApp Cloud API webhooks. Every field is optional or
+//! defaulted and unknown fields are kept in `extra`, so new fields from Meta
+//! do not break parsing and nothing is lost when the payload is re-serialized.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Webhook as received by the gateway. Inbound Flow payloads wrap the Cloud
+/// API envelope in `data`, delivery receipts carry it at the top level.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WhatsAppWebhook {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub xid: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub object: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub entry: Vec<Entry>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Box<WhatsAppWebhook>>,
+    /// DR error message
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<Value>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub changes: Vec<Change>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Change {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<ChangeValue>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChangeValue {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub messaging_product: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<Metadata>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub contacts: Vec<Contact>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub messages: Vec<Message>,
+    /// `Some` as soon as the key exists, an empty list still marks a delivery receipt
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub statuses: Option<Vec<Status>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<WhatsAppError>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Metadata {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_phone_number: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phone_number_id: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Contact {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wa_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<Profile>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Message {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+    /// `text`, `interactive`, `image`, `location`, ...
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub message_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<TextContent>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interactive: Option<Interactive>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<Media>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<Location>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<MessageContext>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<WhatsAppError>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TextContent {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Interactive {
+    /// `nfm_reply`, `button_reply` or `list_reply`
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub interactive_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nfm_reply: Option<NfmReply>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub button_reply: Option<ReplyOption>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub list_reply: Option<ReplyOption>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Flow reply, `response_json` is a JSON document encoded as a string
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NfmReply {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_json: Option<Value>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Selected option of a `button_reply` or `list_reply`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReplyOption {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Media {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub caption: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Location {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latitude: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub longitude: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MessageContext {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Status {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// `sent`, `delivered`, `read` or `failed`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recipient_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conversation: Option<Conversation>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pricing: Option<Pricing>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<WhatsAppError>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Conversation {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin: Option<ConversationOrigin>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expiration_timestamp: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConversationOrigin {
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub origin_type: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Pricing {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub billable: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pricing_model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WhatsAppError {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_data: Option<Value>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl WhatsAppWebhook {
+    /// Typed view of an already parsed payload, `None` when a known field has
+    /// an unexpected type
+    pub fn from_json(json: &Value) -> Option<Self> {
+        Self::deserialize(json).ok()
+    }
+
+    /// Change values of the top level envelope
+    pub fn values(&self) -> impl Iterator<Item = &ChangeValue> {
+        self.entry
+            .iter()
+            .flat_map(|entry| entry.changes.iter())
+            .filter_map(|change| change.value.as_ref())
+    }
+
+    /// Change values of the envelope wrapped in `data`
+    pub fn data_values(&self) -> impl Iterator<Item = &ChangeValue> {
+        self.data.iter().flat_map(|data| data.values())
+    }
+
+    pub fn statuses(&self) -> impl Iterator<Item = &Status> {
+        self.values().flat_map(|value| value.statuses.iter().flatten())
+    }
+
+    /// Messages at the top level and inside `data`
+    pub fn messages(&self) -> impl Iterator<Item = &Message> {
+        self.values()
+            .chain(self.data_values())
+            .flat_map(|value| value.messages.iter())
+    }
+
+    /// `error` field or `entry.changes.value.statuses`
+    pub fn is_delivery_receipt(&self) -> bool {
+        self.error.is_some() || self.values().any(|value| value.statuses.is_some())
+    }
+
+    /// `data.entry.changes.value.messages.interactive.type = nfm_reply`
+    pub fn is_inbound_flow(&self) -> bool {
+        self.data_values()
+            .flat_map(|value| value.messages.iter())
+            .filter_map(|message| message.interactive.as_ref())
+            .any(|interactive| interactive.interactive_type.as_deref() == Some("nfm_reply"))
+    }
+
+    /// WhatsApp phone number id, used as the tenant of a request
+    pub fn phone_number_id(&self) -> Option<&str> {
+        self.values()
+            .chain(self.data_values())
+            .filter_map(|value| value.metadata.as_ref())
+            .find_map(|metadata| metadata.phone_number_id.as_deref())
+    }
+
+    /// One webhook per status or message, keeping the envelope (ids, entry id,
+    /// change field, metadata, contacts) of the item. A payload with a single
+    /// item, or none, is returned unchanged.
+    pub fn split_events(&self) -> Vec<Self> {
+        let mut outer = self.clone();
+        outer.data = None;
+        let mut events = outer.split_entries();
+
+        if let Some(data) = &self.data {
+            for data_event in data.split_entries() {
+                let mut event = self.clone();
+                event.entry.clear();
+                event.data = Some(Box::new(data_event));
+                events.push(event);
+            }
+        }
+
+        if events.len() <= 1 {
+            return vec![self.clone()];
+        }
+        events
+    }
+
+    /// Dedup key of a single-item webhook: `status:{wamid}:{status}` or `message:{wamid}`
+    pub fn event_key(&self) -> Option<String> {
+        if let Some(status) = self.statuses().next() {
+            return status
+                .id
+                .as_deref()
+                .map(|id| format!("status:{}:{}", id, status.status.as_deref().unwrap_or_default()));
+        }
+        self.messages()
+            .next()
+            .and_then(|message| message.id.as_deref())
+            .map(|id| format!("message:{}", id))
+    }
+
+    fn split_entries(&self) -> Vec<Self> {
+        let mut events = Vec::new();
+        for entry in &self.entry {
+            for change in &entry.changes {
+                let Some(value) = &change.value else {
+                    continue;
+                };
+                let single = |value: ChangeValue| {
+                    let mut change = change.clone();
+                    change.value = Some(value);
+                    let mut entry = entry.clone();
+                    entry.changes = vec![change];
+                    let mut event = self.clone();
+                    event.entry = vec![entry];
+                    event
+                };
+
+                for status in value.statuses.iter().flatten() {
+                    let mut item = value.clone();
+                    item.statuses = Some(vec![status.clone()]);
+                    item.messages.clear();
+                    events.push(single(item));
+                }
+                for message in &value.messages {
+                    let mut item = value.clone();
+                    item.statuses = None;
+                    item.messages = vec![message.clone()];
+                    events.push(single(item));
+                }
+            }
+        }
+        events
+    }
+
+    /// Customer the webhook is about: message sender, contact `wa_id`, status
+    /// recipient, then conversation id
+    pub fn customer_id(&self) -> Option<&str> {
+        let values = || self.values().chain(self.data_values());
+        self.messages()
+            .find_map(|message| message.from.as_deref())
+            .or_else(|| values().flat_map(|value| value.contacts.iter()).find_map(|contact| contact.wa_id.as_deref()))
+            .or_else(|| self.statuses().find_map(|status| status.recipient_id.as_deref()))
+            .or_else(|| {
+                self.statuses()
+                    .filter_map(|status| status.conversation.as_ref())
+                    .find_map(|conversation| conversation.id.as_deref())
+            })
+    }
+
+    /// `xid`, then `id`, ignoring empty values
+    pub fn correlation_id(&self) -> Option<&str> {
+        [self.xid.as_deref(), self.id.as_deref()]
+            .into_iter()
+            .flatten()
+            .find(|id| !id.is_empty())
+    }
+}
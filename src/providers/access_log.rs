@@ -0,0 +1,151 @@
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use chrono::{DateTime, Local, Utc};
+use serde_json::json;
+
+use crate::config::{AccessLogConfig, AccessLogFormat, AppConfig, LoggerConfig};
+use crate::providers::log_rotation::RotatingFileWriter;
+use crate::providers::log_writer::BackgroundLogWriter;
+use crate::utils::error::Result;
+
+pub const ACCESS_LOG_SUFFIX: &str = ".access.log";
+
+static ACCESS_LOGGER: OnceLock<AccessLogger> = OnceLock::new();
+
+/// Response extension a handler sets to label its request in the access log,
+/// e.g. `delivery_receipt:forwarded`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestClassification(pub String);
+
+/// One handled HTTP request
+#[derive(Debug, Clone)]
+pub struct AccessLogEntry {
+    pub remote_addr: Option<String>,
+    pub method: String,
+    pub path: String,
+    pub version: String,
+    pub status: u16,
+    pub body_size: Option<u64>,
+    pub latency: Duration,
+    pub request_id: Option<String>,
+    /// Set from the handler's `RequestClassification`
+    pub classification: Option<String>,
+    pub referer: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+impl AccessLogEntry {
+    /// Classification recorded by the handler, or the status class for routes
+    /// that do not record one
+    pub fn classification(&self) -> &str {
+        if let Some(classification) = &self.classification {
+            return classification;
+        }
+        match self.status {
+            100..=199 => "informational",
+            200..=299 => "success",
+            300..=399 => "redirect",
+            400..=499 => "client_error",
+            _ => "server_error",
+        }
+    }
+}
+
+/// Access log for every route, kept separate from the application log
+#[derive(Clone)]
+pub struct AccessLogger {
+    writer: Arc<BackgroundLogWriter>,
+    format: AccessLogFormat,
+    local_time: bool,
+}
+
+impl AccessLogger {
+    pub fn new(access_config: &AccessLogConfig, logger_config: &LoggerConfig) -> Result<Self> {
+        let file_config = LoggerConfig {
+            file_name: access_config.file_name.clone(),
+            max_backups: access_config.max_backups,
+            max_size: access_config.max_size,
+            max_age: access_config.max_age,
+            compress: access_config.compress,
+            ..logger_config.clone()
+        };
+
+        let rotating_writer = RotatingFileWriter::with_suffix(file_config, ACCESS_LOG_SUFFIX)?;
+        let writer = BackgroundLogWriter::start(logger_config.writer.clone(), Some(rotating_writer))?;
+
+        Ok(Self {
+            writer: Arc::new(writer),
+            format: access_config.format,
+            local_time: logger_config.local_time,
+        })
+    }
+
+    /// Start the global access log when `access_log.enabled` is set
+    pub fn init(config: &AppConfig) -> Result<()> {
+        if config.access_log.enabled && ACCESS_LOGGER.get().is_none() {
+            let _ = ACCESS_LOGGER.set(Self::new(&config.access_log, &config.logger)?);
+        }
+        Ok(())
+    }
+
+    /// `None` when the access log is disabled
+    pub fn global() -> Option<&'static AccessLogger> {
+        ACCESS_LOGGER.get()
+    }
+
+    pub fn record(&self, entry: &AccessLogEntry) {
+        let line = if self.local_time {
+            format_entry(self.format, entry, Local::now())
+        } else {
+            format_entry(self.format, entry, Utc::now())
+        };
+        self.writer.write_file(line + "\n");
+    }
+
+    pub fn flush(&self, timeout: Duration) -> bool {
+        self.writer.flush(timeout)
+    }
+
+    pub fn shutdown(&self, timeout: Duration) -> bool {
+        self.writer.shutdown(timeout)
+    }
+}
+
+/// Render one access log line in the configured format
+pub fn format_entry<Tz>(format: AccessLogFormat, entry: &AccessLogEntry, time: DateTime<Tz>) -> String
+where
+    Tz: chrono::TimeZone,
+    Tz::Offset: std::fmt::Display,
+{
+    match format {
+        AccessLogFormat::Json => json!({
+            "timestamp": time.format("%Y-%m-%d %H:%M:%S%.3f").to_string(),
+            "remote_addr": entry.remote_addr,
+            "method": entry.method,
+            "path": entry.path,
+            "status": entry.status,
+            "body_size": entry.body_size,
+            "latency_ms": entry.latency.as_secs_f64() * 1000.0,
+            "classification": entry.classification(),
+            "x-request-id": entry.request_id,
+            "user_agent": entry.user_agent,
+        })
+        .to_string(),
+        AccessLogFormat::Combined => format!(
+            "{} - - [{}] \"{} {} {}\" {} {} \"{}\" \"{}\" {:.3} {} {}",
+            entry.remote_addr.as_deref().unwrap_or("-"),
+            time.format("%d/%b/%Y:%H:%M:%S %z"),
+            entry.method,
+            entry.path,
+            entry.version,
+            entry.status,
+            entry.body_size.map(|size| size.to_string()).unwrap_or_else(|| "-".to_string()),
+            entry.referer.as_deref().unwrap_or("-"),
+            entry.user_agent.as_deref().unwrap_or("-"),
+            entry.latency.as_secs_f64() * 1000.0,
+            entry.classification(),
+            entry.request_id.as_deref().unwrap_or("-"),
+        ),
+    }
+}
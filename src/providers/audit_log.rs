@@ -0,0 +1,133 @@
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use chrono::{Local, Utc};
+use serde_json::{json, Value};
+
+use crate::config::{AppConfig, MaskStrategy};
+use crate::providers::log_rotation::RotatingFileWriter;
+use crate::providers::log_writer::BackgroundLogWriter;
+use crate::utils::error::{AppError, Result};
+use crate::utils::masking::{effective_mask_rules, PayloadMasker};
+
+pub const AUDIT_LOG_SUFFIX: &str = ".audit.log";
+
+static AUDIT_LOGGER: OnceLock<AuditLogger> = OnceLock::new();
+
+/// One Permata callback attempt, as recorded in the audit stream
+#[derive(Debug, Clone, Default)]
+pub struct PermataExchange<'a> {
+    pub attempt: u32,
+    pub signed_body: &'a str,
+    pub permata_timestamp: &'a str,
+    pub status_code: Option<u16>,
+    pub response_body: Option<&'a str>,
+    pub error: Option<&'a str>,
+    pub latency: Duration,
+}
+
+/// Append-only JSON lines stream of webhook payloads and Permata exchanges.
+/// Customer data is masked with the `audit.masking` rules before it is written.
+#[derive(Clone)]
+pub struct AuditLogger {
+    writer: Arc<BackgroundLogWriter>,
+    masker: PayloadMasker,
+    local_time: bool,
+}
+
+impl AuditLogger {
+    pub fn new(config: &AppConfig) -> Result<Self> {
+        let audit_config = &config.audit;
+        let rules = effective_mask_rules(audit_config);
+        // Hash tanpa salt bisa dicocokkan dengan daftar nomor telepon, jadi wajib ada
+        let hashes = rules.iter().any(|rule| rule.strategy == MaskStrategy::Hash);
+        if hashes && audit_config.hash_salt.as_deref().unwrap_or_default().is_empty() {
+            return Err(AppError::configuration(
+                "audit.hash_salt is required when an audit masking rule uses hash",
+            ));
+        }
+
+        let mut file_config = config.logger.clone();
+        file_config.file_name = audit_config.file_name.clone();
+
+        let rotating_writer = RotatingFileWriter::with_suffix(file_config, AUDIT_LOG_SUFFIX)?;
+        let writer = BackgroundLogWriter::start(config.logger.writer.clone(), Some(rotating_writer))?;
+
+        Ok(Self {
+            writer: Arc::new(writer),
+            masker: PayloadMasker::new(&rules, audit_config.hash_salt.clone()),
+            local_time: config.logger.local_time,
+        })
+    }
+
+    /// Start the global audit stream when `audit.enabled` is set
+    pub fn init(config: &AppConfig) -> Result<()> {
+        if config.audit.enabled && AUDIT_LOGGER.get().is_none() {
+            let _ = AUDIT_LOGGER.set(Self::new(config)?);
+        }
+        Ok(())
+    }
+
+    /// `None` when auditing is disabled
+    pub fn global() -> Option<&'static AuditLogger> {
+        AUDIT_LOGGER.get()
+    }
+
+    /// Inbound webhook and the response returned to the sender
+    pub fn record_webhook(
+        &self,
+        request_id: &str,
+        payload: &str,
+        outcome: &str,
+        http_status: u16,
+        response: &Value,
+        latency: Duration,
+    ) {
+        let mut response = response.clone();
+        self.masker.mask(&mut response);
+
+        self.write(json!({
+            "event": "webhook",
+            "request_id": request_id,
+            "outcome": outcome,
+            "payload": self.masker.mask_body(payload),
+            "http_status": http_status,
+            "response": response,
+            "latency_ms": latency.as_millis() as u64,
+        }));
+    }
+
+    /// Body and signature timestamp sent to Permata and what came back
+    pub fn record_permata_exchange(&self, request_id: &str, exchange: &PermataExchange<'_>) {
+        self.write(json!({
+            "event": "permata_exchange",
+            "request_id": request_id,
+            "attempt": exchange.attempt,
+            "signed_body": self.masker.mask_body(exchange.signed_body),
+            "permata_timestamp": exchange.permata_timestamp,
+            "status_code": exchange.status_code,
+            "response": exchange.response_body.map(|body| self.masker.mask_body(body)),
+            "error": exchange.error,
+            "latency_ms": exchange.latency.as_millis() as u64,
+        }));
+    }
+
+    pub fn flush(&self, timeout: Duration) -> bool {
+        self.writer.flush(timeout)
+    }
+
+    pub fn shutdown(&self, timeout: Duration) -> bool {
+        self.writer.shutdown(timeout)
+    }
+
+    fn write(&self, mut record: Value) {
+        let timestamp = if self.local_time {
+            Local::now().format("%Y-%m-%d %H:%M:%S%.3f").to_string()
+        } else {
+            Utc::now().format("%Y-%m-%d %H:%M:%S%.3f").to_string()
+        };
+        record["timestamp"] = json!(timestamp);
+
+        self.writer.write_file(format!("{}\n", record));
+    }
+}
@@ -0,0 +1,82 @@
+use std::io;
+use std::path::PathBuf;
+
+use chrono::{Local, Utc};
+use serde_json::{json, Value};
+
+use crate::config::{AppConfig, LoggerConfig};
+use crate::providers::log_rotation::RotatingFileWriter;
+
+pub const DEAD_LETTER_SUFFIX: &str = ".dead-letter.jsonl";
+
+/// One event that could not be delivered to Permata
+#[derive(Debug, Clone, Default)]
+pub struct DeadLetter<'a> {
+    /// Request id of the batched webhook
+    pub request_id: &'a str,
+    pub event_request_id: &'a str,
+    pub event_key: Option<&'a str>,
+    /// Event body as it would have been sent, unmasked so it can be replayed
+    pub payload: &'a str,
+    pub status_code: Option<u16>,
+    pub error: Option<&'a str>,
+}
+
+/// JSON lines file of undelivered events. Lines are written and fsynced before
+/// `record` returns, so an event is only acknowledged once it is on disk;
+/// backups are never pruned or compressed.
+pub struct DeadLetterLog {
+    writer: RotatingFileWriter,
+    local_time: bool,
+}
+
+impl DeadLetterLog {
+    pub fn new(config: &AppConfig) -> io::Result<Self> {
+        Self::with_file(config, &config.fan_out.dead_letter_file)
+    }
+
+    /// Same as `new` but written to `file_name` instead of `fan_out.dead_letter_file`
+    pub fn with_file(config: &AppConfig, file_name: &str) -> io::Result<Self> {
+        Self::open(&config.logger, file_name)
+    }
+
+    /// Dead letter file `file_name` in the log directory of `logger`
+    pub fn open(logger: &LoggerConfig, file_name: &str) -> io::Result<Self> {
+        let mut file_config = logger.clone();
+        file_config.file_name = file_name.to_string();
+        file_config.max_backups = 0;
+        file_config.max_age = 0;
+        // Kompresi menghapus file asli yang sudah di-fsync, backup dibiarkan apa adanya
+        file_config.compress = false;
+
+        Ok(Self {
+            writer: RotatingFileWriter::with_suffix(file_config, DEAD_LETTER_SUFFIX)?,
+            local_time: logger.local_time,
+        })
+    }
+
+    pub fn record(&self, letter: &DeadLetter<'_>) -> io::Result<()> {
+        let timestamp = if self.local_time {
+            Local::now().format("%Y-%m-%d %H:%M:%S%.3f").to_string()
+        } else {
+            Utc::now().format("%Y-%m-%d %H:%M:%S%.3f").to_string()
+        };
+        let payload = serde_json::from_str::<Value>(letter.payload)
+            .unwrap_or_else(|_| Value::String(letter.payload.to_string()));
+
+        let record = json!({
+            "timestamp": timestamp,
+            "request_id": letter.request_id,
+            "event_request_id": letter.event_request_id,
+            "event_key": letter.event_key,
+            "status_code": letter.status_code,
+            "error": letter.error,
+            "payload": payload,
+        });
+        self.writer.write_line_durable(format!("{}\n", record).as_bytes())
+    }
+
+    pub fn current_path(&self) -> PathBuf {
+        self.writer.current_path()
+    }
+}
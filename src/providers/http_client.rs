@@ -0,0 +1,247 @@
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+use reqwest::{Client, ClientBuilder, Proxy};
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::{verify_tls12_signature, verify_tls13_signature, CryptoProvider};
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{CertificateError, ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme};
+
+use crate::config::{AppConfig, HttpClientConfig, OutboundConfig, OutboundTargetConfig, TlsVersion};
+use crate::utils::error::{AppError, Result};
+use crate::utils::pem::{load_certs, load_key};
+
+static HTTP_CLIENTS: OnceLock<HttpClientFactory> = OnceLock::new();
+
+/// Outbound destinations, each with its own client and connection pool
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Destination {
+    /// Login and callback status calls to Permata
+    Permata,
+    Telegram,
+    Slack,
+}
+
+/// Hands out one shared `reqwest::Client` per destination. Clones of a
+/// client share its connection pool, so services get a clone injected
+/// instead of building their own.
+pub struct HttpClientFactory {
+    settings: HttpClientConfig,
+    request_timeout: Duration,
+    outbound: OutboundConfig,
+    clients: Mutex<HashMap<Destination, Client>>,
+}
+
+impl HttpClientFactory {
+    pub fn new(config: &AppConfig) -> Self {
+        Self {
+            settings: config.http_client.clone(),
+            request_timeout: Duration::from_secs(config.webclient.timeout),
+            outbound: config.outbound.clone(),
+            clients: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Process-wide factory, initialized from the first config it sees
+    pub fn global(config: &AppConfig) -> &'static HttpClientFactory {
+        HTTP_CLIENTS.get_or_init(|| Self::new(config))
+    }
+
+    /// Client of the destination, built on first use
+    pub fn client(&self, destination: Destination) -> Result<Client> {
+        let mut clients = self.clients.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(client) = clients.get(&destination) {
+            return Ok(client.clone());
+        }
+
+        let target = match destination {
+            Destination::Permata => &self.outbound.permata,
+            Destination::Telegram => &self.outbound.telegram,
+            Destination::Slack => &self.outbound.slack,
+        };
+        let client = build_client(&self.settings, self.request_timeout, target)?;
+        clients.insert(destination, client.clone());
+        Ok(client)
+    }
+}
+
+/// Client with the pool settings, the request timeout and the settings of one destination
+pub fn build_client(settings: &HttpClientConfig, request_timeout: Duration, target: &OutboundTargetConfig) -> Result<Client> {
+    let mut builder = Client::builder()
+        .timeout(request_timeout)
+        .connect_timeout(Duration::from_millis(settings.connect_timeout_ms))
+        .pool_max_idle_per_host(settings.pool_max_idle_per_host)
+        .pool_idle_timeout(Duration::from_secs(settings.pool_idle_timeout_secs));
+    if settings.read_timeout_secs > 0 {
+        builder = builder.read_timeout(Duration::from_secs(settings.read_timeout_secs));
+    }
+    if settings.tcp_keepalive_secs > 0 {
+        builder = builder.tcp_keepalive(Duration::from_secs(settings.tcp_keepalive_secs));
+    }
+    if !settings.http2 {
+        builder = builder.http1_only();
+    } else if settings.http2_keepalive_secs > 0 {
+        builder = builder
+            .http2_keep_alive_interval(Duration::from_secs(settings.http2_keepalive_secs))
+            .http2_keep_alive_while_idle(true);
+    }
+
+    Ok(configure(builder, target, settings.http2)?.build()?)
+}
+
+/// Apply the TLS, source address, proxy and DNS settings of a destination
+pub fn configure(mut builder: ClientBuilder, target: &OutboundTargetConfig, http2: bool) -> Result<ClientBuilder> {
+    if has_custom_tls(target) {
+        builder = builder.use_preconfigured_tls(tls_config(target, http2)?);
+    }
+
+    if !target.local_address.is_empty() {
+        let address: IpAddr = target.local_address.trim().parse().map_err(|_| {
+            AppError::configuration(format!("Invalid outbound local_address '{}'", target.local_address))
+        })?;
+        builder = builder.local_address(address);
+    }
+
+    if !target.interface.is_empty() {
+        builder = bind_interface(builder, &target.interface)?;
+    }
+
+    if !target.proxy.url.is_empty() {
+        let mut proxy = Proxy::all(&target.proxy.url)
+            .map_err(|e| AppError::configuration(format!("Invalid outbound proxy url: {}", e)))?;
+        if !target.proxy.username.is_empty() {
+            proxy = proxy.basic_auth(&target.proxy.username, &target.proxy.password);
+        }
+        builder = builder.proxy(proxy);
+    }
+
+    for (host, address) in &target.resolve {
+        builder = builder.resolve(host, resolve_address(host, address)?);
+    }
+
+    Ok(builder)
+}
+
+#[cfg(target_os = "linux")]
+fn bind_interface(builder: ClientBuilder, interface: &str) -> Result<ClientBuilder> {
+    Ok(builder.interface(interface))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn bind_interface(_builder: ClientBuilder, interface: &str) -> Result<ClientBuilder> {
+    Err(AppError::configuration(format!(
+        "Binding outbound calls to interface '{}' is only supported on Linux",
+        interface
+    )))
+}
+
+/// `ip` or `ip:port`; reqwest keeps the port of the URL either way
+fn resolve_address(host: &str, address: &str) -> Result<SocketAddr> {
+    let address = address.trim();
+    address
+        .parse::<SocketAddr>()
+        .or_else(|_| address.parse::<IpAddr>().map(|ip| SocketAddr::new(ip, 0)))
+        .map_err(|_| AppError::configuration(format!("Invalid resolve address '{}' for host {}", address, host)))
+}
+
+fn has_custom_tls(target: &OutboundTargetConfig) -> bool {
+    !target.client_cert_path.is_empty()
+        || !target.ca_bundle_path.is_empty()
+        || !target.pinned_cert_path.is_empty()
+        || target.min_tls_version != TlsVersion::Tls12
+}
+
+/// rustls config with system roots plus the CA bundle, or only the pinned certificate
+pub fn tls_config(target: &OutboundTargetConfig, http2: bool) -> Result<ClientConfig> {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let versions = match target.min_tls_version {
+        TlsVersion::Tls12 => vec![&rustls::version::TLS13, &rustls::version::TLS12],
+        TlsVersion::Tls13 => vec![&rustls::version::TLS13],
+    };
+    let builder = ClientConfig::builder_with_provider(Arc::clone(&provider))
+        .with_protocol_versions(&versions)
+        .map_err(|e| AppError::configuration(format!("Invalid outbound TLS settings: {}", e)))?;
+
+    let builder = if !target.pinned_cert_path.is_empty() {
+        let pinned = load_certs(&target.pinned_cert_path)?.remove(0);
+        builder
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(PinnedCertVerifier { pinned, provider }))
+    } else {
+        let mut roots = RootCertStore::empty();
+        // Root sistem yang tidak bisa dibaca dilewati, sama seperti native-tls
+        let native = rustls_native_certs::load_native_certs();
+        roots.add_parsable_certificates(native.certs);
+        if !target.ca_bundle_path.is_empty() {
+            for cert in load_certs(&target.ca_bundle_path)? {
+                roots.add(cert).map_err(|e| {
+                    AppError::configuration(format!("Invalid CA in {}: {}", target.ca_bundle_path, e))
+                })?;
+            }
+        }
+        builder.with_root_certificates(roots)
+    };
+
+    let mut config = if target.client_cert_path.is_empty() {
+        builder.with_no_client_auth()
+    } else {
+        builder
+            .with_client_auth_cert(load_certs(&target.client_cert_path)?, load_key(&target.client_key_path)?)
+            .map_err(|e| AppError::configuration(format!("Invalid outbound client certificate or key: {}", e)))?
+    };
+    // ALPN harus sama dengan versi HTTP yang dipakai reqwest
+    config.alpn_protocols = if http2 {
+        vec![b"h2".to_vec(), b"http/1.1".to_vec()]
+    } else {
+        vec![b"http/1.1".to_vec()]
+    };
+    Ok(config)
+}
+
+/// Accepts exactly one server certificate; the handshake signature is still verified
+#[derive(Debug)]
+struct PinnedCertVerifier {
+    pinned: CertificateDer<'static>,
+    provider: Arc<CryptoProvider>,
+}
+
+impl ServerCertVerifier for PinnedCertVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> std::result::Result<ServerCertVerified, rustls::Error> {
+        if end_entity.as_ref() == self.pinned.as_ref() {
+            Ok(ServerCertVerified::assertion())
+        } else {
+            Err(rustls::Error::InvalidCertificate(CertificateError::ApplicationVerificationFailure))
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(message, cert, dss, &self.provider.signature_verification_algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(message, cert, dss, &self.provider.signature_verification_algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider.signature_verification_algorithms.supported_schemes()
+    }
+}
@@ -0,0 +1,283 @@
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::level_filters::LevelFilter;
+use tracing::subscriber::Interest;
+use tracing::{Metadata, Subscriber};
+use tracing_subscriber::layer::{Context, Filter};
+use tracing_subscriber::registry::LookupSpan;
+
+use crate::providers::log_format::{span_str_field, REQUEST_ID_FIELD, TENANT_FIELD};
+
+static LOG_CONTROL: OnceLock<LogLevelControl> = OnceLock::new();
+
+/// Spans of this crate carry the request id and tenant and are always created
+const OWN_TARGET: &str = "webhook_gateway";
+
+/// What a level override applies to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "scope", rename_all = "snake_case")]
+pub enum LogScope {
+    Global,
+    /// Events whose target starts with `target`, e.g. `webhook_gateway::services`
+    Module { target: String },
+    /// Events inside the request span with this request id
+    RequestId { request_id: String },
+    /// Events inside request spans tagged with this tenant (phone number id)
+    Tenant { tenant: String },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LogLevelOverride {
+    #[serde(flatten)]
+    pub scope: LogScope,
+    pub level: String,
+    pub expires_at: DateTime<Utc>,
+    #[serde(skip)]
+    filter: LevelFilter,
+}
+
+impl LogLevelOverride {
+    fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at > now
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LogLevelSnapshot {
+    pub level: String,
+    pub overrides: Vec<LogLevelOverride>,
+}
+
+struct ControlState {
+    base: LevelFilter,
+    overrides: Vec<LogLevelOverride>,
+}
+
+/// Runtime adjustable level filter. Overrides only ever raise verbosity above
+/// the base level and expire after their TTL. Callsites enabled by the base level
+/// are cached as always or never; callsites only an override enables are checked
+/// per event, which is also where expired overrides are dropped. Only request and
+/// tenant overrides need the span lookup.
+#[derive(Clone)]
+pub struct LogLevelControl {
+    state: Arc<RwLock<ControlState>>,
+}
+
+impl LogLevelControl {
+    pub fn new(base: LevelFilter) -> Self {
+        Self {
+            state: Arc::new(RwLock::new(ControlState {
+                base,
+                overrides: Vec::new(),
+            })),
+        }
+    }
+
+    pub fn global() -> &'static LogLevelControl {
+        LOG_CONTROL.get_or_init(|| LogLevelControl::new(LevelFilter::INFO))
+    }
+
+    pub fn set_base_level(&self, level: LevelFilter) {
+        self.state.write().unwrap().base = level;
+        rebuild_interest();
+    }
+
+    /// Add or replace the override for `scope`, active for `ttl`
+    pub fn set_override(&self, scope: LogScope, level: LevelFilter, ttl: Duration) -> LogLevelOverride {
+        let expires_at = Utc::now() + chrono::Duration::from_std(ttl).unwrap_or(chrono::Duration::zero());
+        let entry = LogLevelOverride {
+            scope,
+            level: level.to_string().to_lowercase(),
+            expires_at,
+            filter: level,
+        };
+
+        {
+            let mut state = self.state.write().unwrap();
+            let now = Utc::now();
+            state
+                .overrides
+                .retain(|existing| existing.scope != entry.scope && existing.is_active(now));
+            state.overrides.push(entry.clone());
+        }
+        rebuild_interest();
+        entry
+    }
+
+    pub fn clear_override(&self, scope: &LogScope) -> bool {
+        let removed = {
+            let mut state = self.state.write().unwrap();
+            let before = state.overrides.len();
+            state.overrides.retain(|existing| existing.scope != *scope);
+            state.overrides.len() != before
+        };
+        rebuild_interest();
+        removed
+    }
+
+    pub fn clear_all(&self) {
+        self.state.write().unwrap().overrides.clear();
+        rebuild_interest();
+    }
+
+    /// Drop expired overrides, rebuilding the interest cache only when one was
+    /// actually removed
+    fn prune_expired(&self) {
+        let now = Utc::now();
+        let expired = {
+            let state = self.state.read().unwrap();
+            state.overrides.iter().any(|existing| !existing.is_active(now))
+        };
+        if !expired {
+            return;
+        }
+
+        self.state.write().unwrap().overrides.retain(|existing| existing.is_active(now));
+        rebuild_interest();
+    }
+
+    /// Base level of a callsite and the most verbose level an active override
+    /// could give it
+    fn callsite_levels(&self, target: &str) -> (LevelFilter, LevelFilter) {
+        let state = self.state.read().unwrap();
+        let now = Utc::now();
+        let override_level = state
+            .overrides
+            .iter()
+            .filter(|entry| entry.is_active(now))
+            .filter(|entry| match &entry.scope {
+                LogScope::Module { target: prefix } => target.starts_with(prefix.as_str()),
+                LogScope::Global | LogScope::RequestId { .. } | LogScope::Tenant { .. } => true,
+            })
+            .map(|entry| entry.filter)
+            .fold(LevelFilter::OFF, LevelFilter::max);
+        (state.base, override_level)
+    }
+
+    fn has_span_scoped_override(&self) -> bool {
+        let state = self.state.read().unwrap();
+        let now = Utc::now();
+        state.overrides.iter().any(|entry| {
+            entry.is_active(now) && matches!(entry.scope, LogScope::RequestId { .. } | LogScope::Tenant { .. })
+        })
+    }
+
+    /// Most verbose level any event can currently get
+    fn max_level(&self) -> LevelFilter {
+        let state = self.state.read().unwrap();
+        let now = Utc::now();
+        state
+            .overrides
+            .iter()
+            .filter(|entry| entry.is_active(now))
+            .map(|entry| entry.filter)
+            .fold(state.base, LevelFilter::max)
+    }
+
+    /// Base level and the overrides that have not expired yet
+    pub fn snapshot(&self) -> LogLevelSnapshot {
+        self.prune_expired();
+        let state = self.state.read().unwrap();
+
+        LogLevelSnapshot {
+            level: state.base.to_string().to_lowercase(),
+            overrides: state.overrides.clone(),
+        }
+    }
+
+    /// Most verbose level that applies to an event
+    pub fn level_for(&self, target: &str, request_id: Option<&str>, tenant: Option<&str>) -> LevelFilter {
+        let state = self.state.read().unwrap();
+        let now = Utc::now();
+
+        state
+            .overrides
+            .iter()
+            .filter(|entry| entry.is_active(now))
+            .filter(|entry| match &entry.scope {
+                LogScope::Global => true,
+                LogScope::Module { target: prefix } => target.starts_with(prefix.as_str()),
+                LogScope::RequestId { request_id: id } => request_id == Some(id.as_str()),
+                LogScope::Tenant { tenant: id } => tenant == Some(id.as_str()),
+            })
+            .map(|entry| entry.filter)
+            .fold(state.base, LevelFilter::max)
+    }
+}
+
+impl<S> Filter<S> for LogLevelControl
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn enabled(&self, metadata: &Metadata<'_>, cx: &Context<'_, S>) -> bool {
+        if is_own_span(metadata) {
+            return true;
+        }
+
+        let level = *metadata.level();
+        let (base, override_level) = self.callsite_levels(metadata.target());
+        if level <= base {
+            return true;
+        }
+        if level > override_level {
+            return false;
+        }
+
+        // Override yang kedaluwarsa dibuang saat event berikutnya, tanpa timer
+        self.prune_expired();
+        if level <= self.level_for(metadata.target(), None, None) {
+            return true;
+        }
+        if !self.has_span_scoped_override() {
+            return false;
+        }
+
+        let mut request_id = None;
+        let mut tenant = None;
+        if let Some(current) = cx.lookup_current() {
+            for span in current.scope() {
+                request_id = request_id.or_else(|| span_str_field(&span, REQUEST_ID_FIELD));
+                tenant = tenant.or_else(|| span_str_field(&span, TENANT_FIELD));
+            }
+        }
+
+        level <= self.level_for(metadata.target(), request_id.as_deref(), tenant.as_deref())
+    }
+
+    fn callsite_enabled(&self, metadata: &'static Metadata<'static>) -> Interest {
+        if is_own_span(metadata) {
+            return Interest::always();
+        }
+
+        let (base, override_level) = self.callsite_levels(metadata.target());
+        if *metadata.level() <= base {
+            Interest::always()
+        } else if *metadata.level() <= override_level {
+            // Hanya selama override aktif callsite dicek per event
+            Interest::sometimes()
+        } else {
+            Interest::never()
+        }
+    }
+
+    fn max_level_hint(&self) -> Option<LevelFilter> {
+        // Request span dibuat di level INFO, jangan sampai ikut dimatikan oleh hint
+        Some(self.max_level().max(LevelFilter::INFO))
+    }
+}
+
+/// Spans created by this crate, e.g. the request span; spans of dependencies
+/// follow the levels like events
+fn is_own_span(metadata: &Metadata<'_>) -> bool {
+    let target = metadata.target();
+    metadata.is_span()
+        && target.strip_prefix(OWN_TARGET).is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+}
+
+/// Drop cached callsite interest after the levels changed
+fn rebuild_interest() {
+    tracing::callsite::rebuild_interest_cache();
+}
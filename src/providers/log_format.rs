@@ -0,0 +1,172 @@
+use std::fmt;
+use std::sync::Arc;
+
+use chrono::{Local, Utc};
+use serde_json::{Map, Value};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::{LookupSpan, SpanRef};
+use tracing_subscriber::Layer;
+
+use crate::providers::log_writer::BackgroundLogWriter;
+
+/// Field names with a special meaning in events and spans
+pub const UNIQUE_ID_FIELD: &str = "unique_id";
+pub const REQUEST_ID_FIELD: &str = "request_id";
+pub const TENANT_FIELD: &str = "tenant";
+/// JSON object merged into the top level of the record
+pub const ADDITIONAL_DATA_FIELD: &str = "additional_data";
+
+const DEFAULT_UNIQUE_ID: &str = "MAIN";
+
+/// Span fields kept in the span extensions
+struct SpanFields(Map<String, Value>);
+
+/// String field recorded on a span by `JsonLogLayer`
+pub(crate) fn span_str_field<S>(span: &SpanRef<'_, S>, name: &str) -> Option<String>
+where
+    S: for<'a> LookupSpan<'a>,
+{
+    span.extensions()
+        .get::<SpanFields>()
+        .and_then(|SpanFields(fields)| fields.get(name))
+        .and_then(|value| value.as_str())
+        .map(|value| value.to_string())
+}
+
+struct JsonVisitor<'a>(&'a mut Map<String, Value>);
+
+impl Visit for JsonVisitor<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0.insert(field.name().to_string(), Value::from(format!("{:?}", value)));
+    }
+}
+
+/// Formats every tracing event in the gateway log schema:
+/// `{"message": {"<level>": ...}, "timestamp", "uniqueId", "x-request-id", ...}`.
+/// Ids come from the event fields, falling back to the closest span that has
+/// them. Errors and warnings also go to the error file.
+pub struct JsonLogLayer {
+    writer: Arc<BackgroundLogWriter>,
+    local_time: bool,
+}
+
+impl JsonLogLayer {
+    pub fn new(writer: Arc<BackgroundLogWriter>, local_time: bool) -> Self {
+        Self { writer, local_time }
+    }
+}
+
+impl<S> Layer<S> for JsonLogLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            let mut fields = Map::new();
+            attrs.record(&mut JsonVisitor(&mut fields));
+            span.extensions_mut().insert(SpanFields(fields));
+        }
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            let mut extensions = span.extensions_mut();
+            if let Some(SpanFields(fields)) = extensions.get_mut::<SpanFields>() {
+                values.record(&mut JsonVisitor(fields));
+            }
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let mut fields = Map::new();
+        event.record(&mut JsonVisitor(&mut fields));
+
+        // Span terdalam menang, jadi telusuri dari root ke leaf
+        let mut span_ids = Map::new();
+        if let Some(scope) = ctx.event_scope(event) {
+            for span in scope.from_root() {
+                if let Some(SpanFields(span_fields)) = span.extensions().get::<SpanFields>() {
+                    for name in [UNIQUE_ID_FIELD, REQUEST_ID_FIELD] {
+                        if let Some(value) = span_fields.get(name) {
+                            span_ids.insert(name.to_string(), value.clone());
+                        }
+                    }
+                }
+            }
+        }
+
+        let level = *event.metadata().level();
+        let timestamp = if self.local_time {
+            Local::now().format("%Y-%m-%d %H:%M:%S%.3f").to_string()
+        } else {
+            Utc::now().format("%Y-%m-%d %H:%M:%S%.3f").to_string()
+        };
+
+        let line = format_record(level, &timestamp, fields, &span_ids);
+        self.writer.write_line(line, level <= Level::WARN);
+    }
+}
+
+/// Build one log line from the event fields and the ids inherited from spans
+pub fn format_record(level: Level, timestamp: &str, mut fields: Map<String, Value>, span_ids: &Map<String, Value>) -> String {
+    let message = fields.remove("message").unwrap_or(Value::from(""));
+
+    let id_field = |fields: &mut Map<String, Value>, name: &str| {
+        fields
+            .remove(name)
+            .or_else(|| span_ids.get(name).cloned())
+            .and_then(|value| value.as_str().map(|s| s.to_string()))
+    };
+    let unique_id = id_field(&mut fields, UNIQUE_ID_FIELD).unwrap_or_else(|| DEFAULT_UNIQUE_ID.to_string());
+    let request_id = id_field(&mut fields, REQUEST_ID_FIELD).unwrap_or_else(|| unique_id.clone());
+
+    let mut entry = Map::new();
+    if let Some(Value::String(data)) = fields.remove(ADDITIONAL_DATA_FIELD) {
+        if let Ok(Value::Object(data_map)) = serde_json::from_str::<Value>(&data) {
+            entry.extend(data_map);
+        }
+    }
+    entry.extend(fields);
+
+    let mut message_object = Map::new();
+    message_object.insert(level_key(level).to_string(), message);
+    entry.insert("message".to_string(), Value::Object(message_object));
+    entry.insert("timestamp".to_string(), Value::from(timestamp));
+    entry.insert("uniqueId".to_string(), Value::from(unique_id));
+    entry.insert("x-request-id".to_string(), Value::from(request_id));
+
+    Value::Object(entry).to_string()
+}
+
+fn level_key(level: Level) -> &'static str {
+    match level {
+        Level::ERROR => "error",
+        Level::WARN => "warning",
+        Level::INFO => "info",
+        Level::DEBUG => "debug",
+        Level::TRACE => "trace",
+    }
+}
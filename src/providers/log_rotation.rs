@@ -0,0 +1,264 @@
+use chrono::{Local, Utc};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+use crate::config::LoggerConfig;
+
+const ERROR_LOG_SUFFIX: &str = ".error.log";
+
+struct ActiveFile {
+    file: File,
+    path: PathBuf,
+    date: String,
+    size: u64,
+    /// Directory entry of this file synced by `write_line_durable`
+    dir_synced: bool,
+}
+
+/// Error log file writer that rolls over daily and at `max_size` MB, optionally
+/// gzips rotated files, and prunes backups by `max_backups` and `max_age` days.
+///
+/// The active file is `{file_name}.{date}.error.log`, size rollovers are kept as
+/// `{file_name}.{date}.{n}.error.log[.gz]`. A value of 0 disables the
+/// corresponding limit.
+pub struct RotatingFileWriter {
+    config: LoggerConfig,
+    suffix: String,
+    compressed_suffix: String,
+    active: Mutex<Option<ActiveFile>>,
+}
+
+impl RotatingFileWriter {
+    pub fn new(config: LoggerConfig) -> io::Result<Self> {
+        Self::with_suffix(config, ERROR_LOG_SUFFIX)
+    }
+
+    /// Same rotation rules for another kind of file, e.g. `.audit.log`
+    pub fn with_suffix(config: LoggerConfig, suffix: &str) -> io::Result<Self> {
+        fs::create_dir_all(&config.dir)?;
+
+        let writer = Self {
+            config,
+            suffix: suffix.to_string(),
+            compressed_suffix: format!("{}.gz", suffix),
+            active: Mutex::new(None),
+        };
+        writer.prune_backups()?;
+        Ok(writer)
+    }
+
+    /// Path of the file currently written to
+    pub fn current_path(&self) -> PathBuf {
+        self.active_path(&self.today())
+    }
+
+    /// Append one log line, rotating first when the day changed or the line
+    /// would push the file over `max_size`
+    pub fn write_line(&self, line: &[u8]) -> io::Result<()> {
+        self.append(line, false)
+    }
+
+    /// Same as `write_line`, but only returns once the line is on disk. The
+    /// directory is synced as well the first time a new or rotated file is
+    /// written, so the file itself survives a crash.
+    pub fn write_line_durable(&self, line: &[u8]) -> io::Result<()> {
+        self.append(line, true)
+    }
+
+    fn append(&self, line: &[u8], durable: bool) -> io::Result<()> {
+        let mut active = self.active.lock().unwrap_or_else(|e| e.into_inner());
+        let today = self.today();
+
+        if let Some(current) = active.as_ref() {
+            if current.date != today {
+                // Hari berganti, file kemarin jadi backup
+                let previous = active.take().unwrap();
+                drop(previous.file);
+                self.finish_backup(previous.path)?;
+            }
+        }
+
+        if active.is_none() {
+            *active = Some(self.open_active(&today)?);
+        }
+
+        let max_bytes = self.max_bytes();
+        let needs_rollover = {
+            let current = active.as_ref().unwrap();
+            max_bytes > 0 && current.size > 0 && current.size + line.len() as u64 > max_bytes
+        };
+        if needs_rollover {
+            let previous = active.take().unwrap();
+            drop(previous.file);
+            let backup = self.next_backup_path(&previous.date);
+            fs::rename(&previous.path, &backup)?;
+            self.finish_backup(backup)?;
+            // File baru belum dir_synced, tulisan durable berikutnya ikut men-sync rename ini
+            *active = Some(self.open_active(&today)?);
+        }
+
+        let current = active.as_mut().unwrap();
+        current.file.write_all(line)?;
+        current.size += line.len() as u64;
+
+        if durable {
+            current.file.sync_all()?;
+            if !current.dir_synced {
+                sync_dir(Path::new(&self.config.dir))?;
+                current.dir_synced = true;
+            }
+        }
+        Ok(())
+    }
+
+    /// Existing backups, newest first
+    pub fn backups(&self) -> io::Result<Vec<PathBuf>> {
+        let current = self.current_path();
+        let prefix = format!("{}.", self.config.file_name);
+
+        let mut backups: Vec<(SystemTime, PathBuf)> = fs::read_dir(&self.config.dir)?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| {
+                let name = entry.file_name().to_string_lossy().to_string();
+                name.starts_with(&prefix)
+                    && (name.ends_with(&self.suffix) || name.ends_with(&self.compressed_suffix))
+            })
+            .map(|entry| entry.path())
+            .filter(|path| *path != current)
+            .map(|path| {
+                let modified = fs::metadata(&path)
+                    .and_then(|m| m.modified())
+                    .unwrap_or(SystemTime::UNIX_EPOCH);
+                (modified, path)
+            })
+            .collect();
+
+        backups.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| b.1.cmp(&a.1)));
+        Ok(backups.into_iter().map(|(_, path)| path).collect())
+    }
+
+    fn finish_backup(&self, path: PathBuf) -> io::Result<()> {
+        if self.config.compress {
+            compress_file(&path)?;
+        }
+        self.prune_backups()
+    }
+
+    /// Hapus backup yang lewat `max_age` hari atau melebihi `max_backups`
+    fn prune_backups(&self) -> io::Result<()> {
+        let max_age = Duration::from_secs(self.config.max_age as u64 * 24 * 60 * 60);
+        let now = SystemTime::now();
+
+        let mut kept = 0;
+        for path in self.backups()? {
+            let expired = self.config.max_age > 0
+                && fs::metadata(&path)
+                    .and_then(|m| m.modified())
+                    .map(|modified| now.duration_since(modified).unwrap_or_default() > max_age)
+                    .unwrap_or(false);
+            let over_limit = self.config.max_backups > 0 && kept >= self.config.max_backups;
+
+            if expired || over_limit {
+                fs::remove_file(&path)?;
+            } else {
+                kept += 1;
+            }
+        }
+        Ok(())
+    }
+
+    fn open_active(&self, date: &str) -> io::Result<ActiveFile> {
+        let path = self.active_path(date);
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+
+        Ok(ActiveFile {
+            file,
+            path,
+            date: date.to_string(),
+            size,
+            dir_synced: false,
+        })
+    }
+
+    fn active_path(&self, date: &str) -> PathBuf {
+        Path::new(&self.config.dir).join(format!("{}.{}{}", self.config.file_name, date, self.suffix))
+    }
+
+    fn next_backup_path(&self, date: &str) -> PathBuf {
+        let dir = Path::new(&self.config.dir);
+        (1u32..)
+            .map(|n| format!("{}.{}.{}", self.config.file_name, date, n))
+            .find(|stem| {
+                !dir.join(format!("{}{}", stem, self.suffix)).exists()
+                    && !dir.join(format!("{}{}", stem, self.compressed_suffix)).exists()
+            })
+            .map(|stem| dir.join(format!("{}{}", stem, self.suffix)))
+            .unwrap()
+    }
+
+    fn max_bytes(&self) -> u64 {
+        self.config.max_size as u64 * 1024 * 1024
+    }
+
+    fn today(&self) -> String {
+        if self.config.local_time {
+            Local::now().format("%Y-%m-%d").to_string()
+        } else {
+            Utc::now().format("%Y-%m-%d").to_string()
+        }
+    }
+}
+
+impl Write for &RotatingFileWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.write_line(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let mut active = self.active.lock().unwrap_or_else(|e| e.into_inner());
+        match active.as_mut() {
+            Some(current) => current.file.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+fn compress_file(path: &Path) -> io::Result<()> {
+    let mut compressed_path = path.as_os_str().to_owned();
+    compressed_path.push(".gz");
+
+    let mut input = File::open(path)?;
+    let output = File::create(&compressed_path)?;
+    let mut encoder = GzEncoder::new(output, Compression::default());
+    io::copy(&mut input, &mut encoder)?;
+    // Isi .gz harus sudah di disk sebelum file asli dihapus
+    encoder.finish()?.sync_all()?;
+
+    // Pertahankan mtime supaya max_age tetap dihitung dari file aslinya
+    if let Ok(modified) = fs::metadata(path).and_then(|m| m.modified()) {
+        let _ = File::options()
+            .write(true)
+            .open(&compressed_path)
+            .and_then(|f| f.set_modified(modified));
+    }
+
+    fs::remove_file(path)
+}
+
+/// Persist directory entries (new or renamed files) of `dir`
+#[cfg(unix)]
+fn sync_dir(dir: &Path) -> io::Result<()> {
+    File::open(dir)?.sync_all()
+}
+
+#[cfg(not(unix))]
+fn sync_dir(_dir: &Path) -> io::Result<()> {
+    Ok(())
+}
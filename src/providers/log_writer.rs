@@ -0,0 +1,204 @@
+use std::io::{self, BufWriter, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use serde_json::json;
+
+use crate::config::{LogOverflowPolicy, LogWriterConfig};
+use crate::providers::log_rotation::RotatingFileWriter;
+use crate::providers::metrics::{self, Metrics};
+
+enum LogCommand {
+    Line {
+        text: String,
+        to_stdout: bool,
+        to_file: bool,
+    },
+    Flush(SyncSender<()>),
+    Shutdown(SyncSender<()>),
+}
+
+/// Moves log output off the caller's thread. Records go through a bounded
+/// channel to a dedicated thread which owns stdout buffering and the rotating
+/// error file, and writes them in batches.
+pub struct BackgroundLogWriter {
+    sender: SyncSender<LogCommand>,
+    config: LogWriterConfig,
+    dropped: Arc<AtomicU64>,
+    overflowed: AtomicU64,
+    handle: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl BackgroundLogWriter {
+    pub fn start(config: LogWriterConfig, file_writer: Option<RotatingFileWriter>) -> io::Result<Self> {
+        let (sender, receiver) = mpsc::sync_channel(config.buffer_capacity.max(1));
+        let batch_size = config.batch_size.max(1);
+        let dropped = Arc::new(AtomicU64::new(0));
+
+        let writer_dropped = Arc::clone(&dropped);
+        let handle = std::thread::Builder::new()
+            .name("log-writer".to_string())
+            .spawn(move || run_writer(receiver, file_writer, batch_size, writer_dropped))?;
+
+        Ok(Self {
+            sender,
+            config,
+            dropped,
+            overflowed: AtomicU64::new(0),
+            handle: Mutex::new(Some(handle)),
+        })
+    }
+
+    /// Queue a line for stdout and, for errors and warnings, the error file
+    pub fn write_line(&self, line: String, to_file: bool) {
+        self.send(LogCommand::Line {
+            text: line + "\n",
+            to_stdout: true,
+            to_file,
+        });
+    }
+
+    /// Queue already formatted output for the error file only
+    pub fn write_file(&self, text: String) {
+        self.send(LogCommand::Line {
+            text,
+            to_stdout: false,
+            to_file: true,
+        });
+    }
+
+    /// Records lost to the overflow policy since start
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Block until everything queued before this call has been written
+    pub fn flush(&self, timeout: Duration) -> bool {
+        let (ack_sender, ack_receiver) = mpsc::sync_channel(1);
+        if self.sender.send(LogCommand::Flush(ack_sender)).is_err() {
+            return false;
+        }
+        ack_receiver.recv_timeout(timeout).is_ok()
+    }
+
+    /// Write out the queue and stop the writer thread
+    pub fn shutdown(&self, timeout: Duration) -> bool {
+        let (ack_sender, ack_receiver) = mpsc::sync_channel(1);
+        if self.sender.send(LogCommand::Shutdown(ack_sender)).is_err() {
+            return false;
+        }
+        let finished = ack_receiver.recv_timeout(timeout).is_ok();
+
+        if finished {
+            if let Some(handle) = self.handle.lock().unwrap().take() {
+                let _ = handle.join();
+            }
+        }
+        finished
+    }
+
+    fn send(&self, command: LogCommand) {
+        let result = match self.config.overflow {
+            LogOverflowPolicy::Block => self.sender.send(command).map_err(|e| e.0),
+            LogOverflowPolicy::Drop => match self.sender.try_send(command) {
+                Ok(()) => Ok(()),
+                Err(TrySendError::Full(_)) => {
+                    self.record_drop();
+                    Ok(())
+                }
+                Err(TrySendError::Disconnected(command)) => Err(command),
+            },
+            LogOverflowPolicy::Sample => match self.sender.try_send(command) {
+                Ok(()) => Ok(()),
+                Err(TrySendError::Full(command)) => {
+                    let seen = self.overflowed.fetch_add(1, Ordering::Relaxed);
+                    if seen.is_multiple_of(self.config.sample_rate.max(1) as u64) {
+                        self.sender.send(command).map_err(|e| e.0)
+                    } else {
+                        self.record_drop();
+                        Ok(())
+                    }
+                }
+                Err(TrySendError::Disconnected(command)) => Err(command),
+            },
+        };
+
+        // Writer thread sudah berhenti, tulis langsung ke stdout
+        if let Err(LogCommand::Line { text, to_stdout: true, .. }) = result {
+            print!("{}", text);
+        }
+    }
+
+    fn record_drop(&self) {
+        self.dropped.fetch_add(1, Ordering::Relaxed);
+        Metrics::global().increment(metrics::LOG_RECORDS_DROPPED);
+    }
+}
+
+fn run_writer(
+    receiver: Receiver<LogCommand>,
+    file_writer: Option<RotatingFileWriter>,
+    batch_size: usize,
+    dropped: Arc<AtomicU64>,
+) {
+    let mut stdout = BufWriter::new(io::stdout());
+    let mut reported_drops = 0;
+
+    while let Ok(first) = receiver.recv() {
+        let mut batch = vec![first];
+        while batch.len() < batch_size {
+            match receiver.try_recv() {
+                Ok(command) => batch.push(command),
+                Err(_) => break,
+            }
+        }
+
+        let mut acks = Vec::new();
+        let mut shutdown = false;
+        for command in batch {
+            match command {
+                LogCommand::Line { text, to_stdout, to_file } => {
+                    if to_stdout {
+                        let _ = stdout.write_all(text.as_bytes());
+                    }
+                    if to_file {
+                        if let Some(file_writer) = &file_writer {
+                            let _ = file_writer.write_line(text.as_bytes());
+                        }
+                    }
+                }
+                LogCommand::Flush(ack) => acks.push(ack),
+                LogCommand::Shutdown(ack) => {
+                    acks.push(ack);
+                    shutdown = true;
+                }
+            }
+        }
+
+        // Laporkan record yang dibuang sejak batch sebelumnya
+        let total_dropped = dropped.load(Ordering::Relaxed);
+        if total_dropped > reported_drops {
+            let notice = json!({
+                "message": { "warning": format!("{} log records dropped, log queue full", total_dropped - reported_drops) },
+                "uniqueId": "MAIN",
+                "x-request-id": "MAIN"
+            });
+            let _ = writeln!(stdout, "{}", notice);
+            reported_drops = total_dropped;
+        }
+
+        let _ = stdout.flush();
+        if let Some(file_writer) = &file_writer {
+            let _ = (&*file_writer).flush();
+        }
+        for ack in acks {
+            let _ = ack.send(());
+        }
+        if shutdown {
+            break;
+        }
+    }
+}
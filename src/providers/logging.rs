@@ -1,135 +1,130 @@
-use chrono::{Local, Utc};
-use serde_json::{json, Value};
-use std::fs::{File, OpenOptions};
-use std::io::{BufWriter, Write};
+use serde_json::Value;
+use std::future::Future;
 use std::sync::{Arc, RwLock, OnceLock};
-use tracing::Level;
-use tracing_subscriber::fmt::writer::MakeWriterExt;
-
-use crate::config::LoggerConfig;
+use std::time::Duration;
+use tracing::level_filters::LevelFilter;
+use tracing::{field, Instrument, Span};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::filter::filter_fn;
+use tracing_subscriber::Layer;
+
+use crate::config::{LoggerConfig, OtlpConfig};
+use crate::providers::log_control::LogLevelControl;
+use crate::providers::log_format::JsonLogLayer;
+use crate::providers::log_rotation::RotatingFileWriter;
+use crate::providers::log_writer::BackgroundLogWriter;
+use crate::providers::otlp::{OtlpExporter, OtlpTraceLayer};
 use crate::utils::error::Result;
 
-pub struct ConfigurableFileWriter {
-    config: LoggerConfig,
-}
-
-impl ConfigurableFileWriter {
-    fn new(config: LoggerConfig) -> Result<Self> {
-        // Create log directory if it doesn't exist
-        std::fs::create_dir_all(&config.dir)?;
-        Ok(Self { config })
-    }
-    
-}
-
-// Implement MakeWriter trait for ConfigurableFileWriter
-impl<'a> tracing_subscriber::fmt::MakeWriter<'a> for ConfigurableFileWriter {
-    type Writer = BufWriter<File>;
-
-    fn make_writer(&'a self) -> Self::Writer {
-        let today = if self.config.local_time {
-            Local::now().format("%Y-%m-%d").to_string()
-        } else {
-            Utc::now().format("%Y-%m-%d").to_string()
-        };
-        
-        let log_file_path = format!("{}/{}.{}.error.log", 
-            self.config.dir.trim_end_matches('/'), 
-            self.config.file_name,
-            today
-        );
-        
-        // Create the directory if it doesn't exist
-        if let Some(parent) = std::path::Path::new(&log_file_path).parent() {
-            std::fs::create_dir_all(parent)
-                .unwrap_or_else(|e| panic!("Failed to create log directory {}: {}", parent.display(), e));
-        }
-        
-        let file = OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&log_file_path)
-            .unwrap_or_else(|e| panic!("Failed to open log file {}: {}", log_file_path, e));
-            
-        BufWriter::new(file)
-    }
-}
-
 pub struct StructuredLogger;
 
-static LOGGER_CONFIG: OnceLock<Arc<RwLock<Option<LoggerConfig>>>> = OnceLock::new();
+const LOG_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+static LOG_WRITER: OnceLock<Arc<RwLock<Option<Arc<BackgroundLogWriter>>>>> = OnceLock::new();
+static OTLP_EXPORTER: OnceLock<Arc<OtlpExporter>> = OnceLock::new();
 
 impl StructuredLogger {
     pub fn init(level: &str, logger_config: Option<LoggerConfig>) -> Result<()> {
+        Self::init_with_otlp(level, logger_config, &OtlpConfig::default())
+    }
+
+    /// Same as `init`, plus OTLP export of spans from this crate when `otlp.enabled`
+    pub fn init_with_otlp(level: &str, logger_config: Option<LoggerConfig>, otlp_config: &OtlpConfig) -> Result<()> {
         let filter = match level.to_lowercase().as_str() {
-            "error" => "error",
-            "warn" => "warn",
-            "info" => "info",
-            "debug" => "debug",
-            "trace" => "trace",
-            _ => "info",
+            "error" => LevelFilter::ERROR,
+            "warn" => LevelFilter::WARN,
+            "info" => LevelFilter::INFO,
+            "debug" => LevelFilter::DEBUG,
+            "trace" => LevelFilter::TRACE,
+            _ => LevelFilter::INFO,
         };
 
-        // Store config globally for use in logging functions
-        let config_lock = LOGGER_CONFIG.get_or_init(|| Arc::new(RwLock::new(None)));
-        if let Ok(mut config_guard) = config_lock.write() {
-            *config_guard = logger_config.clone();
+        // Semua output log lewat satu background writer, file handle dipegang thread writer
+        let writer_config = logger_config
+            .as_ref()
+            .map(|config| config.writer.clone())
+            .unwrap_or_default();
+        let local_time = logger_config.as_ref().map(|config| config.local_time).unwrap_or(false);
+        let rotating_writer = match logger_config {
+            Some(config) => Some(RotatingFileWriter::new(config)?),
+            None => None,
+        };
+        let log_writer = Arc::new(BackgroundLogWriter::start(writer_config, rotating_writer)?);
+
+        let writer_lock = LOG_WRITER.get_or_init(|| Arc::new(RwLock::new(None)));
+        if let Ok(mut writer_guard) = writer_lock.write() {
+            if let Some(previous) = writer_guard.replace(Arc::clone(&log_writer)) {
+                previous.shutdown(LOG_SHUTDOWN_TIMEOUT);
+            }
         }
 
-        if let Some(config) = logger_config {
-            // Create custom file writer with all config options
-            let file_writer = ConfigurableFileWriter::new(config)?;
-            
-            // Create a writer that only writes ERROR level logs to file
-            let error_file_writer = file_writer.with_max_level(Level::ERROR);
-            
-            tracing_subscriber::fmt()
-                .json()
-                .with_env_filter(filter)
-                .with_writer(std::io::stdout.and(error_file_writer))
-                .init();
+        // StructuredLogger dan event tracing lain memakai format dan filter yang sama,
+        // level bisa diubah saat runtime lewat admin API
+        let level_control = LogLevelControl::global();
+        level_control.set_base_level(filter);
+
+        // Span trace diexport terpisah dari level log, hanya span milik gateway
+        let otlp_layer = if otlp_config.enabled {
+            let exporter = Arc::new(OtlpExporter::start(otlp_config.clone())?);
+            let _ = OTLP_EXPORTER.set(Arc::clone(&exporter));
+            Some(OtlpTraceLayer::new(exporter).with_filter(filter_fn(|metadata| {
+                metadata.is_span() && metadata.target().starts_with(env!("CARGO_CRATE_NAME"))
+            })))
         } else {
-            tracing_subscriber::fmt()
-                .json()
-                .with_env_filter(filter)
-                .init();
-        }
+            None
+        };
+
+        tracing_subscriber::registry()
+            .with(JsonLogLayer::new(log_writer, local_time).with_filter(level_control.clone()))
+            .with(otlp_layer)
+            .init();
 
         Ok(())
     }
 
+    /// Span for one request, events inside it inherit `uniqueId`/`x-request-id`
+    pub fn request_span(request_id: &str) -> Span {
+        tracing::info_span!(
+            "request",
+            unique_id = request_id,
+            request_id = request_id,
+            tenant = field::Empty,
+            otel.kind = "server",
+            http.method = field::Empty,
+            http.route = field::Empty,
+            http.status_code = field::Empty,
+            otel.status_code = field::Empty
+        )
+    }
+
+    /// Tag the current request span with the tenant (WhatsApp phone number id)
+    pub fn record_tenant(tenant: &str) {
+        Span::current().record("tenant", tenant);
+    }
+
+    /// Replace the ids of the current request span, e.g. once the body id is known
+    pub fn record_request_id(request_id: &str) {
+        let span = Span::current();
+        span.record("unique_id", request_id);
+        span.record("request_id", request_id);
+    }
+
+    /// `tokio::spawn` that keeps the current request span for the task
+    pub fn spawn_in_current_span<F>(future: F) -> tokio::task::JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        tokio::spawn(future.instrument(Span::current()))
+    }
+
     pub fn log_error(
         error: &str,
         unique_id: Option<&str>,
         request_id: Option<&str>,
     ) {
-        let use_local_time = LOGGER_CONFIG
-            .get()
-            .and_then(|config_lock| config_lock.read().ok())
-            .and_then(|config_guard| config_guard.as_ref().map(|c| c.local_time))
-            .unwrap_or(false);
-        
-        let timestamp = if use_local_time {
-            Local::now().format("%Y-%m-%d %H:%M:%S%.3f").to_string()
-        } else {
-            Utc::now().format("%Y-%m-%d %H:%M:%S%.3f").to_string()
-        };
-        
-        let unique_id = unique_id.unwrap_or("MAIN");
-        let request_id = request_id.unwrap_or(unique_id);
-
-        let log_entry = json!({
-            "message": {
-                "error": error
-            },
-            "timestamp": timestamp,
-            "uniqueId": unique_id,
-            "x-request-id": request_id
-        });
-
-        // Write directly to stdout and file to avoid tracing wrapper
-        println!("{}", log_entry);
-        Self::write_to_file(&log_entry.to_string());
+        tracing::error!(unique_id, request_id, "{}", error);
     }
 
     pub fn log_info(
@@ -138,42 +133,8 @@ impl StructuredLogger {
         request_id: Option<&str>,
         additional_data: Option<Value>,
     ) {
-        let use_local_time = LOGGER_CONFIG
-            .get()
-            .and_then(|config_lock| config_lock.read().ok())
-            .and_then(|config_guard| config_guard.as_ref().map(|c| c.local_time))
-            .unwrap_or(false);
-        
-        let timestamp = if use_local_time {
-            Local::now().format("%Y-%m-%d %H:%M:%S%.3f").to_string()
-        } else {
-            Utc::now().format("%Y-%m-%d %H:%M:%S%.3f").to_string()
-        };
-        
-        let unique_id = unique_id.unwrap_or("MAIN");
-        let request_id = request_id.unwrap_or(unique_id);
-
-        let mut log_entry = json!({
-            "message": {
-                "info": message
-            },
-            "timestamp": timestamp,
-            "uniqueId": unique_id,
-            "x-request-id": request_id
-        });
-
-        if let Some(data) = additional_data {
-            if let Value::Object(ref mut map) = log_entry {
-                if let Value::Object(data_map) = data {
-                    for (key, value) in data_map {
-                        map.insert(key, value);
-                    }
-                }
-            }
-        }
-
-        // Write directly to stdout only for info logs
-        println!("{}", log_entry);
+        let additional_data = additional_data.map(|data| data.to_string());
+        tracing::info!(unique_id, request_id, additional_data, "{}", message);
     }
 
     pub fn log_warning(
@@ -181,64 +142,32 @@ impl StructuredLogger {
         unique_id: Option<&str>,
         request_id: Option<&str>,
     ) {
-        let use_local_time = LOGGER_CONFIG
-            .get()
-            .and_then(|config_lock| config_lock.read().ok())
-            .and_then(|config_guard| config_guard.as_ref().map(|c| c.local_time))
-            .unwrap_or(false);
-        
-        let timestamp = if use_local_time {
-            Local::now().format("%Y-%m-%d %H:%M:%S%.3f").to_string()
-        } else {
-            Utc::now().format("%Y-%m-%d %H:%M:%S%.3f").to_string()
-        };
-        
-        let unique_id = unique_id.unwrap_or("MAIN");
-        let request_id = request_id.unwrap_or(unique_id);
-
-        let log_entry = json!({
-            "message": {
-                "warning": message
-            },
-            "timestamp": timestamp,
-            "uniqueId": unique_id,
-            "x-request-id": request_id
-        });
-
-        // Write directly to stdout and file to avoid tracing wrapper
-        println!("{}", log_entry);
-        Self::write_to_file(&log_entry.to_string());
+        tracing::warn!(unique_id, request_id, "{}", message);
     }
 
-    fn write_to_file(log_line: &str) {
-        if let Some(config_lock) = LOGGER_CONFIG.get() {
-            if let Ok(config_guard) = config_lock.read() {
-                if let Some(config) = config_guard.as_ref() {
-                    let today = if config.local_time {
-                        Local::now().format("%Y-%m-%d").to_string()
-                    } else {
-                        Utc::now().format("%Y-%m-%d").to_string()
-                    };
-                    
-                    let log_file_path = format!("{}/{}.{}.error.log", 
-                        config.dir.trim_end_matches('/'), 
-                        config.file_name,
-                        today
-                    );
-                    
-                    if let Some(parent) = std::path::Path::new(&log_file_path).parent() {
-                        let _ = std::fs::create_dir_all(parent);
-                    }
-                    
-                    if let Ok(mut file) = OpenOptions::new()
-                        .create(true)
-                        .append(true)
-                        .open(&log_file_path) 
-                    {
-                        let _ = writeln!(file, "{}", log_line);
-                    }
-                }
-            }
+    /// Block until queued log records are written
+    pub fn flush() -> bool {
+        match Self::writer() {
+            Some(writer) => writer.flush(LOG_SHUTDOWN_TIMEOUT),
+            None => true,
+        }
+    }
+
+    /// Flush and stop the background writer and the trace exporter
+    pub fn shutdown() -> bool {
+        if let Some(exporter) = OTLP_EXPORTER.get() {
+            exporter.shutdown(LOG_SHUTDOWN_TIMEOUT);
+        }
+        match Self::writer() {
+            Some(writer) => writer.shutdown(LOG_SHUTDOWN_TIMEOUT),
+            None => true,
         }
     }
-}
\ No newline at end of file
+
+    fn writer() -> Option<Arc<BackgroundLogWriter>> {
+        LOG_WRITER
+            .get()
+            .and_then(|writer_lock| writer_lock.read().ok())
+            .and_then(|writer_guard| writer_guard.clone())
+    }
+}
@@ -0,0 +1,163 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Longest sliding window that can be queried, older buckets are pruned
+pub const MAX_WINDOW_SECS: u64 = 3600;
+
+pub const WEBHOOK_RECEIVED: &str = "webhook_received";
+pub const JSON_PARSE_ERROR: &str = "json_parse_error";
+pub const PERMATA_CALLBACK_TOTAL: &str = "permata_callback_total";
+pub const PERMATA_CALLBACK_SUCCESS: &str = "permata_callback_success";
+pub const PERMATA_CALLBACK_FAILURE: &str = "permata_callback_failure";
+pub const PERMATA_AUTH_FAILURE: &str = "permata_auth_failure";
+pub const PERMATA_CALLBACK_RETRIED: &str = "permata_callback_retried";
+pub const PERMATA_STATUS_REJECTED: &str = "permata_status_rejected";
+pub const TOKEN_REFRESH_SUCCESS: &str = "token_refresh_success";
+pub const TOKEN_REFRESH_FAILURE: &str = "token_refresh_failure";
+pub const ALERT_DELIVERED: &str = "alert_delivered";
+pub const ALERT_DELIVERY_FAILED: &str = "alert_delivery_failed";
+pub const ALERT_DROPPED: &str = "alert_dropped";
+pub const LOG_RECORDS_DROPPED: &str = "log_records_dropped";
+pub const FLOW_REPLY_DECODED: &str = "flow_reply_decoded";
+pub const FLOW_REPLY_INVALID: &str = "flow_reply_invalid";
+pub const FAN_OUT_EVENTS: &str = "fan_out_events";
+pub const FAN_OUT_DUPLICATE: &str = "fan_out_duplicate";
+pub const DEAD_LETTERED: &str = "dead_lettered";
+pub const STATUS_REGRESSION: &str = "status_regression";
+pub const STATUS_COALESCED: &str = "status_coalesced";
+pub const DISPATCH_REJECTED: &str = "dispatch_rejected";
+pub const BACKPRESSURE_REJECTED: &str = "backpressure_rejected";
+pub const INGRESS_IP_DENIED: &str = "ingress_ip_denied";
+pub const INGRESS_RATE_LIMITED: &str = "ingress_rate_limited";
+pub const TLS_CERT_RELOADED: &str = "tls_cert_reloaded";
+pub const TLS_RELOAD_FAILED: &str = "tls_reload_failed";
+pub const TLS_HANDSHAKE_FAILED: &str = "tls_handshake_failed";
+
+static METRICS: OnceLock<Metrics> = OnceLock::new();
+
+#[derive(Debug, Default)]
+struct CounterState {
+    total: u64,
+    // (unix second, count) buckets in ascending order
+    buckets: VecDeque<(i64, u64)>,
+    last_seen: Option<DateTime<Utc>>,
+}
+
+impl CounterState {
+    fn prune(&mut self, now_secs: i64) {
+        let oldest = now_secs - MAX_WINDOW_SECS as i64;
+        while matches!(self.buckets.front(), Some((second, _)) if *second <= oldest) {
+            self.buckets.pop_front();
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CounterSnapshot {
+    pub total: u64,
+    pub last_minute: u64,
+    pub last_seen: Option<DateTime<Utc>>,
+}
+
+/// In-process counters with per-second buckets for sliding window queries
+#[derive(Clone)]
+pub struct Metrics {
+    counters: Arc<Mutex<HashMap<String, CounterState>>>,
+    started_at: DateTime<Utc>,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self {
+            counters: Arc::new(Mutex::new(HashMap::new())),
+            started_at: Utc::now(),
+        }
+    }
+
+    pub fn global() -> &'static Metrics {
+        METRICS.get_or_init(Metrics::new)
+    }
+
+    pub fn increment(&self, name: &str) {
+        self.add(name, 1);
+    }
+
+    pub fn add(&self, name: &str, value: u64) {
+        self.add_at(name, value, Utc::now());
+    }
+
+    /// Record `value` events at the given time, used by tests to build windows
+    pub fn add_at(&self, name: &str, value: u64, at: DateTime<Utc>) {
+        let second = at.timestamp();
+        let mut counters = self.counters.lock().unwrap();
+        let state = counters.entry(name.to_string()).or_default();
+
+        state.total += value;
+        state.last_seen = Some(state.last_seen.map_or(at, |seen| seen.max(at)));
+
+        match state.buckets.iter_mut().rev().find(|(bucket, _)| *bucket == second) {
+            Some((_, count)) => *count += value,
+            None => {
+                let position = state.buckets.partition_point(|(bucket, _)| *bucket < second);
+                state.buckets.insert(position, (second, value));
+            }
+        }
+
+        state.prune(Utc::now().timestamp().max(second));
+    }
+
+    pub fn total(&self, name: &str) -> u64 {
+        let counters = self.counters.lock().unwrap();
+        counters.get(name).map_or(0, |state| state.total)
+    }
+
+    /// Number of events recorded in the last `window_secs` seconds
+    pub fn count_in_window(&self, name: &str, window_secs: u64) -> u64 {
+        let window_secs = window_secs.min(MAX_WINDOW_SECS) as i64;
+        let since = Utc::now().timestamp() - window_secs;
+
+        let counters = self.counters.lock().unwrap();
+        counters.get(name).map_or(0, |state| {
+            state
+                .buckets
+                .iter()
+                .filter(|(second, _)| *second > since)
+                .map(|(_, count)| count)
+                .sum()
+        })
+    }
+
+    pub fn last_seen(&self, name: &str) -> Option<DateTime<Utc>> {
+        let counters = self.counters.lock().unwrap();
+        counters.get(name).and_then(|state| state.last_seen)
+    }
+
+    pub fn started_at(&self) -> DateTime<Utc> {
+        self.started_at
+    }
+
+    pub fn snapshot(&self) -> BTreeMap<String, CounterSnapshot> {
+        let names: Vec<String> = self.counters.lock().unwrap().keys().cloned().collect();
+
+        names
+            .into_iter()
+            .map(|name| {
+                let snapshot = CounterSnapshot {
+                    total: self.total(&name),
+                    last_minute: self.count_in_window(&name, 60),
+                    last_seen: self.last_seen(&name),
+                };
+                (name, snapshot)
+            })
+            .collect()
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,3 +1,23 @@
+pub mod access_log;
+pub mod audit_log;
+pub mod dead_letter;
+pub mod http_client;
 pub mod logging;
+pub mod log_control;
+pub mod log_format;
+pub mod log_rotation;
+pub mod log_writer;
+pub mod metrics;
+pub mod otlp;
 
-pub use logging::*;
\ No newline at end of file
+pub use access_log::AccessLogger;
+pub use audit_log::AuditLogger;
+pub use dead_letter::DeadLetterLog;
+pub use http_client::{Destination, HttpClientFactory};
+pub use logging::*;
+pub use log_control::{LogLevelControl, LogScope};
+pub use log_format::JsonLogLayer;
+pub use log_rotation::RotatingFileWriter;
+pub use log_writer::BackgroundLogWriter;
+pub use metrics::Metrics;
+pub use otlp::{OtlpExporter, OtlpTraceLayer, TraceContext};
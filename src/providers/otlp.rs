@@ -0,0 +1,430 @@
+use std::fmt;
+use std::io;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use reqwest::{Client, RequestBuilder};
+use serde_json::{json, Map, Value};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Span, Subscriber};
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::{LookupSpan, Registry};
+use tracing_subscriber::Layer;
+
+use crate::config::OtlpConfig;
+use crate::providers::logging::StructuredLogger;
+
+/// W3C trace context header
+pub const TRACEPARENT_HEADER: &str = "traceparent";
+/// Span field for the OTLP span kind: `server`, `client`, `producer`, `consumer`
+pub const SPAN_KIND_FIELD: &str = "otel.kind";
+/// Span field marking the span as failed when set to `error`
+pub const SPAN_STATUS_FIELD: &str = "otel.status_code";
+
+/// Trace and span id of a span, as carried in `traceparent`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceContext {
+    pub trace_id: [u8; 16],
+    pub span_id: [u8; 8],
+    pub sampled: bool,
+}
+
+impl TraceContext {
+    /// Parse `00-<trace-id>-<parent-id>-<flags>`, invalid or all-zero ids are rejected
+    pub fn parse_traceparent(value: &str) -> Option<Self> {
+        let parts: Vec<&str> = value.trim().split('-').collect();
+        if parts.len() < 4 || parts[0].len() != 2 || parts[0] == "ff" {
+            return None;
+        }
+        // Versi 00 harus tepat 4 bagian, versi lain boleh punya tambahan
+        if parts[0] == "00" && parts.len() != 4 {
+            return None;
+        }
+
+        let trace_id: [u8; 16] = decode_hex(parts[1])?.try_into().ok()?;
+        let span_id: [u8; 8] = decode_hex(parts[2])?.try_into().ok()?;
+        let flags = decode_hex(parts[3])?;
+        if flags.len() != 1 || trace_id == [0; 16] || span_id == [0; 8] {
+            return None;
+        }
+
+        Some(Self {
+            trace_id,
+            span_id,
+            sampled: flags[0] & 0x01 == 0x01,
+        })
+    }
+
+    pub fn to_traceparent(&self) -> String {
+        format!(
+            "00-{}-{}-{}",
+            encode_hex(&self.trace_id),
+            encode_hex(&self.span_id),
+            if self.sampled { "01" } else { "00" }
+        )
+    }
+
+    pub fn trace_id_hex(&self) -> String {
+        encode_hex(&self.trace_id)
+    }
+
+    pub fn span_id_hex(&self) -> String {
+        encode_hex(&self.span_id)
+    }
+}
+
+/// Make `span` continue the trace from an inbound `traceparent`
+pub fn set_remote_parent(span: &Span, parent: &TraceContext) {
+    span.with_subscriber(|(id, dispatch)| {
+        let Some(registry) = dispatch.downcast_ref::<Registry>() else {
+            return;
+        };
+        if let Some(span_ref) = registry.span(id) {
+            if let Some(data) = span_ref.extensions_mut().get_mut::<OtlpSpanData>() {
+                data.context.trace_id = parent.trace_id;
+                data.context.sampled = parent.sampled;
+                data.parent_span_id = Some(parent.span_id);
+            }
+        }
+    });
+}
+
+/// Trace context of the current span, `None` when trace export is disabled
+pub fn current_trace_context() -> Option<TraceContext> {
+    Span::current()
+        .with_subscriber(|(id, dispatch)| {
+            dispatch
+                .downcast_ref::<Registry>()
+                .and_then(|registry| registry.span(id))
+                .and_then(|span_ref| span_ref.extensions().get::<OtlpSpanData>().map(|data| data.context))
+        })
+        .flatten()
+}
+
+/// Add `traceparent` of the current span to an outbound request
+pub fn inject_traceparent(request: RequestBuilder) -> RequestBuilder {
+    match current_trace_context() {
+        Some(context) => request.header(TRACEPARENT_HEADER, context.to_traceparent()),
+        None => request,
+    }
+}
+
+/// Per-span state kept in the span extensions until the span closes
+struct OtlpSpanData {
+    context: TraceContext,
+    parent_span_id: Option<[u8; 8]>,
+    start: SystemTime,
+    attributes: Map<String, Value>,
+}
+
+struct AttributeVisitor<'a>(&'a mut Map<String, Value>);
+
+impl Visit for AttributeVisitor<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0.insert(field.name().to_string(), Value::from(format!("{:?}", value)));
+    }
+}
+
+/// Turns closed tracing spans into OTLP spans and hands them to the exporter.
+/// Child spans inherit the trace id of their parent, root spans start a new
+/// trace unless `set_remote_parent` attached them to an inbound one.
+pub struct OtlpTraceLayer {
+    exporter: Arc<OtlpExporter>,
+}
+
+impl OtlpTraceLayer {
+    pub fn new(exporter: Arc<OtlpExporter>) -> Self {
+        Self { exporter }
+    }
+}
+
+impl<S> Layer<S> for OtlpTraceLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+
+        let parent = span
+            .parent()
+            .and_then(|parent| parent.extensions().get::<OtlpSpanData>().map(|data| data.context));
+        let context = TraceContext {
+            trace_id: parent.map(|p| p.trace_id).unwrap_or_else(random_trace_id),
+            span_id: random_span_id(),
+            sampled: parent.map(|p| p.sampled).unwrap_or(true),
+        };
+
+        let mut attributes = Map::new();
+        attrs.record(&mut AttributeVisitor(&mut attributes));
+        span.extensions_mut().insert(OtlpSpanData {
+            context,
+            parent_span_id: parent.map(|p| p.span_id),
+            start: SystemTime::now(),
+            attributes,
+        });
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            if let Some(data) = span.extensions_mut().get_mut::<OtlpSpanData>() {
+                values.record(&mut AttributeVisitor(&mut data.attributes));
+            }
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let Some(data) = span.extensions_mut().remove::<OtlpSpanData>() else {
+            return;
+        };
+        if data.context.sampled {
+            self.exporter.export(otlp_span(span.name(), data, SystemTime::now()));
+        }
+    }
+}
+
+fn otlp_span(name: &str, mut data: OtlpSpanData, end: SystemTime) -> Value {
+    let kind = match data.attributes.remove(SPAN_KIND_FIELD).as_ref().and_then(Value::as_str) {
+        Some("server") => 2,
+        Some("client") => 3,
+        Some("producer") => 4,
+        Some("consumer") => 5,
+        _ => 1,
+    };
+    let status_code = match data.attributes.remove(SPAN_STATUS_FIELD).as_ref().and_then(Value::as_str) {
+        Some("error") => 2,
+        Some("ok") => 1,
+        _ => 0,
+    };
+
+    let mut span = json!({
+        "traceId": data.context.trace_id_hex(),
+        "spanId": data.context.span_id_hex(),
+        "name": name,
+        "kind": kind,
+        "startTimeUnixNano": unix_nanos(data.start).to_string(),
+        "endTimeUnixNano": unix_nanos(end).to_string(),
+        "attributes": data.attributes.into_iter().map(|(key, value)| otlp_attribute(key, value)).collect::<Vec<_>>(),
+        "status": { "code": status_code },
+    });
+    if let Some(parent_span_id) = data.parent_span_id {
+        span["parentSpanId"] = json!(encode_hex(&parent_span_id));
+    }
+    span
+}
+
+fn otlp_attribute(key: String, value: Value) -> Value {
+    let value = match value {
+        Value::Bool(b) => json!({ "boolValue": b }),
+        // int64 dikirim sebagai string di OTLP JSON
+        Value::Number(n) if n.is_i64() || n.is_u64() => json!({ "intValue": n.to_string() }),
+        Value::Number(n) => json!({ "doubleValue": n.as_f64() }),
+        Value::String(s) => json!({ "stringValue": s }),
+        other => json!({ "stringValue": other.to_string() }),
+    };
+    json!({ "key": key, "value": value })
+}
+
+/// OTLP/HTTP request body for a batch of spans
+pub fn export_request(service_name: &str, spans: Vec<Value>) -> Value {
+    json!({
+        "resourceSpans": [{
+            "resource": {
+                "attributes": [otlp_attribute("service.name".to_string(), json!(service_name))]
+            },
+            "scopeSpans": [{
+                "scope": { "name": env!("CARGO_PKG_NAME"), "version": env!("CARGO_PKG_VERSION") },
+                "spans": spans,
+            }]
+        }]
+    })
+}
+
+enum ExportCommand {
+    Span(Value),
+    Flush(SyncSender<()>),
+    Shutdown(SyncSender<()>),
+}
+
+/// Batches finished spans on a dedicated thread and posts them to the OTLP
+/// collector. Spans are dropped, never blocked on, when the queue is full.
+pub struct OtlpExporter {
+    sender: SyncSender<ExportCommand>,
+    dropped: AtomicU64,
+    handle: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl OtlpExporter {
+    pub fn start(config: OtlpConfig) -> io::Result<Self> {
+        let (sender, receiver) = mpsc::sync_channel(config.queue_capacity.max(1));
+        let client = Client::builder()
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .build()
+            .map_err(io::Error::other)?;
+
+        let handle = std::thread::Builder::new()
+            .name("otlp-exporter".to_string())
+            .spawn(move || run_exporter(receiver, client, config))?;
+
+        Ok(Self {
+            sender,
+            dropped: AtomicU64::new(0),
+            handle: Mutex::new(Some(handle)),
+        })
+    }
+
+    pub fn export(&self, span: Value) {
+        if let Err(TrySendError::Full(_)) = self.sender.try_send(ExportCommand::Span(span)) {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Send every queued span to the collector
+    pub fn flush(&self, timeout: Duration) -> bool {
+        let (ack_sender, ack_receiver) = mpsc::sync_channel(1);
+        if self.sender.send(ExportCommand::Flush(ack_sender)).is_err() {
+            return false;
+        }
+        ack_receiver.recv_timeout(timeout).is_ok()
+    }
+
+    /// Flush and stop the exporter thread
+    pub fn shutdown(&self, timeout: Duration) -> bool {
+        let (ack_sender, ack_receiver) = mpsc::sync_channel(1);
+        if self.sender.send(ExportCommand::Shutdown(ack_sender)).is_err() {
+            return false;
+        }
+        let finished = ack_receiver.recv_timeout(timeout).is_ok();
+        if finished {
+            if let Some(handle) = self.handle.lock().unwrap().take() {
+                let _ = handle.join();
+            }
+        }
+        finished
+    }
+}
+
+fn run_exporter(receiver: Receiver<ExportCommand>, client: Client, config: OtlpConfig) {
+    // Thread sendiri dengan runtime kecil supaya export tidak bergantung runtime aplikasi
+    let runtime = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+        Ok(runtime) => runtime,
+        Err(_) => return,
+    };
+    let batch_size = config.batch_size.max(1);
+    let interval = Duration::from_millis(config.export_interval_ms.max(1));
+
+    let mut batch = Vec::new();
+    let mut deadline = Instant::now() + interval;
+    loop {
+        let command = receiver.recv_timeout(deadline.saturating_duration_since(Instant::now()));
+
+        let mut acks = Vec::new();
+        let mut shutdown = false;
+        match command {
+            Ok(ExportCommand::Span(span)) => batch.push(span),
+            Ok(ExportCommand::Flush(ack)) => acks.push(ack),
+            Ok(ExportCommand::Shutdown(ack)) => {
+                acks.push(ack);
+                shutdown = true;
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => shutdown = true,
+        }
+
+        let due = Instant::now() >= deadline;
+        if batch.len() >= batch_size || ((due || !acks.is_empty() || shutdown) && !batch.is_empty()) {
+            let spans = std::mem::take(&mut batch);
+            runtime.block_on(post_spans(&client, &config, spans));
+        }
+        if due {
+            deadline = Instant::now() + interval;
+        }
+
+        for ack in acks {
+            let _ = ack.send(());
+        }
+        if shutdown {
+            break;
+        }
+    }
+}
+
+async fn post_spans(client: &Client, config: &OtlpConfig, spans: Vec<Value>) {
+    let count = spans.len();
+    let result = client
+        .post(&config.endpoint)
+        .json(&export_request(&config.service_name, spans))
+        .send()
+        .await;
+
+    // Aman lewat tracing: layer OTLP hanya mengekspor span, event di thread exporter tidak punya span
+    match result {
+        Ok(response) if response.status().is_success() => {}
+        Ok(response) => StructuredLogger::log_warning(
+            &format!("OTLP export of {} spans rejected with status {}", count, response.status()),
+            None,
+            None,
+        ),
+        Err(e) => StructuredLogger::log_warning(&format!("OTLP export of {} spans failed: {}", count, e), None, None),
+    }
+}
+
+fn random_trace_id() -> [u8; 16] {
+    *uuid::Uuid::new_v4().as_bytes()
+}
+
+fn random_span_id() -> [u8; 8] {
+    let mut span_id = [0u8; 8];
+    span_id.copy_from_slice(&uuid::Uuid::new_v4().as_bytes()[..8]);
+    span_id
+}
+
+fn unix_nanos(time: SystemTime) -> u128 {
+    time.duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos()
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn decode_hex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) || !text.bytes().all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b)) {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&text[i..i + 2], 16).ok())
+        .collect()
+}
@@ -0,0 +1,286 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use reqwest::{Client, StatusCode};
+use serde_json::Value;
+use tokio::sync::{mpsc, watch};
+use tokio::time::{sleep_until, Instant};
+use tracing::{field, Instrument, Span};
+
+use crate::config::AlertDeliveryConfig;
+use crate::providers::metrics::{self, Metrics};
+use crate::providers::otlp::inject_traceparent;
+use crate::providers::StructuredLogger;
+
+/// One alert message for one channel
+#[derive(Debug, Clone)]
+pub struct AlertDelivery {
+    pub channel: &'static str,
+    /// Client of the channel's destination, with its TLS, proxy and DNS settings
+    pub client: Client,
+    pub url: String,
+    pub payload: Value,
+    /// Rendered text, used for logging
+    pub summary: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeliveryOutcome {
+    Delivered,
+    /// Gave up after a permanent error or after all attempts
+    Failed,
+}
+
+/// Bounded in-process alert queue with a dedicated sender task. Transient
+/// failures are retried with backoff and Telegram's 429 `retry_after` is honored
+/// in full; a retry that would land after the flush deadline is given up.
+#[derive(Clone)]
+pub struct AlertDispatcher {
+    sender: Arc<Mutex<Option<mpsc::Sender<AlertDelivery>>>>,
+    worker: Arc<Mutex<Option<tokio::task::JoinHandle<()>>>>,
+    pending: Arc<AtomicUsize>,
+    shutdown_deadline: Arc<watch::Sender<Option<Instant>>>,
+}
+
+impl AlertDispatcher {
+    /// Spawn the sender task, must be called inside a tokio runtime
+    pub fn start(config: AlertDeliveryConfig) -> Self {
+        let (sender, mut receiver) = mpsc::channel::<AlertDelivery>(config.queue_capacity.max(1));
+        let pending = Arc::new(AtomicUsize::new(0));
+        let (shutdown_deadline, mut shutdown) = watch::channel(None);
+
+        let worker_pending = Arc::clone(&pending);
+        let worker = tokio::spawn(async move {
+            while let Some(delivery) = receiver.recv().await {
+                let span = tracing::info_span!(
+                    "alert_send",
+                    channel = delivery.channel,
+                    otel.kind = "client",
+                    attempts = field::Empty,
+                    otel.status_code = field::Empty
+                );
+                let outcome = deliver(&config, &delivery, &mut shutdown).instrument(span.clone()).await;
+                if outcome == DeliveryOutcome::Failed {
+                    span.record("otel.status_code", "error");
+                }
+                worker_pending.fetch_sub(1, Ordering::SeqCst);
+            }
+        });
+
+        Self {
+            sender: Arc::new(Mutex::new(Some(sender))),
+            worker: Arc::new(Mutex::new(Some(worker))),
+            pending,
+            shutdown_deadline: Arc::new(shutdown_deadline),
+        }
+    }
+
+    /// Queue an alert, returns false when the queue is full or shut down
+    pub fn enqueue(&self, delivery: AlertDelivery) -> bool {
+        let sender_guard = self.sender.lock().unwrap();
+        let Some(sender) = sender_guard.as_ref() else {
+            StructuredLogger::log_error(
+                &format!("Alert dispatcher is shut down, dropping {} alert: {}", delivery.channel, delivery.summary),
+                None,
+                None,
+            );
+            Metrics::global().increment(metrics::ALERT_DROPPED);
+            return false;
+        };
+
+        self.pending.fetch_add(1, Ordering::SeqCst);
+        match sender.try_send(delivery) {
+            Ok(()) => true,
+            Err(e) => {
+                self.pending.fetch_sub(1, Ordering::SeqCst);
+                let delivery = match e {
+                    mpsc::error::TrySendError::Full(delivery) => delivery,
+                    mpsc::error::TrySendError::Closed(delivery) => delivery,
+                };
+                StructuredLogger::log_error(
+                    &format!("Alert queue full, dropping {} alert: {}", delivery.channel, delivery.summary),
+                    None,
+                    None,
+                );
+                Metrics::global().increment(metrics::ALERT_DROPPED);
+                false
+            }
+        }
+    }
+
+    /// Alerts queued or in flight
+    pub fn pending(&self) -> usize {
+        self.pending.load(Ordering::SeqCst)
+    }
+
+    /// Stop accepting alerts and wait up to `deadline` for the queue to drain.
+    /// Returns true when every queued alert was processed.
+    pub async fn flush(&self, deadline: Duration) -> bool {
+        // Dropping the only sender lets the worker finish once the queue is empty
+        self.sender.lock().unwrap().take();
+        self.shutdown_deadline.send_replace(Some(Instant::now() + deadline));
+
+        let Some(mut worker) = self.worker.lock().unwrap().take() else {
+            return true;
+        };
+
+        match tokio::time::timeout(deadline, &mut worker).await {
+            Ok(_) => true,
+            Err(_) => {
+                worker.abort();
+                StructuredLogger::log_error(
+                    &format!(
+                        "Alert queue not drained within {}s, {} alerts lost",
+                        deadline.as_secs(),
+                        self.pending()
+                    ),
+                    None,
+                    None,
+                );
+                false
+            }
+        }
+    }
+}
+
+async fn deliver(
+    config: &AlertDeliveryConfig,
+    delivery: &AlertDelivery,
+    shutdown: &mut watch::Receiver<Option<Instant>>,
+) -> DeliveryOutcome {
+    let max_attempts = config.max_attempts.max(1);
+
+    for attempt in 1..=max_attempts {
+        Span::current().record("attempts", attempt);
+        let wait = match inject_traceparent(delivery.client.post(&delivery.url))
+            .header("Content-Type", "application/json")
+            .json(&delivery.payload)
+            .send()
+            .await
+        {
+            Ok(response) if response.status().is_success() => {
+                StructuredLogger::log_info(
+                    &format!("{} alert sent successfully: {}", delivery.channel, delivery.summary),
+                    None,
+                    None,
+                    None,
+                );
+                Metrics::global().increment(metrics::ALERT_DELIVERED);
+                return DeliveryOutcome::Delivered;
+            }
+            Ok(response) => {
+                let status = response.status();
+                let retry_after_header = response
+                    .headers()
+                    .get(reqwest::header::RETRY_AFTER)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|value| value.parse::<u64>().ok());
+                let error_text = response.text().await.unwrap_or_default();
+
+                if status == StatusCode::TOO_MANY_REQUESTS {
+                    let retry_after = telegram_retry_after(&error_text)
+                        .or(retry_after_header)
+                        .map(Duration::from_secs)
+                        .unwrap_or_else(|| backoff(config, attempt));
+                    StructuredLogger::log_warning(
+                        &format!(
+                            "{} alert rate limited (attempt {}/{}), retrying in {}s",
+                            delivery.channel, attempt, max_attempts, retry_after.as_secs()
+                        ),
+                        None,
+                        None,
+                    );
+                    retry_after
+                } else if status.is_server_error() || status == StatusCode::REQUEST_TIMEOUT {
+                    StructuredLogger::log_warning(
+                        &format!(
+                            "Failed to send {} alert (attempt {}/{}). Status: {}, Error: {}",
+                            delivery.channel, attempt, max_attempts, status, error_text
+                        ),
+                        None,
+                        None,
+                    );
+                    backoff(config, attempt)
+                } else {
+                    StructuredLogger::log_error(&format!(
+                        "Failed to send {} alert. Status: {}, Error: {}",
+                        delivery.channel, status, error_text
+                    ), None, None);
+                    Metrics::global().increment(metrics::ALERT_DELIVERY_FAILED);
+                    return DeliveryOutcome::Failed;
+                }
+            }
+            Err(e) => {
+                StructuredLogger::log_warning(
+                    &format!(
+                        "Failed to send {} alert (attempt {}/{}): {}",
+                        delivery.channel, attempt, max_attempts, e
+                    ),
+                    None,
+                    None,
+                );
+                backoff(config, attempt)
+            }
+        };
+
+        if attempt < max_attempts && !wait_for_retry(wait, shutdown).await {
+            StructuredLogger::log_error(
+                &format!(
+                    "Giving up on {} alert, retry in {}s is past the shutdown deadline: {}",
+                    delivery.channel, wait.as_secs(), delivery.summary
+                ),
+                None,
+                None,
+            );
+            Metrics::global().increment(metrics::ALERT_DELIVERY_FAILED);
+            return DeliveryOutcome::Failed;
+        }
+    }
+
+    StructuredLogger::log_error(
+        &format!(
+            "Giving up on {} alert after {} attempts: {}",
+            delivery.channel, max_attempts, delivery.summary
+        ),
+        None,
+        None,
+    );
+    Metrics::global().increment(metrics::ALERT_DELIVERY_FAILED);
+    DeliveryOutcome::Failed
+}
+
+/// Sleep until the next attempt, false when it would wake up after the
+/// deadline set by `flush`
+async fn wait_for_retry(wait: Duration, shutdown: &mut watch::Receiver<Option<Instant>>) -> bool {
+    let wake_at = Instant::now() + wait;
+    loop {
+        if shutdown.borrow_and_update().is_some_and(|deadline| wake_at > deadline) {
+            return false;
+        }
+        tokio::select! {
+            _ = sleep_until(wake_at) => return true,
+            changed = shutdown.changed() => {
+                if changed.is_err() {
+                    sleep_until(wake_at).await;
+                    return true;
+                }
+            }
+        }
+    }
+}
+
+/// Exponential backoff capped at `max_backoff_secs`
+fn backoff(config: &AlertDeliveryConfig, attempt: u32) -> Duration {
+    let factor = 2u64.saturating_pow(attempt.saturating_sub(1));
+    let delay = Duration::from_millis(config.initial_backoff_ms.saturating_mul(factor));
+    delay.min(Duration::from_secs(config.max_backoff_secs))
+}
+
+/// Telegram puts the wait time in `parameters.retry_after`
+fn telegram_retry_after(body: &str) -> Option<u64> {
+    serde_json::from_str::<Value>(body)
+        .ok()?
+        .pointer("/parameters/retry_after")?
+        .as_u64()
+}
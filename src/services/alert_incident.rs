@@ -0,0 +1,183 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::config::AppConfig;
+use crate::providers::http_client::HttpClientFactory;
+use crate::providers::StructuredLogger;
+use crate::services::{AlertContext, TelegramAlertService};
+
+/// Number of resolved incidents kept for the admin API
+const RESOLVED_HISTORY_LIMIT: usize = 50;
+
+static INCIDENT_TRACKER: OnceLock<IncidentTracker> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IncidentStatus {
+    Open,
+    Resolved,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Incident {
+    /// Name of the alert rule that opened the incident
+    pub name: String,
+    pub title: String,
+    pub status: IncidentStatus,
+    pub opened_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub affected_count: u64,
+    pub last_detail: String,
+}
+
+impl Incident {
+    pub fn duration_secs(&self) -> i64 {
+        let end = self.resolved_at.unwrap_or_else(Utc::now);
+        (end - self.opened_at).num_seconds().max(0)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IncidentSnapshot {
+    pub open: Vec<Incident>,
+    pub resolved: Vec<Incident>,
+}
+
+/// Tracks alert rules as stateful incidents: one alert when the rule starts
+/// breaching and one resolved alert when it recovers
+#[derive(Clone)]
+pub struct IncidentTracker {
+    open: Arc<Mutex<HashMap<String, Incident>>>,
+    resolved: Arc<Mutex<VecDeque<Incident>>>,
+    alert_service: Option<TelegramAlertService>,
+}
+
+impl IncidentTracker {
+    pub fn new(alert_service: Option<TelegramAlertService>) -> Self {
+        Self {
+            open: Arc::new(Mutex::new(HashMap::new())),
+            resolved: Arc::new(Mutex::new(VecDeque::new())),
+            alert_service,
+        }
+    }
+
+    /// Process-wide tracker, initialized from the first config it sees
+    pub fn global(config: &AppConfig) -> &'static IncidentTracker {
+        INCIDENT_TRACKER.get_or_init(|| {
+            let clients = HttpClientFactory::global(config);
+            Self::new(TelegramAlertService::with_clients(config.clone(), clients).ok())
+        })
+    }
+
+    /// Open an incident for `name` or add `affected` to the one already open.
+    /// `sample` is the latest failing request, used to enrich the open alert
+    pub fn record_breach(&self, name: &str, title: &str, detail: &str, affected: u64, sample: Option<AlertContext>) {
+        let mut open = self.open.lock().unwrap();
+
+        if let Some(incident) = open.get_mut(name) {
+            incident.affected_count += affected;
+            incident.last_detail = detail.to_string();
+            return;
+        }
+
+        let incident = Incident {
+            name: name.to_string(),
+            title: title.to_string(),
+            status: IncidentStatus::Open,
+            opened_at: Utc::now(),
+            resolved_at: None,
+            affected_count: affected,
+            last_detail: detail.to_string(),
+        };
+        open.insert(name.to_string(), incident);
+        drop(open);
+
+        StructuredLogger::log_error(
+            &format!("Incident opened: {} - {}", title, detail),
+            None,
+            None,
+        );
+
+        if let Some(alert_service) = &self.alert_service {
+            let mut context = sample.unwrap_or_default().with_title(format!("[OPEN] {}", title));
+            context.message = detail.to_string();
+            alert_service.send_alert(&context);
+        }
+    }
+
+    pub fn record_recovery(&self, name: &str) {
+        let incident = {
+            let mut open = self.open.lock().unwrap();
+            match open.remove(name) {
+                Some(mut incident) => {
+                    incident.status = IncidentStatus::Resolved;
+                    incident.resolved_at = Some(Utc::now());
+                    incident
+                }
+                None => return,
+            }
+        };
+
+        let message = format!(
+            "{} recovered after {}, {} affected",
+            incident.title,
+            format_duration(incident.duration_secs()),
+            incident.affected_count
+        );
+
+        StructuredLogger::log_info(
+            &format!("Incident resolved: {}", message),
+            None,
+            None,
+            None,
+        );
+
+        if let Some(alert_service) = &self.alert_service {
+            alert_service.send_resolved_alert(&message);
+        }
+
+        let mut resolved = self.resolved.lock().unwrap();
+        resolved.push_front(incident);
+        resolved.truncate(RESOLVED_HISTORY_LIMIT);
+    }
+
+    pub fn is_open(&self, name: &str) -> bool {
+        self.open.lock().unwrap().contains_key(name)
+    }
+
+    /// Flush pending open/resolved notifications, used on graceful shutdown
+    pub async fn flush_alerts(&self, deadline: Duration) -> bool {
+        match &self.alert_service {
+            Some(alert_service) => alert_service.flush(deadline).await,
+            None => true,
+        }
+    }
+
+    pub fn snapshot(&self) -> IncidentSnapshot {
+        let mut open: Vec<Incident> = self.open.lock().unwrap().values().cloned().collect();
+        open.sort_by_key(|incident| incident.opened_at);
+
+        IncidentSnapshot {
+            open,
+            resolved: self.resolved.lock().unwrap().iter().cloned().collect(),
+        }
+    }
+}
+
+fn format_duration(total_secs: i64) -> String {
+    let hours = total_secs / 3600;
+    let minutes = (total_secs % 3600) / 60;
+    let seconds = total_secs % 60;
+
+    if hours > 0 {
+        format!("{}h {}m {}s", hours, minutes, seconds)
+    } else if minutes > 0 {
+        format!("{}m {}s", minutes, seconds)
+    } else {
+        format!("{}s", seconds)
+    }
+}
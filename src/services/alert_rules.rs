@@ -0,0 +1,249 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{Datelike, FixedOffset, Timelike, Utc, Weekday};
+use tokio::time::{interval, MissedTickBehavior};
+
+use crate::config::{AlertRuleConfig, AlertRuleKind, AlertingConfig, AppConfig, BusinessHoursConfig};
+use crate::providers::metrics::{self, Metrics};
+use crate::providers::StructuredLogger;
+use crate::services::alert_template::latest_alert_sample;
+use crate::services::IncidentTracker;
+
+/// Built-in rules, see `effective_rules`
+pub fn default_rules() -> Vec<AlertRuleConfig> {
+    vec![
+        AlertRuleConfig {
+            name: "permata_callback_failing".to_string(),
+            title: Some("Permata callbacks failing".to_string()),
+            kind: AlertRuleKind::ErrorRate,
+            counter: metrics::PERMATA_CALLBACK_FAILURE.to_string(),
+            total_counter: Some(metrics::PERMATA_CALLBACK_TOTAL.to_string()),
+            threshold: 5.0,
+            window_secs: 300,
+            min_events: 5,
+            business_hours: None,
+        },
+        AlertRuleConfig {
+            name: "token_refresh_failing".to_string(),
+            title: Some("Token refresh failing".to_string()),
+            kind: AlertRuleKind::Count,
+            counter: metrics::TOKEN_REFRESH_FAILURE.to_string(),
+            total_counter: None,
+            threshold: 0.0,
+            window_secs: 300,
+            min_events: 0,
+            business_hours: None,
+        },
+        AlertRuleConfig {
+            name: "json_parse_errors".to_string(),
+            title: Some("JSON parse errors".to_string()),
+            kind: AlertRuleKind::Count,
+            counter: metrics::JSON_PARSE_ERROR.to_string(),
+            total_counter: None,
+            threshold: 10.0,
+            window_secs: 60,
+            min_events: 0,
+            business_hours: None,
+        },
+        AlertRuleConfig {
+            name: "gateway_saturated".to_string(),
+            title: Some("Gateway saturated, webhooks rejected".to_string()),
+            kind: AlertRuleKind::Count,
+            counter: metrics::BACKPRESSURE_REJECTED.to_string(),
+            total_counter: None,
+            threshold: 10.0,
+            window_secs: 60,
+            min_events: 0,
+            business_hours: None,
+        },
+    ]
+}
+
+/// Built-in rules plus `alerting.rules`; a configured rule replaces the
+/// built-in one with the same name
+pub fn effective_rules(config: &AlertingConfig) -> Vec<AlertRuleConfig> {
+    let mut rules = if config.include_default_rules {
+        default_rules()
+    } else {
+        Vec::new()
+    };
+    for rule in &config.rules {
+        match rules.iter_mut().find(|existing| existing.name == rule.name) {
+            Some(existing) => *existing = rule.clone(),
+            None => rules.push(rule.clone()),
+        }
+    }
+    rules
+}
+
+/// Evaluates threshold rules over the sliding window metrics and feeds the
+/// results into the incident tracker
+#[derive(Clone)]
+pub struct AlertRuleEngine {
+    rules: Vec<AlertRuleConfig>,
+    evaluation_interval: Duration,
+    metrics: Metrics,
+    tracker: IncidentTracker,
+    last_totals: Arc<Mutex<HashMap<String, u64>>>,
+    handle: Arc<Mutex<Option<tokio::task::JoinHandle<()>>>>,
+}
+
+impl AlertRuleEngine {
+    pub fn new(config: &AppConfig) -> Self {
+        Self::with_components(
+            effective_rules(&config.alerting),
+            Duration::from_secs(config.alerting.evaluation_interval_secs.max(1)),
+            Metrics::global().clone(),
+            IncidentTracker::global(config).clone(),
+        )
+    }
+
+    pub fn with_components(
+        rules: Vec<AlertRuleConfig>,
+        evaluation_interval: Duration,
+        metrics: Metrics,
+        tracker: IncidentTracker,
+    ) -> Self {
+        Self {
+            rules,
+            evaluation_interval,
+            metrics,
+            tracker,
+            last_totals: Arc::new(Mutex::new(HashMap::new())),
+            handle: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub fn rules(&self) -> &[AlertRuleConfig] {
+        &self.rules
+    }
+
+    /// Start periodic evaluation in the background
+    pub fn start(&self) {
+        self.stop();
+
+        let engine = self.clone();
+        let handle = tokio::spawn(async move {
+            let mut timer = interval(engine.evaluation_interval);
+            timer.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+            loop {
+                timer.tick().await;
+                engine.evaluate_all();
+            }
+        });
+
+        *self.handle.lock().unwrap() = Some(handle);
+    }
+
+    pub fn stop(&self) {
+        if let Some(handle) = self.handle.lock().unwrap().take() {
+            handle.abort();
+        }
+    }
+
+    pub fn shutdown(&self) {
+        StructuredLogger::log_info(
+            "Shutting down AlertRuleEngine",
+            None,
+            None,
+            None,
+        );
+        self.stop();
+    }
+
+    pub fn evaluate_all(&self) {
+        for rule in &self.rules {
+            let affected = self.new_events(rule);
+
+            match self.evaluate(rule) {
+                Some(detail) => {
+                    let title = rule.title.as_deref().unwrap_or(&rule.name);
+                    let sample = latest_alert_sample(&rule.counter);
+                    self.tracker.record_breach(&rule.name, title, &detail, affected, sample);
+                }
+                None => self.tracker.record_recovery(&rule.name),
+            }
+        }
+    }
+
+    /// Returns a description of the breach, or `None` when the rule is healthy
+    pub fn evaluate(&self, rule: &AlertRuleConfig) -> Option<String> {
+        if let Some(business_hours) = &rule.business_hours {
+            if !within_business_hours(business_hours) {
+                return None;
+            }
+        }
+
+        match rule.kind {
+            AlertRuleKind::ErrorRate => {
+                let total_counter = rule.total_counter.as_deref()?;
+                let total = self.metrics.count_in_window(total_counter, rule.window_secs);
+                if total == 0 || total < rule.min_events {
+                    return None;
+                }
+
+                let errors = self.metrics.count_in_window(&rule.counter, rule.window_secs);
+                let rate = errors as f64 * 100.0 / total as f64;
+                (rate > rule.threshold).then(|| {
+                    format!(
+                        "error rate {:.1}% ({}/{}) over {}s exceeds {}%",
+                        rate, errors, total, rule.window_secs, rule.threshold
+                    )
+                })
+            }
+            AlertRuleKind::Count => {
+                let count = self.metrics.count_in_window(&rule.counter, rule.window_secs);
+                (count as f64 > rule.threshold).then(|| {
+                    format!(
+                        "{} {} events over {}s exceeds {}",
+                        count, rule.counter, rule.window_secs, rule.threshold
+                    )
+                })
+            }
+            AlertRuleKind::Absence => {
+                let uptime = (Utc::now() - self.metrics.started_at()).num_seconds();
+                if uptime < rule.window_secs as i64 {
+                    return None;
+                }
+
+                let count = self.metrics.count_in_window(&rule.counter, rule.window_secs);
+                (count == 0).then(|| {
+                    format!("no {} events for {}s", rule.counter, rule.window_secs)
+                })
+            }
+        }
+    }
+
+    /// Events counted towards an incident since the previous evaluation
+    fn new_events(&self, rule: &AlertRuleConfig) -> u64 {
+        let counter = match rule.kind {
+            AlertRuleKind::Absence => match rule.total_counter.as_deref() {
+                Some(counter) => counter,
+                None => return 0,
+            },
+            _ => rule.counter.as_str(),
+        };
+
+        let total = self.metrics.total(counter);
+        let mut last_totals = self.last_totals.lock().unwrap();
+        let previous = last_totals.insert(rule.name.clone(), total).unwrap_or(0);
+        total.saturating_sub(previous)
+    }
+}
+
+fn within_business_hours(business_hours: &BusinessHoursConfig) -> bool {
+    let offset = FixedOffset::east_opt(business_hours.utc_offset_hours * 3600)
+        .unwrap_or_else(|| FixedOffset::east_opt(0).unwrap());
+    let now = Utc::now().with_timezone(&offset);
+
+    is_business_time(business_hours, now.weekday(), now.hour())
+}
+
+pub fn is_business_time(business_hours: &BusinessHoursConfig, weekday: Weekday, hour: u32) -> bool {
+    business_hours.days.contains(&weekday)
+        && hour >= business_hours.start_hour
+        && hour < business_hours.end_hour
+}
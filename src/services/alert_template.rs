@@ -0,0 +1,326 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::config::AlertMessageFormat;
+use crate::models::{PermataWebhookResponse, WhatsAppWebhook};
+use crate::utils::{describe_whatsapp_error, navigate_json_path};
+
+pub const DEFAULT_PLAIN_TEMPLATE: &str = "{{prefix}} {{title}}
+{{message}}
+Error: {{error}}
+Request ID: {{request_id}}
+Phone number ID: {{phone_number_id}}
+Message ID: {{message_id}}
+Recipient: {{recipient_id}}
+Status: {{status}}
+Permata: {{permata_status_code}} {{permata_status_desc}}
+WhatsApp error {{error_code}}: {{error_description}}";
+
+pub const DEFAULT_MARKDOWN_V2_TEMPLATE: &str = "*{{prefix}} {{title}}*
+{{message}}
+*Error:* {{error}}
+*Request ID:* {{request_id}}
+*Phone number ID:* {{phone_number_id}}
+*Message ID:* {{message_id}}
+*Recipient:* {{recipient_id}}
+*Status:* {{status}}
+*Permata:* {{permata_status_code}} {{permata_status_desc}}
+*WhatsApp error {{error_code}}:* {{error_description}}";
+
+pub const DEFAULT_HTML_TEMPLATE: &str = "<b>{{prefix}} {{title}}</b>
+{{message}}
+<b>Error:</b> {{error}}
+<b>Request ID:</b> {{request_id}}
+<b>Phone number ID:</b> {{phone_number_id}}
+<b>Message ID:</b> {{message_id}}
+<b>Recipient:</b> {{recipient_id}}
+<b>Status:</b> {{status}}
+<b>Permata:</b> {{permata_status_code}} {{permata_status_desc}}
+<b>WhatsApp error {{error_code}}:</b> {{error_description}}";
+
+pub const DEFAULT_SLACK_TEMPLATE: &str = "*{{prefix}} {{title}}*
+{{message}}
+*Error:* {{error}}
+*Request ID:* {{request_id}}
+*Phone number ID:* {{phone_number_id}}
+*Message ID:* {{message_id}}
+*Status:* {{status}}
+*Permata:* {{permata_status_code}} {{permata_status_desc}}
+*WhatsApp error {{error_code}}:* {{error_description}}";
+
+static ALERT_SAMPLES: OnceLock<Mutex<HashMap<String, AlertContext>>> = OnceLock::new();
+
+/// Variables available to alert templates
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AlertContext {
+    pub title: Option<String>,
+    pub message: String,
+    pub error: Option<String>,
+    pub request_id: Option<String>,
+    pub phone_number_id: Option<String>,
+    pub message_id: Option<String>,
+    pub recipient_id: Option<String>,
+    pub status: Option<String>,
+    pub permata_status_code: Option<String>,
+    pub permata_status_desc: Option<String>,
+    pub error_code: Option<String>,
+    pub error_title: Option<String>,
+}
+
+impl AlertContext {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn with_error(mut self, error: impl Into<String>) -> Self {
+        self.error = Some(error.into());
+        self
+    }
+
+    pub fn with_request_id(mut self, request_id: Option<&str>) -> Self {
+        self.request_id = request_id.map(|s| s.to_string());
+        self
+    }
+
+    /// Pull ids, status and Meta error details out of a WhatsApp webhook payload
+    pub fn with_webhook_payload(self, payload: &Value) -> Self {
+        match WhatsAppWebhook::from_json(payload) {
+            Some(webhook) => self.with_whatsapp(&webhook),
+            None => self.with_payload_paths(payload),
+        }
+    }
+
+    /// Path based extraction for payloads that do not match the typed model,
+    /// picks up whatever fields are still present
+    fn with_payload_paths(mut self, payload: &Value) -> Self {
+        let value_path = ["entry", "[*]", "changes", "[*]", "value"];
+        let first_str = |suffix: &[&str]| -> Option<String> {
+            let path: Vec<&str> = value_path.iter().chain(suffix.iter()).copied().collect();
+            navigate_json_path(payload, &path)
+                .into_iter()
+                .find_map(json_scalar_to_string)
+        };
+
+        self.phone_number_id = first_str(&["metadata", "phone_number_id"]);
+        self.message_id = first_str(&["statuses", "[*]", "id"])
+            .or_else(|| first_str(&["messages", "[*]", "id"]));
+        self.recipient_id = first_str(&["statuses", "[*]", "recipient_id"])
+            .or_else(|| first_str(&["messages", "[*]", "from"]));
+        self.status = first_str(&["statuses", "[*]", "status"]);
+
+        self.error_code = first_str(&["statuses", "[*]", "errors", "[*]", "code"])
+            .or_else(|| first_str(&["errors", "[*]", "code"]))
+            .or_else(|| payload.pointer("/error/code").and_then(json_scalar_to_string));
+        self.error_title = first_str(&["statuses", "[*]", "errors", "[*]", "title"])
+            .or_else(|| first_str(&["errors", "[*]", "title"]))
+            .or_else(|| payload.pointer("/error/message").and_then(json_scalar_to_string));
+
+        self
+    }
+
+    /// Same as `with_webhook_payload` for the typed view
+    pub fn with_whatsapp(mut self, webhook: &WhatsAppWebhook) -> Self {
+        let non_empty = |value: &Option<String>| value.clone().filter(|s| !s.is_empty());
+        let messages = || webhook.values().flat_map(|value| value.messages.iter());
+        let status_errors = || webhook.statuses().flat_map(|status| status.errors.iter());
+        let value_errors = || webhook.values().flat_map(|value| value.errors.iter());
+
+        self.phone_number_id = webhook
+            .values()
+            .filter_map(|value| value.metadata.as_ref())
+            .find_map(|metadata| non_empty(&metadata.phone_number_id));
+        self.message_id = webhook
+            .statuses()
+            .find_map(|status| non_empty(&status.id))
+            .or_else(|| messages().find_map(|message| non_empty(&message.id)));
+        self.recipient_id = webhook
+            .statuses()
+            .find_map(|status| non_empty(&status.recipient_id))
+            .or_else(|| messages().find_map(|message| non_empty(&message.from)));
+        self.status = webhook.statuses().find_map(|status| non_empty(&status.status));
+
+        let error = webhook.error.as_ref();
+        self.error_code = status_errors()
+            .chain(value_errors())
+            .find_map(|error| error.code.map(|code| code.to_string()))
+            .or_else(|| error.and_then(|e| e.get("code")).and_then(json_scalar_to_string));
+        self.error_title = status_errors()
+            .chain(value_errors())
+            .find_map(|error| non_empty(&error.title))
+            .or_else(|| error.and_then(|e| e.get("message")).and_then(json_scalar_to_string));
+
+        self
+    }
+
+    /// Same as `with_webhook_payload` for a raw body, ignored when not JSON
+    pub fn with_webhook_body(self, body: &str) -> Self {
+        match serde_json::from_str::<Value>(body) {
+            Ok(payload) => self.with_webhook_payload(&payload),
+            Err(_) => self,
+        }
+    }
+
+    /// Attach Permata's `StatusCode`/`StatusDesc` when the body has them
+    pub fn with_permata_response(mut self, body: &str) -> Self {
+        if let Ok(response) = serde_json::from_str::<PermataWebhookResponse>(body) {
+            self.permata_status_code = Some(response.status_code);
+            self.permata_status_desc = Some(response.status_desc);
+        }
+        self
+    }
+
+    /// Catalog description of the WhatsApp error code, falling back to Meta's title
+    pub fn error_description(&self) -> Option<String> {
+        self.error_code
+            .as_deref()
+            .and_then(|code| code.parse::<i64>().ok())
+            .and_then(describe_whatsapp_error)
+            .map(|description| description.to_string())
+            .or_else(|| self.error_title.clone())
+    }
+
+    pub fn variables(&self, prefix: &str) -> HashMap<&'static str, String> {
+        let optional = |value: &Option<String>| value.clone().unwrap_or_default();
+
+        HashMap::from([
+            ("prefix", prefix.to_string()),
+            ("title", optional(&self.title)),
+            ("message", self.message.clone()),
+            ("error", optional(&self.error)),
+            ("request_id", optional(&self.request_id)),
+            ("phone_number_id", optional(&self.phone_number_id)),
+            ("message_id", optional(&self.message_id)),
+            ("recipient_id", optional(&self.recipient_id)),
+            ("status", optional(&self.status)),
+            ("permata_status_code", optional(&self.permata_status_code)),
+            ("permata_status_desc", optional(&self.permata_status_desc)),
+            ("error_code", optional(&self.error_code)),
+            ("error_title", optional(&self.error_title)),
+            ("error_description", self.error_description().unwrap_or_default()),
+        ])
+    }
+}
+
+fn json_scalar_to_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) if !s.is_empty() => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// Escape function for the target markup of a channel
+pub type Escaper = fn(&str) -> String;
+
+pub fn escape_plain(text: &str) -> String {
+    text.to_string()
+}
+
+/// Telegram MarkdownV2: every reserved character outside entities needs a backslash
+pub fn escape_markdown_v2(text: &str) -> String {
+    const RESERVED: &[char] = &[
+        '\\', '_', '*', '[', ']', '(', ')', '~', '`', '>', '#', '+', '-', '=', '|', '{', '}', '.', '!',
+    ];
+
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if RESERVED.contains(&c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Slack mrkdwn only reserves the control characters
+pub fn escape_slack(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+pub fn escaper_for(format: AlertMessageFormat) -> Escaper {
+    match format {
+        AlertMessageFormat::Plain => escape_plain,
+        AlertMessageFormat::MarkdownV2 => escape_markdown_v2,
+        AlertMessageFormat::Html => escape_html,
+    }
+}
+
+pub fn default_template_for(format: AlertMessageFormat) -> &'static str {
+    match format {
+        AlertMessageFormat::Plain => DEFAULT_PLAIN_TEMPLATE,
+        AlertMessageFormat::MarkdownV2 => DEFAULT_MARKDOWN_V2_TEMPLATE,
+        AlertMessageFormat::Html => DEFAULT_HTML_TEMPLATE,
+    }
+}
+
+/// Render `{{variable}}` placeholders with escaped values. The template text
+/// itself is already in the target markup and is left untouched. Lines whose
+/// placeholders all resolve to empty values are dropped.
+pub fn render_template(template: &str, variables: &HashMap<&'static str, String>, escape: Escaper) -> String {
+    let mut lines = Vec::new();
+
+    for line in template.lines() {
+        let mut rendered = String::with_capacity(line.len());
+        let mut rest = line;
+        let mut placeholders = 0;
+        let mut filled = 0;
+
+        while let Some(start) = rest.find("{{") {
+            let Some(end) = rest[start + 2..].find("}}") else {
+                break;
+            };
+            let name = rest[start + 2..start + 2 + end].trim();
+            let value = variables.get(name).map(String::as_str).unwrap_or("");
+
+            placeholders += 1;
+            if !value.is_empty() {
+                filled += 1;
+            }
+
+            rendered.push_str(&rest[..start]);
+            rendered.push_str(&escape(value));
+            rest = &rest[start + 2 + end + 2..];
+        }
+        rendered.push_str(rest);
+
+        if placeholders > 0 && filled == 0 {
+            continue;
+        }
+        lines.push(rendered.trim_end().to_string());
+    }
+
+    lines.join("\n")
+}
+
+/// Remember the latest failing request for a metrics counter, so alerts
+/// opened by rules over that counter can show a concrete example
+pub fn record_alert_sample(counter: &str, context: AlertContext) {
+    let samples = ALERT_SAMPLES.get_or_init(|| Mutex::new(HashMap::new()));
+    samples.lock().unwrap().insert(counter.to_string(), context);
+}
+
+pub fn latest_alert_sample(counter: &str) -> Option<AlertContext> {
+    ALERT_SAMPLES
+        .get()
+        .and_then(|samples| samples.lock().unwrap().get(counter).cloned())
+}
@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::config::BackpressureConfig;
+use crate::providers::metrics::{self, Metrics};
+use crate::services::alert_template::{record_alert_sample, AlertContext};
+
+/// Why a webhook was turned away
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RejectReason {
+    GlobalLimit,
+    TenantQueueFull,
+    TenantQueueTimeout,
+}
+
+impl RejectReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::GlobalLimit => "global_limit",
+            Self::TenantQueueFull => "tenant_queue_full",
+            Self::TenantQueueTimeout => "tenant_queue_timeout",
+        }
+    }
+}
+
+struct TenantLimit {
+    permits: Arc<Semaphore>,
+    queued: AtomicUsize,
+}
+
+impl TenantLimit {
+    /// No webhook holds, waits for or is about to take a slot
+    fn is_idle(limit: &Arc<TenantLimit>, max_in_flight: usize) -> bool {
+        Arc::strong_count(limit) == 1
+            && limit.queued.load(Ordering::SeqCst) == 0
+            && limit.permits.available_permits() == max_in_flight
+    }
+}
+
+/// Global and per-tenant concurrency limits of `webhook_handler`. The global
+/// limit never waits; a tenant over its limit waits in a bounded queue. Tenants
+/// come from the request body, so idle ones are dropped once `max_tenants` are
+/// tracked; an idle tenant has no state worth keeping.
+pub struct Backpressure {
+    config: BackpressureConfig,
+    global: Arc<Semaphore>,
+    tenants: Mutex<HashMap<String, Arc<TenantLimit>>>,
+}
+
+impl Backpressure {
+    pub fn new(config: BackpressureConfig) -> Self {
+        Self {
+            global: Arc::new(Semaphore::new(config.max_in_flight.max(1))),
+            config,
+            tenants: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    pub fn retry_after_secs(&self) -> u64 {
+        self.config.retry_after_secs
+    }
+
+    /// Tenants with a limit entry
+    pub fn tracked_tenants(&self) -> usize {
+        self.tenants.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    /// Webhooks currently holding a global slot
+    pub fn in_flight(&self) -> usize {
+        self.config.max_in_flight.max(1) - self.global.available_permits()
+    }
+
+    /// Global slot, taken before the body is read. `None` when disabled.
+    pub fn try_acquire_global(&self) -> Result<Option<OwnedSemaphorePermit>, RejectReason> {
+        if !self.enabled() {
+            return Ok(None);
+        }
+        Arc::clone(&self.global)
+            .try_acquire_owned()
+            .map(Some)
+            .map_err(|_| RejectReason::GlobalLimit)
+    }
+
+    /// Tenant slot, waiting up to `queue_timeout_ms` behind at most
+    /// `tenant_queue_depth` other webhooks of the same tenant
+    pub async fn acquire_tenant(&self, tenant: &str) -> Result<Option<OwnedSemaphorePermit>, RejectReason> {
+        if !self.enabled() {
+            return Ok(None);
+        }
+        let limit = self.tenant(tenant);
+        if let Ok(permit) = Arc::clone(&limit.permits).try_acquire_owned() {
+            return Ok(Some(permit));
+        }
+
+        if limit.queued.fetch_add(1, Ordering::SeqCst) >= self.config.tenant_queue_depth {
+            limit.queued.fetch_sub(1, Ordering::SeqCst);
+            return Err(RejectReason::TenantQueueFull);
+        }
+        let waited = tokio::time::timeout(
+            Duration::from_millis(self.config.queue_timeout_ms),
+            Arc::clone(&limit.permits).acquire_owned(),
+        )
+        .await;
+        limit.queued.fetch_sub(1, Ordering::SeqCst);
+
+        match waited {
+            Ok(Ok(permit)) => Ok(Some(permit)),
+            _ => Err(RejectReason::TenantQueueTimeout),
+        }
+    }
+
+    /// Count the rejection and leave a sample for the alert rules
+    pub fn record_rejection(&self, reason: RejectReason, tenant: Option<&str>, request_id: &str) {
+        Metrics::global().increment(metrics::BACKPRESSURE_REJECTED);
+        record_alert_sample(
+            metrics::BACKPRESSURE_REJECTED,
+            AlertContext::new("Gateway saturated, webhooks rejected with 503")
+                .with_error(format!("{} (tenant {})", reason.as_str(), tenant.unwrap_or("-")))
+                .with_request_id(Some(request_id)),
+        );
+    }
+
+    fn tenant(&self, tenant: &str) -> Arc<TenantLimit> {
+        let mut tenants = self.tenants.lock().unwrap_or_else(|e| e.into_inner());
+        if !tenants.contains_key(tenant) && tenants.len() >= self.config.max_tenants.max(1) {
+            // Tenant yang sibuk dibatasi slot global, jadi sisa map tetap terbatas
+            let max_in_flight = self.config.tenant_max_in_flight.max(1);
+            tenants.retain(|_, limit| !TenantLimit::is_idle(limit, max_in_flight));
+        }
+        Arc::clone(tenants.entry(tenant.to_string()).or_insert_with(|| {
+            Arc::new(TenantLimit {
+                permits: Arc::new(Semaphore::new(self.config.tenant_max_in_flight.max(1))),
+                queued: AtomicUsize::new(0),
+            })
+        }))
+    }
+}
@@ -0,0 +1,91 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Default)]
+struct DedupState {
+    seen: HashMap<String, Instant>,
+    // (key, claimed at) in insertion order, for TTL and capacity eviction
+    order: VecDeque<(String, Instant)>,
+}
+
+/// In-memory set of delivered and in-flight event keys, so a webhook
+/// redelivered by Meta does not send the same status or message to Permata twice
+#[derive(Debug)]
+pub struct EventDeduplicator {
+    ttl: Duration,
+    capacity: usize,
+    state: Mutex<DedupState>,
+}
+
+impl EventDeduplicator {
+    pub fn new(ttl: Duration, capacity: usize) -> Self {
+        Self {
+            ttl,
+            capacity: capacity.max(1),
+            state: Mutex::new(DedupState::default()),
+        }
+    }
+
+    /// Claim a key before sending its event, false when it was delivered or is
+    /// being delivered within the TTL. Check and insert happen under one lock
+    /// so concurrent redeliveries of the same event send it only once.
+    pub fn claim(&self, key: &str) -> bool {
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        self.evict(&mut state, now);
+
+        if state.seen.contains_key(key) {
+            return false;
+        }
+        state.seen.insert(key.to_string(), now);
+        state.order.push_back((key.to_string(), now));
+        while state.seen.len() > self.capacity {
+            match state.order.pop_front() {
+                Some((oldest, at)) => Self::forget(&mut state, &oldest, at),
+                None => break,
+            }
+        }
+        true
+    }
+
+    /// Give a claim back after the event was not delivered, so a redelivery
+    /// sends it again
+    pub fn release(&self, key: &str) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(at) = state.seen.remove(key) else {
+            return;
+        };
+        // Klaim yang dilepas biasanya yang terbaru, jadi dicari dari belakang
+        if let Some(position) = state.order.iter().rposition(|(existing, claimed)| existing == key && *claimed == at) {
+            state.order.remove(position);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap_or_else(|e| e.into_inner()).seen.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn evict(&self, state: &mut DedupState, now: Instant) {
+        while let Some((key, at)) = state.order.front() {
+            if now.duration_since(*at) < self.ttl {
+                break;
+            }
+            let (key, at) = (key.clone(), *at);
+            state.order.pop_front();
+            Self::forget(state, &key, at);
+        }
+    }
+
+    /// Remove a key only when it is still the claim made at `at`, not a later
+    /// claim of the same key
+    fn forget(state: &mut DedupState, key: &str, at: Instant) {
+        if state.seen.get(key) == Some(&at) {
+            state.seen.remove(key);
+        }
+    }
+}
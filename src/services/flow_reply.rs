@@ -0,0 +1,205 @@
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::config::{FlowFieldType, FlowReplyConfig, FlowReplyDecodeMode, FlowSchemaConfig};
+use crate::models::InboundWebhook;
+use crate::providers::metrics::{self, Metrics};
+use crate::providers::StructuredLogger;
+
+pub const FLOW_TOKEN_FIELD: &str = "flow_token";
+/// Added to `nfm_reply` when `response_json` cannot be decoded or validated
+pub const DECODE_ERROR_FIELD: &str = "decode_error";
+
+pub const MALFORMED_RESPONSE_JSON: &str = "malformed_response_json";
+pub const SCHEMA_VIOLATION: &str = "schema_violation";
+
+/// Why one flow reply could not be decoded, forwarded to Permata as `decode_error`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FlowReplyError {
+    pub code: &'static str,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flow_token: Option<String>,
+}
+
+/// Decodes `interactive.nfm_reply.response_json` of Inbound Flow payloads and
+/// validates the result against the schema of its `flow_token`
+#[derive(Debug, Clone)]
+pub struct FlowReplyDecoder {
+    config: FlowReplyConfig,
+}
+
+impl FlowReplyDecoder {
+    pub fn new(config: FlowReplyConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.config.decode != FlowReplyDecodeMode::Off
+    }
+
+    /// Webhook to forward: the same one when decoding is off or nothing changed,
+    /// otherwise a copy whose body (and signature input) has the decoded replies
+    pub fn apply(&self, webhook: InboundWebhook) -> InboundWebhook {
+        if !self.enabled() {
+            return webhook;
+        }
+        let Some(mut payload) = webhook.json.clone() else {
+            return webhook;
+        };
+
+        let (decoded, errors) = self.decode(&mut payload);
+        if decoded == 0 && errors.is_empty() {
+            return webhook;
+        }
+
+        if decoded > 0 {
+            Metrics::global().add(metrics::FLOW_REPLY_DECODED, decoded as u64);
+        }
+        for error in &errors {
+            Metrics::global().increment(metrics::FLOW_REPLY_INVALID);
+            StructuredLogger::log_warning(
+                &format!("Invalid flow reply ({}): {}", error.code, error.message),
+                Some(&webhook.request_id),
+                Some(&webhook.request_id),
+            );
+        }
+
+        webhook.with_json(payload)
+    }
+
+    /// Decode every flow reply in the payload in place. Returns the number of
+    /// decoded replies and the errors attached to the others.
+    pub fn decode(&self, payload: &mut Value) -> (usize, Vec<FlowReplyError>) {
+        let mut decoded = 0;
+        let mut errors = Vec::new();
+
+        for nfm_reply in nfm_replies(payload) {
+            match self.decode_reply(nfm_reply) {
+                Ok(true) => decoded += 1,
+                Ok(false) => {}
+                Err(error) => {
+                    nfm_reply.insert(DECODE_ERROR_FIELD.to_string(), json!(error));
+                    errors.push(error);
+                }
+            }
+        }
+
+        (decoded, errors)
+    }
+
+    fn decode_reply(&self, nfm_reply: &mut serde_json::Map<String, Value>) -> Result<bool, FlowReplyError> {
+        let data = match nfm_reply.get("response_json") {
+            Some(Value::String(text)) => serde_json::from_str::<Value>(text).map_err(|e| FlowReplyError {
+                code: MALFORMED_RESPONSE_JSON,
+                message: format!("response_json is not valid JSON: {}", e),
+                flow_token: None,
+            })?,
+            // Sudah berupa object, mis. payload yang dikirim ulang
+            Some(Value::Object(_)) => return Ok(false),
+            _ => return Ok(false),
+        };
+
+        let Value::Object(fields) = &data else {
+            return Err(FlowReplyError {
+                code: MALFORMED_RESPONSE_JSON,
+                message: "response_json is not a JSON object".to_string(),
+                flow_token: None,
+            });
+        };
+
+        let flow_token = fields.get(FLOW_TOKEN_FIELD).and_then(Value::as_str).map(|s| s.to_string());
+        let validation = match flow_token.as_ref().and_then(|token| self.config.schemas.get(token)) {
+            Some(schema) => validate(schema, fields).map_err(|message| FlowReplyError {
+                code: SCHEMA_VIOLATION,
+                message,
+                flow_token: flow_token.clone(),
+            }),
+            None => Ok(()),
+        };
+
+        match self.config.decode {
+            FlowReplyDecodeMode::Replace => {
+                nfm_reply.insert("response_json".to_string(), data);
+            }
+            FlowReplyDecodeMode::Sibling => {
+                nfm_reply.insert(self.config.decoded_field.clone(), data);
+            }
+            FlowReplyDecodeMode::Off => {}
+        }
+
+        validation.map(|_| true)
+    }
+}
+
+/// Check decoded reply fields against a flow schema
+pub fn validate(schema: &FlowSchemaConfig, fields: &serde_json::Map<String, Value>) -> Result<(), String> {
+    let mut problems = Vec::new();
+
+    for name in &schema.required {
+        if !fields.contains_key(name) {
+            problems.push(format!("missing required field '{}'", name));
+        }
+    }
+
+    let mut names: Vec<&String> = fields.keys().collect();
+    names.sort();
+    for name in names {
+        match schema.properties.get(name) {
+            Some(expected) if !matches_type(*expected, &fields[name]) => {
+                let type_name = format!("{:?}", expected).to_lowercase();
+                problems.push(format!("field '{}' must be {}", name, type_name));
+            }
+            None if schema.deny_unknown && name != FLOW_TOKEN_FIELD => {
+                problems.push(format!("unknown field '{}'", name));
+            }
+            _ => {}
+        }
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(problems.join(", "))
+    }
+}
+
+fn matches_type(expected: FlowFieldType, value: &Value) -> bool {
+    match expected {
+        FlowFieldType::String => value.is_string(),
+        FlowFieldType::Number => value.is_number(),
+        FlowFieldType::Boolean => value.is_boolean(),
+        FlowFieldType::Object => value.is_object(),
+        FlowFieldType::Array => value.is_array(),
+    }
+}
+
+/// `data.entry[*].changes[*].value.messages[*].interactive.nfm_reply` objects
+fn nfm_replies(payload: &mut Value) -> Vec<&mut serde_json::Map<String, Value>> {
+    let mut replies = Vec::new();
+    let entries = payload.get_mut("data").and_then(|data| data.get_mut("entry"));
+    for entry in each(entries) {
+        for change in each(entry.get_mut("changes")) {
+            let messages = change.get_mut("value").and_then(|value| value.get_mut("messages"));
+            for message in each(messages) {
+                let Some(interactive) = message.get_mut("interactive") else {
+                    continue;
+                };
+                if interactive.get("type").and_then(Value::as_str) != Some("nfm_reply") {
+                    continue;
+                }
+                if let Some(Value::Object(nfm_reply)) = interactive.get_mut("nfm_reply") {
+                    replies.push(nfm_reply);
+                }
+            }
+        }
+    }
+    replies
+}
+
+fn each(value: Option<&mut Value>) -> Vec<&mut Value> {
+    match value {
+        Some(Value::Array(items)) => items.iter_mut().collect(),
+        _ => Vec::new(),
+    }
+}
@@ -3,9 +3,33 @@ pub mod permata_callbackstatus_client;
 pub mod permata_login;
 pub mod token_scheduler;
 pub mod telegram_alert;
+pub mod alert_dispatcher;
+pub mod alert_incident;
+pub mod alert_rules;
+pub mod alert_template;
+pub mod event_dedup;
+pub mod status_order;
+pub mod status_coalescer;
+pub mod partitioned_dispatcher;
+pub mod backpressure;
+pub mod flow_reply;
+pub mod response_classifier;
+pub mod response_policy;
 
-pub use webhook_processor::{WebhookProcessor, WebhookProcessorTrait};
+pub use webhook_processor::{FanOutSummary, WebhookProcessor, WebhookProcessorTrait};
 pub use permata_callbackstatus_client::PermataCallbackStatusClient;
 pub use permata_login::LoginHandler;
 pub use token_scheduler::{TokenScheduler, SchedulerConfig};
-pub use telegram_alert::TelegramAlertService;
\ No newline at end of file
+pub use telegram_alert::TelegramAlertService;
+pub use alert_dispatcher::AlertDispatcher;
+pub use alert_incident::{Incident, IncidentStatus, IncidentTracker};
+pub use alert_rules::AlertRuleEngine;
+pub use alert_template::AlertContext;
+pub use event_dedup::EventDeduplicator;
+pub use status_order::{GuardedWebhook, StatusOrderGuard};
+pub use status_coalescer::StatusCoalescer;
+pub use partitioned_dispatcher::PartitionedDispatcher;
+pub use backpressure::Backpressure;
+pub use flow_reply::{FlowReplyDecoder, FlowReplyError};
+pub use response_classifier::{ResponseClass, ResponseClassifier};
+pub use response_policy::{MetaReply, ResponsePolicy};
\ No newline at end of file
@@ -0,0 +1,140 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tokio::sync::{mpsc, oneshot, watch};
+use tokio::task::JoinHandle;
+use tracing::{Instrument, Span};
+
+use crate::config::DispatchConfig;
+use crate::models::InboundWebhook;
+use crate::providers::metrics::{self, Metrics};
+use crate::providers::StructuredLogger;
+use crate::services::webhook_processor::WebhookResponse;
+use crate::services::WebhookProcessorTrait;
+use crate::utils::error::{AppError, Result};
+
+struct Job {
+    webhook: InboundWebhook,
+    reply: oneshot::Sender<Result<WebhookResponse>>,
+    /// Request span of the handler, the worker runs the job inside it
+    span: Span,
+}
+
+/// Runs an inner processor on a fixed set of partitions, each with one worker
+/// and a bounded backlog. Webhooks with the same ordering key (customer or
+/// conversation) always land on the same partition and are forwarded in arrival
+/// order; different keys are forwarded in parallel.
+pub struct PartitionedDispatcher {
+    partitions: Vec<mpsc::Sender<Job>>,
+    stop: watch::Sender<bool>,
+    workers: Mutex<Vec<JoinHandle<()>>>,
+}
+
+impl PartitionedDispatcher {
+    /// Spawn the partition workers, must be called inside a tokio runtime
+    pub fn start(config: &DispatchConfig, processor: Arc<dyn WebhookProcessorTrait + Send + Sync>) -> Self {
+        let (stop, _) = watch::channel(false);
+        let (partitions, workers) = (0..config.partitions.max(1))
+            .map(|_| {
+                let (sender, mut receiver) = mpsc::channel::<Job>(config.partition_backlog.max(1));
+                let processor = Arc::clone(&processor);
+                let mut stop = stop.subscribe();
+                let worker = tokio::spawn(async move {
+                    loop {
+                        tokio::select! {
+                            job = receiver.recv() => match job {
+                                Some(job) => run_job(processor.as_ref(), job).await,
+                                None => break,
+                            },
+                            _ = stopped(&mut stop) => {
+                                // Job baru ditolak, yang sudah antri sudah dijawab ke Meta dan tetap dikirim
+                                receiver.close();
+                                while let Some(job) = receiver.recv().await {
+                                    run_job(processor.as_ref(), job).await;
+                                }
+                                break;
+                            }
+                        }
+                    }
+                });
+                (sender, worker)
+            })
+            .unzip();
+
+        Self { partitions, stop, workers: Mutex::new(workers) }
+    }
+
+    /// Stop taking webhooks and wait until the queued ones are forwarded, false
+    /// when the workers did not finish before `deadline`
+    pub async fn shutdown(&self, deadline: Duration) -> bool {
+        self.stop.send_replace(true);
+        let workers = std::mem::take(&mut *self.workers.lock().unwrap_or_else(|e| e.into_inner()));
+        tokio::time::timeout(deadline, async {
+            for worker in workers {
+                let _ = worker.await;
+            }
+        })
+        .await
+        .is_ok()
+    }
+
+    pub fn partition_count(&self) -> usize {
+        self.partitions.len()
+    }
+
+    /// Partition of a webhook, from its ordering key
+    pub fn partition_of(&self, webhook: &InboundWebhook) -> usize {
+        let mut hasher = DefaultHasher::new();
+        ordering_key(webhook).hash(&mut hasher);
+        (hasher.finish() % self.partitions.len() as u64) as usize
+    }
+}
+
+async fn stopped(stop: &mut watch::Receiver<bool>) {
+    let _ = stop.wait_for(|stopped| *stopped).await;
+}
+
+async fn run_job(processor: &(dyn WebhookProcessorTrait + Send + Sync), job: Job) {
+    let result = processor.process_webhook(&job.webhook).instrument(job.span).await;
+    // Pengirim bisa sudah pergi (client disconnect), hasil tetap dibuang
+    let _ = job.reply.send(result);
+}
+
+/// Customer or conversation id of the webhook, the request id when there is none
+pub fn ordering_key(webhook: &InboundWebhook) -> &str {
+    webhook
+        .whatsapp
+        .as_ref()
+        .and_then(|whatsapp| whatsapp.customer_id())
+        .unwrap_or(&webhook.request_id)
+}
+
+#[async_trait]
+impl WebhookProcessorTrait for PartitionedDispatcher {
+    async fn process_webhook(&self, webhook: &InboundWebhook) -> Result<WebhookResponse> {
+        let partition = self.partition_of(webhook);
+        let (reply, response) = oneshot::channel();
+
+        let job = Job { webhook: webhook.clone(), reply, span: Span::current() };
+        if let Err(e) = self.partitions[partition].try_send(job) {
+            let message = match e {
+                mpsc::error::TrySendError::Full(_) => format!("partition {} backlog is full", partition),
+                mpsc::error::TrySendError::Closed(_) => format!("partition {} is shut down", partition),
+            };
+            StructuredLogger::log_warning(
+                &format!("Rejecting webhook, {}", message),
+                Some(&webhook.request_id),
+                Some(&webhook.request_id),
+            );
+            Metrics::global().increment(metrics::DISPATCH_REJECTED);
+            return Err(AppError::overloaded(message));
+        }
+
+        response
+            .await
+            .map_err(|_| AppError::error(format!("partition {} worker stopped", partition)))?
+    }
+}
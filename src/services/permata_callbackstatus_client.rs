@@ -1,17 +1,31 @@
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use reqwest::Client;
 use tokio::time::sleep;
+use tracing::{field, Instrument, Span};
 
-use crate::config::AppConfig;
-use crate::services::{LoginHandler, TelegramAlertService};
-use crate::providers::StructuredLogger;
+use crate::config::{AppConfig, ResponseOutcome};
+use crate::models::InboundWebhook;
+use crate::services::alert_template::{record_alert_sample, AlertContext};
+use crate::services::{LoginHandler, ResponseClassifier};
+use crate::providers::audit_log::{AuditLogger, PermataExchange};
+use crate::providers::http_client::{Destination, HttpClientFactory};
+use crate::providers::otlp::inject_traceparent;
+use crate::providers::{metrics, Metrics, StructuredLogger};
 use crate::utils::{error::Result, generate_signature, compact_json};
 
 #[derive(Debug, Clone)]
 pub struct HttpWebhookResponse {
     pub status_code: u16,
     pub body: String,
+    /// Classification of the last attempt, see `ResponseClassifier`
+    pub outcome: ResponseOutcome,
+}
+
+impl HttpWebhookResponse {
+    pub fn is_success(&self) -> bool {
+        self.outcome == ResponseOutcome::Success
+    }
 }
 
 #[derive(Clone)]
@@ -19,22 +33,28 @@ pub struct PermataCallbackStatusClient {
     client: Client,
     config: AppConfig,
     login_handler: LoginHandler,
+    classifier: ResponseClassifier,
 }
 
 impl PermataCallbackStatusClient {
     pub fn new(config: AppConfig) -> Result<Self> {
-        let timeout = Duration::from_secs(config.webclient.timeout);
-        let client = Client::builder()
-            .timeout(timeout)
-            .build()?;
+        Self::with_http_clients(config.clone(), &HttpClientFactory::new(&config))
+    }
 
-        let login_handler = LoginHandler::new(config.clone())?;
+    /// Login and callback calls share the Permata client of the factory
+    pub fn with_http_clients(config: AppConfig, clients: &HttpClientFactory) -> Result<Self> {
+        let client = clients.client(Destination::Permata)?;
+        let login_handler = LoginHandler::with_client(config.clone(), client.clone())?;
+        Ok(Self::with_client(config, client, login_handler))
+    }
 
-        Ok(Self {
+    pub fn with_client(config: AppConfig, client: Client, login_handler: LoginHandler) -> Self {
+        Self {
             client,
+            classifier: ResponseClassifier::new(config.permata_response.clone()),
             config,
             login_handler,
-        })
+        }
     }
 
     pub async fn send_webhook(&self, webhook_body: &str, request_id: &str) -> Result<HttpWebhookResponse> {
@@ -42,19 +62,63 @@ impl PermataCallbackStatusClient {
     }
 
     pub async fn send_webhook_with_context(&self, webhook_body: &str, request_id: &str, unique_id: Option<&str>, x_request_id: Option<&str>) -> Result<HttpWebhookResponse> {
+        // Compact the JSON to remove spaces and newlines for the signature
+        let compacted_body = compact_json(webhook_body)?;
+        self.send_with_retries(webhook_body, &compacted_body, request_id, unique_id, x_request_id).await
+    }
+
+    /// Forward a parsed webhook, reusing its body and compacted signature input
+    pub async fn send_inbound(&self, webhook: &InboundWebhook) -> Result<HttpWebhookResponse> {
+        let request_id = webhook.request_id.as_str();
+        self.send_with_retries(&webhook.body_str(), &webhook.compact_body, request_id, Some(request_id), Some(request_id))
+            .await
+    }
+
+    async fn send_with_retries(
+        &self,
+        webhook_body: &str,
+        compacted_body: &str,
+        request_id: &str,
+        unique_id: Option<&str>,
+        x_request_id: Option<&str>,
+    ) -> Result<HttpWebhookResponse> {
         let webclient_config = &self.config.webclient;
         
         let mut last_error = None;
         
         for attempt in 1..=webclient_config.max_retries {
-            match self.make_webhook_request(webhook_body, request_id, unique_id, x_request_id).await {
-                Ok(response) => {
-                    StructuredLogger::log_info(
-                        &format!("Webhook sent successfully on attempt {} for request {}", attempt, request_id),
+            let attempt_span = tracing::info_span!(
+                "permata_callback_attempt",
+                attempt,
+                otel.kind = "client",
+                http.status_code = field::Empty,
+                otel.status_code = field::Empty
+            );
+            match self.make_webhook_request(webhook_body, compacted_body, request_id, attempt, unique_id, x_request_id)
+                .instrument(attempt_span)
+                .await
+            {
+                Ok(response) if response.outcome == ResponseOutcome::RetryableFailure
+                    && attempt < webclient_config.max_retries =>
+                {
+                    StructuredLogger::log_warning(
+                        &format!("Webhook attempt {} got a retryable response (HTTP {}) for request {}, retrying in {}s",
+                            attempt, response.status_code, request_id, webclient_config.retry_delay),
                         unique_id,
                         x_request_id,
-                        None,
                     );
+                    Metrics::global().increment(metrics::PERMATA_CALLBACK_RETRIED);
+                    sleep(Duration::from_secs(webclient_config.retry_delay)).await;
+                }
+                Ok(response) => {
+                    if response.is_success() {
+                        StructuredLogger::log_info(
+                            &format!("Webhook sent successfully on attempt {} for request {}", attempt, request_id),
+                            unique_id,
+                            x_request_id,
+                            None,
+                        );
+                    }
                     return Ok(response);
                 }
                 Err(e) => {
@@ -65,14 +129,24 @@ impl PermataCallbackStatusClient {
                             unique_id,
                             x_request_id,
                         );
+                        Metrics::global().increment(metrics::PERMATA_AUTH_FAILURE);
+                        record_alert_sample(
+                            metrics::PERMATA_AUTH_FAILURE,
+                            AlertContext::new("Permata authentication failed")
+                                .with_error(e.to_string())
+                                .with_request_id(x_request_id)
+                                .with_webhook_body(webhook_body),
+                        );
                         return Ok(HttpWebhookResponse {
                             status_code: 401,
-                            body: Some(e.to_string()).unwrap_or_default(),
+                            body: e.to_string(),
+                            outcome: ResponseOutcome::PermanentFailure,
                         });
                     }
                     
                     last_error = Some(e);
                     if attempt < webclient_config.max_retries {
+                        Metrics::global().increment(metrics::PERMATA_CALLBACK_RETRIED);
                         StructuredLogger::log_warning(
                             &format!("Webhook attempt {} failed for request {}, retrying in {}s", 
                                 attempt, request_id, webclient_config.retry_delay),
@@ -97,7 +171,9 @@ impl PermataCallbackStatusClient {
     async fn make_webhook_request(
         &self,
         webhook_body: &str,
+        compacted_body: &str,
         request_id: &str,
+        attempt: u32,
         unique_id: Option<&str>,
         x_request_id: Option<&str>,
     ) -> Result<HttpWebhookResponse> {
@@ -110,14 +186,12 @@ impl PermataCallbackStatusClient {
                                 .format("%Y-%m-%dT%H:%M:%S%.3f+07:00")
                                 .to_string();
 
-        // Generate signature using permata_static_key:timestamp:webhook_body
-        // First, compact the JSON to remove spaces and newlines
-        let compacted_body = compact_json(webhook_body)?;
+        // Generate signature using permata_static_key:timestamp:compacted webhook_body
         let signature = generate_signature(
             &self.config.permata_bank_login.permata_static_key,
             &access_token,
             &timestamp,
-            &compacted_body
+            compacted_body
         )?;
 
         StructuredLogger::log_info(
@@ -126,19 +200,22 @@ impl PermataCallbackStatusClient {
             x_request_id,
             None,
         );
-        
-        let response = match self.client
-            .post(&self.config.permata_bank_webhook.callbackstatus_url)
+
+        Metrics::global().increment(metrics::PERMATA_CALLBACK_TOTAL);
+
+        let started = Instant::now();
+        let response = match inject_traceparent(self.client.post(&self.config.permata_bank_webhook.callbackstatus_url))
             .header("Content-Type", "application/json")
             .header("Authorization", format!("Bearer {}", access_token))
             .header("permata-signature", signature)
             .header("organizationname", &self.config.permata_bank_webhook.organizationname)
-            .header("permata-timestamp", timestamp)
+            .header("permata-timestamp", &timestamp)
             .body(webhook_body.to_string())
             .send()
             .await
         {
             Err(e) => {
+                Span::current().record("otel.status_code", "error");
                 let error_message = "Request timeout/connection error for Permata Bank";
                 
                 StructuredLogger::log_error(
@@ -147,43 +224,102 @@ impl PermataCallbackStatusClient {
                     x_request_id,
                 );
 
+                if let Some(audit) = AuditLogger::global() {
+                    let error = e.to_string();
+                    audit.record_permata_exchange(request_id, &PermataExchange {
+                        attempt,
+                        signed_body: compacted_body,
+                        permata_timestamp: &timestamp,
+                        error: Some(&error),
+                        latency: started.elapsed(),
+                        ..Default::default()
+                    });
+                }
+
+                Metrics::global().increment(metrics::PERMATA_CALLBACK_FAILURE);
+                record_alert_sample(
+                    metrics::PERMATA_CALLBACK_FAILURE,
+                    AlertContext::new(error_message)
+                        .with_error(format!("{}: {}", error_message, e))
+                        .with_request_id(x_request_id)
+                        .with_webhook_body(webhook_body),
+                );
+
                 return Err(crate::utils::error::AppError::error(error_message));
             },
             Ok(resp) => resp,
         };
 
         let status_code = response.status().as_u16();
+        Span::current().record("http.status_code", status_code);
         let body = response.text().await.unwrap_or_default();
-        
-        // Log based on status code type
-        if status_code >= 200 && status_code < 300 {
+        let class = self.classifier.classify(status_code, &body);
+        if !class.is_success() {
+            Span::current().record("otel.status_code", "error");
+        }
+
+        if let Some(audit) = AuditLogger::global() {
+            audit.record_permata_exchange(request_id, &PermataExchange {
+                attempt,
+                signed_body: compacted_body,
+                permata_timestamp: &timestamp,
+                status_code: Some(status_code),
+                response_body: Some(&body),
+                latency: started.elapsed(),
+                ..Default::default()
+            });
+        }
+
+        // Log berdasarkan klasifikasi, HTTP 200 dengan StatusCode error tetap gagal
+        if class.is_success() {
             StructuredLogger::log_info(
                 &format!("Received HTTP {} from Permata Bank for request {}", status_code, request_id),
                 unique_id,
                 x_request_id,
                 None,
             );
+            Metrics::global().increment(metrics::PERMATA_CALLBACK_SUCCESS);
         } else {
-            let error_message = format!("Received Error {} from Permata Bank for: {}", status_code, body);
-            
+            let (title, error_message) = if (200..300).contains(&status_code) {
+                let code = class.status_code.as_deref().unwrap_or("-");
+                Metrics::global().increment(metrics::PERMATA_STATUS_REJECTED);
+                (
+                    format!("Permata Bank rejected callback with StatusCode {}", code),
+                    format!("Received StatusCode {} ({}) in HTTP {} from Permata Bank for: {}",
+                        code, class.status_desc.as_deref().unwrap_or(""), status_code, body),
+                )
+            } else {
+                (
+                    format!("Received non-2xx HTTP {} from Permata Bank", status_code),
+                    format!("Received Error {} from Permata Bank for: {}", status_code, body),
+                )
+            };
+
             StructuredLogger::log_error(
                 &error_message,
                 unique_id,
                 x_request_id,
             );
-            
-            // Send telegram alert for non-2xx status
-            if let Ok(telegram_service) = TelegramAlertService::new(self.config.clone()) {
-                telegram_service.send_error_alert(
-                    format!("Received non-2xx HTTP {} from Permata Bank", status_code).as_str(),
-                    x_request_id
-                );
+
+            // Alerting dievaluasi dari counters oleh AlertRuleEngine
+            let sample = AlertContext::new(title)
+                .with_error(error_message)
+                .with_request_id(x_request_id)
+                .with_webhook_body(webhook_body)
+                .with_permata_response(&body);
+
+            Metrics::global().increment(metrics::PERMATA_CALLBACK_FAILURE);
+            if status_code == 401 || status_code == 403 {
+                Metrics::global().increment(metrics::PERMATA_AUTH_FAILURE);
+                record_alert_sample(metrics::PERMATA_AUTH_FAILURE, sample.clone());
             }
+            record_alert_sample(metrics::PERMATA_CALLBACK_FAILURE, sample);
         }
         
         Ok(HttpWebhookResponse {
             status_code,
             body,
+            outcome: class.outcome,
         })
     }
 
@@ -4,12 +4,16 @@ use std::time::{Duration, Instant};
 
 use reqwest::Client;
 use tokio::time::sleep;
+use tracing::{field, Instrument, Span};
 
 use crate::config::{AppConfig, PermataBankLoginConfig};
 use crate::models::TokenResponse;
-use crate::providers::StructuredLogger;
+use crate::providers::http_client::{Destination, HttpClientFactory};
+use crate::providers::otlp::inject_traceparent;
+use crate::providers::{metrics, Metrics, StructuredLogger};
 use crate::utils::{error::Result, generate_signature};
-use crate::services::{TokenScheduler, TelegramAlertService};
+use crate::services::alert_template::{record_alert_sample, AlertContext};
+use crate::services::TokenScheduler;
 
 #[derive(Clone)]
 pub struct LoginHandler {
@@ -27,11 +31,11 @@ struct CachedToken {
 
 impl LoginHandler {
     pub fn new(config: AppConfig) -> Result<Self> {
-        let timeout = Duration::from_secs(config.webclient.timeout);
-        let client = Client::builder()
-            .timeout(timeout)
-            .build()?;
-        
+        let client = HttpClientFactory::new(&config).client(Destination::Permata)?;
+        Self::with_client(config, client)
+    }
+
+    pub fn with_client(config: AppConfig, client: Client) -> Result<Self> {
         let scheduler = TokenScheduler::with_config(config.token_scheduler.clone());
 
         let handler = Self {
@@ -52,6 +56,11 @@ impl LoginHandler {
     }
 
     pub async fn get_token_with_context(&self, unique_id: Option<&str>, request_id: Option<&str>) -> Result<String> {
+        let span = tracing::info_span!("token_acquisition", cache_hit = field::Empty, otel.status_code = field::Empty);
+        self.acquire_token(unique_id, request_id).instrument(span).await
+    }
+
+    async fn acquire_token(&self, unique_id: Option<&str>, request_id: Option<&str>) -> Result<String> {
         let cache_key = "permata_bank_token";
         
         // Check cache first
@@ -59,6 +68,7 @@ impl LoginHandler {
             let cache = self.token_cache.lock().unwrap();
             if let Some(cached_token) = cache.get(cache_key) {
                 if cached_token.expires_at > Instant::now() {
+                    Span::current().record("cache_hit", true);
                     StructuredLogger::log_info(
                         "Using cached token",
                         unique_id,
@@ -77,7 +87,14 @@ impl LoginHandler {
             request_id,
             None,
         );
-        let token_response = self.login_with_context(unique_id, request_id).await?;
+        Span::current().record("cache_hit", false);
+        let token_response = match self.login_with_context(unique_id, request_id).await {
+            Ok(token_response) => token_response,
+            Err(e) => {
+                Span::current().record("otel.status_code", "error");
+                return Err(e);
+            }
+        };
         
         // Cache the token (subtract 5 minutes from expires_in for safety)
         let expires_at = Instant::now() + Duration::from_secs(token_response.expires_in.saturating_sub(300));
@@ -133,7 +150,14 @@ impl LoginHandler {
         let mut last_error = None;
         
         for attempt in 1..=webclient_config.max_retries {
-            match self.make_login_request_with_context(login_config, unique_id, request_id).await {
+            let attempt_span = tracing::info_span!(
+                "permata_login_request",
+                attempt,
+                otel.kind = "client",
+                http.status_code = field::Empty,
+                otel.status_code = field::Empty
+            );
+            match self.make_login_request_with_context(login_config, unique_id, request_id).instrument(attempt_span).await {
                 Ok(response) => {
                     StructuredLogger::log_info(
                         &format!("Login successful on attempt {}", attempt),
@@ -141,6 +165,7 @@ impl LoginHandler {
                         request_id,
                         None,
                     );
+                    Metrics::global().increment(metrics::TOKEN_REFRESH_SUCCESS);
                     return Ok(response);
                 }
                 Err(e) => {
@@ -158,13 +183,21 @@ impl LoginHandler {
                             unique_id,
                             request_id,
                         );
-        
                     }
                 }
             }
         }
-        
-        Err(last_error.unwrap())
+
+        let error = last_error.unwrap();
+        Metrics::global().increment(metrics::TOKEN_REFRESH_FAILURE);
+        record_alert_sample(
+            metrics::TOKEN_REFRESH_FAILURE,
+            AlertContext::new("All login attempts failed")
+                .with_error(error.to_string())
+                .with_request_id(request_id),
+        );
+
+        Err(error)
     }
 
     async fn make_login_request_with_context(&self, config: &PermataBankLoginConfig, unique_id: Option<&str>, request_id: Option<&str>) -> Result<TokenResponse> {
@@ -186,8 +219,7 @@ impl LoginHandler {
             &config.login_payload
         )?;
 
-        let response = self.client
-            .post(&config.token_url)
+        let response = inject_traceparent(self.client.post(&config.token_url))
             .header("Authorization", auth_header)
             .header("OAUTH-Signature", signature)
             .header("Content-Type", "application/x-www-form-urlencoded")
@@ -197,7 +229,9 @@ impl LoginHandler {
             .send()
             .await?;
 
+        Span::current().record("http.status_code", response.status().as_u16());
         if !response.status().is_success() {
+            Span::current().record("otel.status_code", "error");
             let status = response.status();
             let body = response.text().await.unwrap_or_default();
             let error_message = format!("Login request failed with status {}: {}", status, body);
@@ -208,14 +242,6 @@ impl LoginHandler {
                 request_id,
             );
             
-            // Send telegram alert for individual login request failures
-            if let Ok(telegram_service) = TelegramAlertService::new(self.config.clone()) {
-                telegram_service.send_error_alert(
-                    &error_message,
-                    request_id
-                );
-            }
-            
             return Err(crate::utils::error::AppError::error(body));
         }
 
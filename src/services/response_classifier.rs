@@ -0,0 +1,52 @@
+use crate::config::{PermataResponseConfig, ResponseOutcome};
+use crate::models::PermataWebhookResponse;
+
+/// Outcome of one Permata callback response with the `StatusCode` it was based on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResponseClass {
+    pub outcome: ResponseOutcome,
+    pub status_code: Option<String>,
+    pub status_desc: Option<String>,
+}
+
+impl ResponseClass {
+    pub fn is_success(&self) -> bool {
+        self.outcome == ResponseOutcome::Success
+    }
+}
+
+/// Maps Permata's HTTP status and `StatusCode` to success, retryable or
+/// permanent failure. An HTTP 200 carrying an error code is a failure.
+#[derive(Debug, Clone)]
+pub struct ResponseClassifier {
+    config: PermataResponseConfig,
+}
+
+impl ResponseClassifier {
+    pub fn new(config: PermataResponseConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn classify(&self, http_status: u16, body: &str) -> ResponseClass {
+        let response = serde_json::from_str::<PermataWebhookResponse>(body).ok();
+        let status_code = response.as_ref().map(|r| r.status_code.trim().to_string());
+        let status_desc = response.map(|r| r.status_desc);
+
+        let mapped = status_code.as_ref().and_then(|code| self.config.status_codes.get(code)).copied();
+        let outcome = match mapped {
+            Some(outcome) => outcome,
+            None if (200..300).contains(&http_status) => match status_code {
+                Some(_) => self.config.unknown_status_code,
+                None => self.config.missing_status_code,
+            },
+            None if self.config.retryable_http_statuses.contains(&http_status) => ResponseOutcome::RetryableFailure,
+            None => ResponseOutcome::PermanentFailure,
+        };
+
+        ResponseClass {
+            outcome,
+            status_code,
+            status_desc,
+        }
+    }
+}
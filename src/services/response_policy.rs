@@ -0,0 +1,181 @@
+use serde_json::{json, Value};
+
+use crate::config::{AppConfig, MetaResponseBody, MetaResponseConfig, MetaResponseMode, MetaResponseRule, ResponseOutcome};
+use crate::models::InboundWebhook;
+use crate::providers::dead_letter::{DeadLetter, DeadLetterLog};
+use crate::providers::logging::StructuredLogger;
+use crate::providers::metrics::{self, Metrics};
+use crate::services::webhook_processor::WebhookResponse;
+use crate::utils::error::{AppError, Result};
+
+/// Response `webhook_handler` sends back to Meta
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetaReply {
+    /// Audit outcome: `forwarded`, `accepted` or `error`
+    pub outcome: &'static str,
+    pub http_status: u16,
+    pub body: Value,
+    /// Gateway overloaded, answered with `Retry-After`
+    pub retry_after: bool,
+}
+
+/// Chooses what Meta sees for a processed webhook. A failed delivery is only
+/// answered with 2xx once it is dead-lettered, otherwise it passes through so
+/// Meta keeps the event and retries.
+pub struct ResponsePolicy {
+    config: MetaResponseConfig,
+    dead_letter: Option<DeadLetterLog>,
+}
+
+impl ResponsePolicy {
+    pub fn new(config: &AppConfig) -> Result<Self> {
+        let policy = &config.meta_response;
+        let dead_letter = if policy.mode == MetaResponseMode::Passthrough || policy.dead_letter_file.is_empty() {
+            None
+        } else {
+            Some(DeadLetterLog::with_file(config, &policy.dead_letter_file)?)
+        };
+
+        Ok(Self {
+            config: policy.clone(),
+            dead_letter,
+        })
+    }
+
+    pub fn respond(&self, webhook: &InboundWebhook, result: &Result<WebhookResponse>) -> MetaReply {
+        let (outcome, passthrough) = match result {
+            Ok(response) => (
+                Some(response.outcome),
+                MetaReply {
+                    outcome: "forwarded",
+                    http_status: response.http_status,
+                    body: response_json(&response.body),
+                    retry_after: false,
+                },
+            ),
+            Err(e) => {
+                let overloaded = matches!(e, AppError::Overloaded { .. });
+                (
+                    None,
+                    MetaReply {
+                        outcome: "error",
+                        http_status: if overloaded { 503 } else { 500 },
+                        body: json!({
+                            "StatusCode": "06",
+                            "StatusDesc": e.to_string()
+                        }),
+                        retry_after: overloaded,
+                    },
+                )
+            }
+        };
+
+        // Gateway penuh bukan masalah bank, Meta tetap harus mundur dan kirim ulang
+        if passthrough.retry_after {
+            return passthrough;
+        }
+
+        let rule = match self.config.mode {
+            MetaResponseMode::Passthrough => return passthrough,
+            MetaResponseMode::AlwaysOk if outcome == Some(ResponseOutcome::Success) => MetaResponseRule {
+                status: 200,
+                body: None,
+            },
+            MetaResponseMode::AlwaysOk => MetaResponseRule {
+                status: 200,
+                body: Some(MetaResponseBody {
+                    status_code: "00".to_string(),
+                    status_desc: "Accepted".to_string(),
+                }),
+            },
+            MetaResponseMode::Mapped => match self.mapped_rule(outcome) {
+                Some(rule) => rule.clone(),
+                None => return passthrough,
+            },
+        };
+
+        let acknowledges_failure = (200..300).contains(&rule.status) && outcome != Some(ResponseOutcome::Success);
+        if acknowledges_failure && !self.dead_letter(webhook, result) {
+            return passthrough;
+        }
+
+        MetaReply {
+            outcome: if acknowledges_failure { "accepted" } else { passthrough.outcome },
+            http_status: rule.status,
+            body: match rule.body {
+                Some(body) => json!({
+                    "StatusCode": body.status_code,
+                    "StatusDesc": body.status_desc
+                }),
+                None => passthrough.body,
+            },
+            retry_after: false,
+        }
+    }
+
+    fn mapped_rule(&self, outcome: Option<ResponseOutcome>) -> Option<&MetaResponseRule> {
+        let mappings = &self.config.mappings;
+        match outcome {
+            Some(ResponseOutcome::Success) => mappings.success.as_ref(),
+            Some(ResponseOutcome::RetryableFailure) => mappings.retryable_failure.as_ref(),
+            Some(ResponseOutcome::PermanentFailure) => mappings.permanent_failure.as_ref(),
+            None => mappings.error.as_ref(),
+        }
+    }
+
+    /// Store the undelivered webhook before Meta is told it was accepted
+    fn dead_letter(&self, webhook: &InboundWebhook, result: &Result<WebhookResponse>) -> bool {
+        let request_id = webhook.request_id.as_str();
+        let Some(dead_letter) = &self.dead_letter else {
+            StructuredLogger::log_warning(
+                "No dead letter file for undelivered webhook, passing Permata response through",
+                Some(request_id),
+                Some(request_id),
+            );
+            return false;
+        };
+
+        let (status_code, error) = match result {
+            Ok(response) => (Some(response.http_status), response.body.clone()),
+            Err(e) => (None, e.to_string()),
+        };
+        let recorded = dead_letter.record(&DeadLetter {
+            request_id,
+            event_request_id: request_id,
+            event_key: None,
+            payload: &webhook.body_str(),
+            status_code,
+            error: Some(&error),
+        });
+
+        match recorded {
+            Ok(()) => {
+                StructuredLogger::log_warning(
+                    &format!("Webhook {} dead-lettered and acknowledged to Meta: {}", request_id, error),
+                    Some(request_id),
+                    Some(request_id),
+                );
+                Metrics::global().increment(metrics::DEAD_LETTERED);
+                true
+            }
+            Err(e) => {
+                StructuredLogger::log_error(
+                    &format!("Failed to dead-letter webhook {}, passing Permata response through: {}", request_id, e),
+                    Some(request_id),
+                    Some(request_id),
+                );
+                false
+            }
+        }
+    }
+}
+
+/// Permata's body as JSON, a non-JSON body is wrapped as an error description
+fn response_json(body: &str) -> Value {
+    serde_json::from_str(body).unwrap_or_else(|_| {
+        json!({
+            "StatusCode": "06",
+            "StatusDesc": body
+        })
+    })
+}
@@ -0,0 +1,188 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::config::{ResponseOutcome, StatusCoalesceConfig};
+use crate::models::{InboundWebhook, PayloadClassification};
+use crate::providers::dead_letter::{DeadLetter, DeadLetterLog};
+use crate::providers::metrics::{self, Metrics};
+use crate::providers::StructuredLogger;
+use crate::services::status_order::status_rank;
+use crate::services::WebhookProcessorTrait;
+
+/// What the handler does with a receipt offered to the coalescer
+#[derive(Debug, Clone)]
+pub enum CoalesceDecision {
+    /// Forward now
+    Forward(InboundWebhook),
+    /// Held, or superseded by a held receipt; acknowledged without a callback
+    Held(InboundWebhook),
+}
+
+struct Pending {
+    webhook: InboundWebhook,
+    rank: u8,
+}
+
+/// Debounces delivery receipts per WhatsApp message id. The first `sent`,
+/// `delivered` or `read` starts a hold window; later receipts replace the held
+/// one when they are at least as advanced, and only the held receipt is sent to
+/// Permata when the window ends. `failed` and other statuses are never delayed.
+/// A held receipt was already acknowledged to Meta, so one that still fails after
+/// the processor's retries is dead-lettered; without a dead letter file nothing
+/// is held.
+pub struct StatusCoalescer {
+    config: StatusCoalesceConfig,
+    processor: Arc<dyn WebhookProcessorTrait + Send + Sync>,
+    dead_letter: Option<DeadLetterLog>,
+    pending: Mutex<HashMap<String, Pending>>,
+}
+
+impl StatusCoalescer {
+    pub fn new(
+        config: StatusCoalesceConfig,
+        processor: Arc<dyn WebhookProcessorTrait + Send + Sync>,
+        dead_letter: Option<DeadLetterLog>,
+    ) -> Self {
+        Self {
+            config,
+            processor,
+            dead_letter,
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.config.enabled && self.dead_letter.is_some()
+    }
+
+    /// Number of messages with a held receipt
+    pub fn pending(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Hold the receipt or hand it back for forwarding. Only receipts with a
+    /// single status are coalesced, batched payloads are forwarded as they are.
+    pub fn offer(self: &Arc<Self>, webhook: InboundWebhook) -> CoalesceDecision {
+        if !self.enabled() || webhook.classification != PayloadClassification::DeliveryReceipt {
+            return CoalesceDecision::Forward(webhook);
+        }
+        let Some((message_id, status)) = single_status(&webhook) else {
+            return CoalesceDecision::Forward(webhook);
+        };
+
+        let Some(rank) = status_rank(&status) else {
+            // Status final seperti failed langsung dikirim, status yang ditahan sudah tidak relevan
+            if self.lock().remove(&message_id).is_some() {
+                Metrics::global().increment(metrics::STATUS_COALESCED);
+            }
+            return CoalesceDecision::Forward(webhook);
+        };
+
+        let mut pending = self.lock();
+        if let Some(held) = pending.get_mut(&message_id) {
+            Metrics::global().increment(metrics::STATUS_COALESCED);
+            if rank >= held.rank {
+                held.webhook = webhook.clone();
+                held.rank = rank;
+            }
+            return CoalesceDecision::Held(webhook);
+        }
+        if pending.len() >= self.config.max_pending {
+            return CoalesceDecision::Forward(webhook);
+        }
+
+        pending.insert(message_id.clone(), Pending { webhook: webhook.clone(), rank });
+        drop(pending);
+
+        let coalescer = Arc::clone(self);
+        let window = Duration::from_millis(self.config.hold_window_ms);
+        // Release tetap di span request yang menahan receipt, supaya log membawa request id-nya
+        StructuredLogger::spawn_in_current_span(async move {
+            tokio::time::sleep(window).await;
+            coalescer.release(&message_id).await;
+        });
+
+        CoalesceDecision::Held(webhook)
+    }
+
+    /// Forward every held receipt now, used on shutdown
+    pub async fn flush(&self) {
+        let held: Vec<String> = self.lock().keys().cloned().collect();
+        for message_id in held {
+            self.release(&message_id).await;
+        }
+    }
+
+    async fn release(&self, message_id: &str) {
+        let Some(held) = self.lock().remove(message_id) else {
+            return;
+        };
+        let request_id = held.webhook.request_id.as_str();
+
+        // Retry sudah dilakukan processor sesuai klasifikasi response Permata
+        let (status_code, error) = match self.processor.process_webhook(&held.webhook).await {
+            Ok(response) if response.outcome == ResponseOutcome::Success => {
+                StructuredLogger::log_info(
+                    &format!("Coalesced status for message {} sent with HTTP status {}", message_id, response.http_status),
+                    Some(request_id),
+                    Some(request_id),
+                    None,
+                );
+                return;
+            }
+            Ok(response) => (Some(response.http_status), response.body),
+            Err(e) => (None, e.to_string()),
+        };
+
+        let Some(dead_letter) = &self.dead_letter else {
+            StructuredLogger::log_error(
+                &format!("Failed to send coalesced status for message {}: {}", message_id, error),
+                Some(request_id),
+                Some(request_id),
+            );
+            return;
+        };
+        let recorded = dead_letter.record(&DeadLetter {
+            request_id,
+            event_request_id: request_id,
+            event_key: Some(message_id),
+            payload: &held.webhook.body_str(),
+            status_code,
+            error: Some(&error),
+        });
+        match recorded {
+            Ok(()) => {
+                StructuredLogger::log_warning(
+                    &format!("Coalesced status for message {} not delivered, dead-lettered: {}", message_id, error),
+                    Some(request_id),
+                    Some(request_id),
+                );
+                Metrics::global().increment(metrics::DEAD_LETTERED);
+            }
+            Err(e) => StructuredLogger::log_error(
+                &format!(
+                    "Coalesced status for message {} lost, failed to dead-letter it: {} (delivery error: {})",
+                    message_id, e, error
+                ),
+                Some(request_id),
+                Some(request_id),
+            ),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Pending>> {
+        self.pending.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// (message id, status) of a receipt carrying exactly one status
+fn single_status(webhook: &InboundWebhook) -> Option<(String, String)> {
+    let whatsapp = webhook.whatsapp.as_ref()?;
+    let mut statuses = whatsapp.statuses();
+    let status = statuses.next()?;
+    if statuses.next().is_some() {
+        return None;
+    }
+    Some((status.id.clone()?, status.status.clone()?))
+}
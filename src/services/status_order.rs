@@ -0,0 +1,192 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+
+use crate::config::{StatusOrderConfig, StatusRegressionMode};
+use crate::models::InboundWebhook;
+use crate::providers::metrics::{self, Metrics};
+use crate::providers::StructuredLogger;
+
+/// Position of a status in the WhatsApp lifecycle, `None` for statuses that
+/// are never treated as a regression (`failed`, `deleted`, ...)
+pub fn status_rank(status: &str) -> Option<u8> {
+    match status {
+        "sent" => Some(1),
+        "delivered" => Some(2),
+        "read" => Some(3),
+        _ => None,
+    }
+}
+
+/// Result of checking one status against the highest one seen
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StatusCheck {
+    Forward,
+    /// A later status was already seen for the message
+    Regression { highest: &'static str },
+}
+
+/// What to do with a delivery receipt after the guard ran
+#[derive(Debug, Clone)]
+pub enum GuardedWebhook {
+    /// Forward, possibly with regressed statuses tagged or removed
+    Forward(InboundWebhook),
+    /// Every status was a suppressed regression
+    Suppressed(InboundWebhook),
+}
+
+#[derive(Debug, Default)]
+struct OrderState {
+    highest: HashMap<String, (u8, Instant)>,
+    // (message id, first seen) in insertion order, for TTL and capacity eviction
+    order: VecDeque<(String, Instant)>,
+}
+
+/// Tracks the highest status per WhatsApp message id so `delivered` arriving
+/// after `read` is not shown to Permata as the latest state
+#[derive(Debug)]
+pub struct StatusOrderGuard {
+    config: StatusOrderConfig,
+    state: Mutex<OrderState>,
+}
+
+impl StatusOrderGuard {
+    pub fn new(config: StatusOrderConfig) -> Self {
+        Self {
+            config,
+            state: Mutex::new(OrderState::default()),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    /// Record a status and report whether it goes backwards
+    pub fn check(&self, message_id: &str, status: &str) -> StatusCheck {
+        let Some(rank) = status_rank(status) else {
+            return StatusCheck::Forward;
+        };
+
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        self.evict(&mut state, now);
+
+        match state.highest.get_mut(message_id) {
+            Some((highest, _)) if *highest > rank => {
+                return StatusCheck::Regression { highest: rank_name(*highest) };
+            }
+            Some((highest, _)) => *highest = rank,
+            None => {
+                state.highest.insert(message_id.to_string(), (rank, now));
+                state.order.push_back((message_id.to_string(), now));
+                while state.highest.len() > self.config.capacity.max(1) {
+                    match state.order.pop_front() {
+                        Some((oldest, _)) => {
+                            state.highest.remove(&oldest);
+                        }
+                        None => break,
+                    }
+                }
+            }
+        }
+        StatusCheck::Forward
+    }
+
+    /// Run every status of a delivery receipt through the guard, tagging or
+    /// removing regressions according to `status_order.mode`
+    pub fn apply(&self, webhook: InboundWebhook) -> GuardedWebhook {
+        if !self.enabled() {
+            return GuardedWebhook::Forward(webhook);
+        }
+        let Some(mut payload) = webhook.json.clone() else {
+            return GuardedWebhook::Forward(webhook);
+        };
+
+        let mut regressions = 0;
+        let mut remaining = 0;
+        for statuses in status_lists(&mut payload) {
+            statuses.retain_mut(|status| {
+                let (Some(id), Some(name)) = (
+                    status.get("id").and_then(Value::as_str),
+                    status.get("status").and_then(Value::as_str),
+                ) else {
+                    remaining += 1;
+                    return true;
+                };
+
+                let StatusCheck::Regression { highest } = self.check(id, name) else {
+                    remaining += 1;
+                    return true;
+                };
+                regressions += 1;
+                StructuredLogger::log_warning(
+                    &format!("Status '{}' for message {} arrived after '{}'", name, id, highest),
+                    Some(&webhook.request_id),
+                    Some(&webhook.request_id),
+                );
+
+                match self.config.mode {
+                    StatusRegressionMode::Suppress => false,
+                    StatusRegressionMode::Tag => {
+                        status[self.config.tag_field.as_str()] = Value::Bool(true);
+                        remaining += 1;
+                        true
+                    }
+                }
+            });
+        }
+
+        if regressions == 0 {
+            return GuardedWebhook::Forward(webhook);
+        }
+        Metrics::global().add(metrics::STATUS_REGRESSION, regressions);
+
+        if remaining == 0 {
+            GuardedWebhook::Suppressed(webhook)
+        } else {
+            GuardedWebhook::Forward(webhook.with_json(payload))
+        }
+    }
+
+    fn evict(&self, state: &mut OrderState, now: Instant) {
+        let ttl = Duration::from_secs(self.config.ttl_secs);
+        while let Some((id, at)) = state.order.front() {
+            if now.duration_since(*at) < ttl {
+                break;
+            }
+            let id = id.clone();
+            state.order.pop_front();
+            state.highest.remove(&id);
+        }
+    }
+}
+
+fn rank_name(rank: u8) -> &'static str {
+    match rank {
+        1 => "sent",
+        2 => "delivered",
+        _ => "read",
+    }
+}
+
+/// `entry[*].changes[*].value.statuses` arrays
+fn status_lists(payload: &mut Value) -> Vec<&mut Vec<Value>> {
+    let mut lists = Vec::new();
+    let Some(Value::Array(entries)) = payload.get_mut("entry") else {
+        return lists;
+    };
+    for entry in entries {
+        let Some(Value::Array(changes)) = entry.get_mut("changes") else {
+            continue;
+        };
+        for change in changes {
+            if let Some(Value::Array(statuses)) = change.get_mut("value").and_then(|value| value.get_mut("statuses")) {
+                lists.push(statuses);
+            }
+        }
+    }
+    lists
+}
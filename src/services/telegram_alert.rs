@@ -1,84 +1,134 @@
 use reqwest::Client;
-use serde_json::json;
+use serde_json::{json, Value};
+use std::sync::{Arc, OnceLock};
 use std::time::Duration;
 
-use crate::config::AppConfig;
+use crate::config::{AlertMessageFormat, AppConfig};
+use crate::providers::http_client::{Destination, HttpClientFactory};
+use crate::services::alert_dispatcher::{AlertDelivery, AlertDispatcher};
+use crate::services::alert_template::{
+    default_template_for, escape_slack, escaper_for, render_template, AlertContext,
+    DEFAULT_SLACK_TEMPLATE,
+};
 use crate::utils::error::Result;
-use crate::providers::StructuredLogger;
 
 #[derive(Clone)]
 pub struct TelegramAlertService {
     client: Client,
+    slack_client: Client,
     config: AppConfig,
+    dispatcher: Arc<OnceLock<AlertDispatcher>>,
 }
 
 impl TelegramAlertService {
     pub fn new(config: AppConfig) -> Result<Self> {
-        let timeout = Duration::from_secs(config.webclient.timeout);
-        let client = Client::builder()
-            .timeout(timeout)
-            .build()?;
+        let clients = HttpClientFactory::new(&config);
+        Self::with_clients(config, &clients)
+    }
 
-        Ok(Self { client, config })
+    /// Telegram and Slack each get the client of their own destination
+    pub fn with_clients(config: AppConfig, clients: &HttpClientFactory) -> Result<Self> {
+        Ok(Self {
+            client: clients.client(Destination::Telegram)?,
+            slack_client: clients.client(Destination::Slack)?,
+            config,
+            dispatcher: Arc::new(OnceLock::new()),
+        })
     }
 
     pub fn send_error_alert(&self, error_message: &str, request_id: Option<&str>) {
-        let telegram_config = self.config.telegram_alert.clone();
-        let client = self.client.clone();
-        let error_message = error_message.to_string();
-        let request_id = request_id.map(|s| s.to_string());
-        
-        // Spawn async task untuk non-blocking execution
-        tokio::spawn(async move {
-            let formatted_message = match request_id {
-                Some(req_id) => format!(
-                    "{} [request-id: {}] {}",
-                    telegram_config.alert_message_prefix,
-                    req_id,
-                    error_message
-                ),
-                None => format!(
-                    "{} {}",
-                    telegram_config.alert_message_prefix,
-                    error_message
-                ),
-            };
+        self.send_alert(&AlertContext::new(error_message).with_request_id(request_id));
+    }
+
+    /// Send a recovery notification for a previously alerted condition
+    pub fn send_resolved_alert(&self, message: &str) {
+        self.send_alert(&AlertContext::new(message).with_title("[RESOLVED]"));
+    }
+
+    /// Render the configured templates for the context and send to every channel
+    pub fn send_alert(&self, context: &AlertContext) {
+        let telegram_config = &self.config.telegram_alert;
+        let template_config = &self.config.alert_template;
 
+        let text = self.render_telegram(context);
+        let mut payload = json!({
+            "chat_id": telegram_config.chat_id,
+            "message_thread_id": telegram_config.message_thread_id,
+            "text": text
+        });
+        match template_config.telegram_format {
+            AlertMessageFormat::Plain => {}
+            AlertMessageFormat::MarkdownV2 => payload["parse_mode"] = json!("MarkdownV2"),
+            AlertMessageFormat::Html => payload["parse_mode"] = json!("HTML"),
+        }
+        self.dispatch("Telegram", &self.client, telegram_config.api_url.clone(), payload, text);
+
+        if let Some(slack_config) = &template_config.slack {
+            let text = self.render_slack(context);
             let payload = json!({
-                "chat_id": telegram_config.chat_id,
-                "message_thread_id": telegram_config.message_thread_id,
-                "text": formatted_message
+                "text": context.message,
+                "blocks": [
+                    {
+                        "type": "section",
+                        "text": { "type": "mrkdwn", "text": text }
+                    }
+                ]
             });
+            self.dispatch("Slack", &self.slack_client, slack_config.webhook_url.clone(), payload, text);
+        }
+    }
 
-            match client
-                .post(&telegram_config.api_url)
-                .header("Content-Type", "application/json")
-                .json(&payload)
-                .send()
-                .await
-            {
-                Ok(response) => {
-                    if response.status().is_success() {
-                        StructuredLogger::log_info(
-                            &format!("Telegram alert sent successfully: {}", formatted_message), 
-                            None, None, None
-                        );
-                    } else {
-                        let status = response.status();
-                        let error_text = response.text().await.unwrap_or_default();
-                        StructuredLogger::log_error(&format!(
-                            "Failed to send Telegram alert. Status: {}, Error: {}",
-                            status, error_text
-                        ), None, None);
-                    }
-                }
-                Err(e) => {
-                    StructuredLogger::log_error(&format!(
-                        "Failed to send Telegram alert: {}",
-                        e
-                    ), None, None);
-                }
-            }
+    pub fn render_telegram(&self, context: &AlertContext) -> String {
+        let template_config = &self.config.alert_template;
+        let format = template_config.telegram_format;
+        let template = template_config
+            .telegram_template
+            .as_deref()
+            .unwrap_or_else(|| default_template_for(format));
+
+        render_template(
+            template,
+            &context.variables(&self.config.telegram_alert.alert_message_prefix),
+            escaper_for(format),
+        )
+    }
+
+    pub fn render_slack(&self, context: &AlertContext) -> String {
+        let template = self
+            .config
+            .alert_template
+            .slack
+            .as_ref()
+            .and_then(|slack| slack.template.as_deref())
+            .unwrap_or(DEFAULT_SLACK_TEMPLATE);
+
+        render_template(
+            template,
+            &context.variables(&self.config.telegram_alert.alert_message_prefix),
+            escape_slack,
+        )
+    }
+
+    /// Wait for queued alerts to be delivered, see `AlertDispatcher::flush`
+    pub async fn flush(&self, deadline: Duration) -> bool {
+        match self.dispatcher.get() {
+            Some(dispatcher) => dispatcher.flush(deadline).await,
+            None => true,
+        }
+    }
+
+    fn dispatch(&self, channel: &'static str, client: &Client, url: String, payload: Value, formatted_message: String) {
+        // Dispatcher dibuat saat alert pertama, karena butuh tokio runtime
+        let dispatcher = self.dispatcher.get_or_init(|| {
+            AlertDispatcher::start(self.config.alerting.delivery.clone())
+        });
+
+        dispatcher.enqueue(AlertDelivery {
+            channel,
+            client: client.clone(),
+            url,
+            payload,
+            summary: formatted_message,
         });
     }
 }
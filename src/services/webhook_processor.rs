@@ -1,35 +1,153 @@
+use std::sync::Arc;
+use std::time::Duration;
+
 use async_trait::async_trait;
 
-use crate::config::AppConfig;
-use crate::models::WebhookMessage;
-use crate::services::{PermataCallbackStatusClient, TelegramAlertService};
-use crate::utils::error::Result;
+use crate::config::{AppConfig, ResponseOutcome};
+use crate::models::{InboundWebhook, WhatsAppWebhook};
+use crate::services::event_dedup::EventDeduplicator;
+use crate::services::PermataCallbackStatusClient;
+use crate::utils::error::{AppError, Result};
+use crate::providers::dead_letter::{DeadLetter, DeadLetterLog};
+use crate::providers::http_client::HttpClientFactory;
 use crate::providers::logging::StructuredLogger;
+use crate::providers::metrics::{self, Metrics};
 
 #[derive(Debug, Clone)]
 pub struct WebhookResponse {
     pub http_status: u16,
     pub body: String,
+    /// How Permata's response was classified, fan-out batches count as success
+    pub outcome: ResponseOutcome,
 }
 
 #[async_trait]
 pub trait WebhookProcessorTrait {
-    async fn process_webhook(&self, webhook: WebhookMessage, request_id: &str) -> Result<WebhookResponse>;
+    async fn process_webhook(&self, webhook: &InboundWebhook) -> Result<WebhookResponse>;
+}
+
+/// How the events of one fan-out webhook ended
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FanOutSummary {
+    pub events: usize,
+    pub delivered: usize,
+    pub duplicates: usize,
+    pub dead_lettered: usize,
+}
+
+/// Dedup and dead-letter state of `fan_out` mode
+struct FanOut {
+    dedup: EventDeduplicator,
+    dead_letter: Option<DeadLetterLog>,
 }
 
 #[derive(Clone)]
 pub struct WebhookProcessor {
     permata_client: PermataCallbackStatusClient,
-    config: AppConfig,
+    fan_out: Option<Arc<FanOut>>,
 }
 
 impl WebhookProcessor {
     pub fn new(config: AppConfig) -> Result<Self> {
-        let permata_client = PermataCallbackStatusClient::new(config.clone())?;
-        Ok(Self {
-            permata_client,
-            config,
-        })
+        Self::with_http_clients(config.clone(), &HttpClientFactory::new(&config))
+    }
+
+    pub fn with_http_clients(config: AppConfig, clients: &HttpClientFactory) -> Result<Self> {
+        let fan_out = if config.fan_out.enabled {
+            let dead_letter = if config.fan_out.dead_letter_file.is_empty() {
+                None
+            } else {
+                Some(DeadLetterLog::new(&config)?)
+            };
+            Some(Arc::new(FanOut {
+                dedup: EventDeduplicator::new(
+                    Duration::from_secs(config.fan_out.dedup_ttl_secs),
+                    config.fan_out.dedup_capacity,
+                ),
+                dead_letter,
+            }))
+        } else {
+            None
+        };
+
+        let permata_client = PermataCallbackStatusClient::with_http_clients(config, clients)?;
+        Ok(Self { permata_client, fan_out })
+    }
+
+    /// Send every status or message of a batched webhook as its own callback.
+    /// Each event is claimed before it is sent, so concurrent redeliveries send
+    /// it once; a failed event releases its claim and is dead-lettered. The
+    /// batch fails only when an event could be neither delivered nor dead-lettered.
+    async fn process_fan_out(
+        &self,
+        fan_out: &FanOut,
+        webhook: &InboundWebhook,
+        whatsapp: &WhatsAppWebhook,
+    ) -> Result<FanOutSummary> {
+        let events = whatsapp.split_events();
+        let mut summary = FanOutSummary::default();
+
+        for (index, event) in events.iter().enumerate() {
+            // Payload satu item dikirim apa adanya, tanpa serialisasi ulang
+            let event_webhook = if events.len() > 1 {
+                let mut event_webhook = webhook.with_json(serde_json::to_value(event)?);
+                event_webhook.request_id = format!("{}-{}", webhook.request_id, index + 1);
+                event_webhook
+            } else {
+                webhook.clone()
+            };
+            let event_key = event.event_key();
+            summary.events += 1;
+            Metrics::global().increment(metrics::FAN_OUT_EVENTS);
+
+            if event_key.as_deref().is_some_and(|key| !fan_out.dedup.claim(key)) {
+                StructuredLogger::log_info(
+                    &format!("Skipping duplicate event {}", event_key.as_deref().unwrap_or_default()),
+                    Some(&event_webhook.request_id),
+                    Some(&webhook.request_id),
+                    None,
+                );
+                Metrics::global().increment(metrics::FAN_OUT_DUPLICATE);
+                summary.duplicates += 1;
+                continue;
+            }
+
+            let (status_code, error) = match self.permata_client.send_inbound(&event_webhook).await {
+                Ok(response) if response.is_success() => {
+                    summary.delivered += 1;
+                    continue;
+                }
+                Ok(response) => (Some(response.status_code), response.body),
+                Err(e) => (None, e.to_string()),
+            };
+            if let Some(key) = &event_key {
+                fan_out.dedup.release(key);
+            }
+
+            let Some(dead_letter) = &fan_out.dead_letter else {
+                return Err(AppError::error(format!(
+                    "Event {} was not delivered: {}",
+                    event_webhook.request_id, error
+                )));
+            };
+            dead_letter.record(&DeadLetter {
+                request_id: &webhook.request_id,
+                event_request_id: &event_webhook.request_id,
+                event_key: event_key.as_deref(),
+                payload: &event_webhook.body_str(),
+                status_code,
+                error: Some(&error),
+            })?;
+            StructuredLogger::log_warning(
+                &format!("Event {} dead-lettered: {}", event_webhook.request_id, error),
+                Some(&event_webhook.request_id),
+                Some(&webhook.request_id),
+            );
+            Metrics::global().increment(metrics::DEAD_LETTERED);
+            summary.dead_lettered += 1;
+        }
+
+        Ok(summary)
     }
 
     pub async fn shutdown(&self) {
@@ -45,24 +163,45 @@ impl WebhookProcessor {
 
 #[async_trait]
 impl WebhookProcessorTrait for WebhookProcessor {
-    async fn process_webhook(&self, webhook: WebhookMessage, request_id: &str) -> Result<WebhookResponse> {
+    async fn process_webhook(&self, webhook: &InboundWebhook) -> Result<WebhookResponse> {
+        let request_id = webhook.request_id.as_str();
         StructuredLogger::log_info(
             "Processing webhook for Permata Bank",
             Some(request_id),
             Some(request_id),
             Some(serde_json::json!({
-                "body_size": webhook.body.len(),
-                "headers_count": webhook.headers.len()
+                "body_size": webhook.raw.len(),
+                "headers_count": webhook.headers.len(),
+                "classification": webhook.classification.as_str()
             })),
         );
 
+        if let (Some(fan_out), Some(whatsapp)) = (&self.fan_out, &webhook.whatsapp) {
+            let summary = self.process_fan_out(fan_out, webhook, whatsapp).await?;
+            // Batch diterima setelah semua event terkirim, duplikat atau masuk dead letter
+            return Ok(WebhookResponse {
+                http_status: 200,
+                body: serde_json::json!({
+                    "StatusCode": "00",
+                    "StatusDesc": "Success",
+                    "Events": summary.events,
+                    "Delivered": summary.delivered,
+                    "Duplicates": summary.duplicates,
+                    "DeadLettered": summary.dead_lettered
+                })
+                .to_string(),
+                outcome: ResponseOutcome::Success,
+            });
+        }
+
         // Send webhook to Permata Bank callback status URL
-        match self.permata_client.send_webhook_with_context(&webhook.body, request_id, Some(request_id), Some(request_id)).await {
+        match self.permata_client.send_inbound(webhook).await {
             Ok(http_response) => {
                 // Return langsung HTTP response dari Permata Bank
                 Ok(WebhookResponse {
                     http_status: http_response.status_code,
                     body: http_response.body,
+                    outcome: http_response.outcome,
                 })
             }
             Err(e) => {
@@ -73,15 +212,8 @@ impl WebhookProcessorTrait for WebhookProcessor {
                     Some(request_id),
                     Some(request_id),
                 );
-                
-                // Send telegram alert for webhook failures
-                if let Ok(telegram_service) = TelegramAlertService::new(self.config.clone()) {
-                    telegram_service.send_error_alert(
-                        &e.to_string(),
-                        Some(request_id)
-                    );
-                }
-                
+
+                // Alerting sudah di-handle oleh incident tracker di Permata client / login handler
                 Err(e)
             }
         }
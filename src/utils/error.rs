@@ -40,6 +40,9 @@ pub enum AppError {
 
     #[error("{message}")]
     ReqError { message: String },
+
+    #[error("Service overloaded: {message}")]
+    Overloaded { message: String },
 }
 
 impl AppError {
@@ -78,6 +81,12 @@ impl AppError {
             message: message.into(),
         }
     }
+
+    pub fn overloaded(message: impl Into<String>) -> Self {
+        Self::Overloaded {
+            message: message.into(),
+        }
+    }
 }
 
 pub type Result<T> = std::result::Result<T, AppError>;
\ No newline at end of file
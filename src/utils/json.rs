@@ -3,8 +3,12 @@ use crate::utils::error::Result;
 /// Compacts a JSON string by removing unnecessary whitespace and newlines
 /// while preserving the original values
 pub fn compact_json(json_str: &str) -> Result<String> {
-    let compacted = json_str.chars().filter(|c| !c.is_whitespace()).collect::<String>();
-    Ok(compacted)
+    Ok(compact_json_str(json_str))
+}
+
+/// Infallible form of `compact_json`
+pub fn compact_json_str(json_str: &str) -> String {
+    json_str.chars().filter(|c| !c.is_whitespace()).collect()
 }
 
 /// Checks if a JSON path exists in the given JSON value
@@ -78,4 +82,16 @@ pub fn is_inbound_flow_payload(json: &serde_json::Value) -> bool {
         &["data", "entry", "[*]", "changes", "[*]", "value", "messages", "[*]", "interactive", "type"],
         "nfm_reply"
     )
-}
\ No newline at end of file
+}
+
+/// Returns the WhatsApp phone number id (metadata.phone_number_id) of a DR or
+/// Inbound Flow payload, used as the tenant of a request
+pub fn extract_phone_number_id(json: &serde_json::Value) -> Option<String> {
+    let value_path = ["entry", "[*]", "changes", "[*]", "value", "metadata", "phone_number_id"];
+    let inbound_path = ["data", "entry", "[*]", "changes", "[*]", "value", "metadata", "phone_number_id"];
+
+    navigate_json_path(json, &value_path)
+        .into_iter()
+        .chain(navigate_json_path(json, &inbound_path))
+        .find_map(|value| value.as_str().map(|s| s.to_string()))
+}
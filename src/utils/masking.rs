@@ -0,0 +1,167 @@
+use std::sync::Arc;
+
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::config::{AuditConfig, MaskRuleConfig, MaskStrategy};
+
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Built-in masking rules, see `effective_mask_rules`
+pub fn default_mask_rules() -> Vec<MaskRuleConfig> {
+    let rule = |path: &str, strategy: MaskStrategy| MaskRuleConfig {
+        path: path.to_string(),
+        strategy,
+        keep_last: 4,
+    };
+
+    vec![
+        // Nomor telepon customer
+        rule("**.wa_id", MaskStrategy::Hash),
+        rule("**.from", MaskStrategy::Hash),
+        rule("**.recipient_id", MaskStrategy::Partial),
+        // Nama
+        rule("**.profile.name", MaskStrategy::Redact),
+        // Free text
+        rule("**.text.body", MaskStrategy::Redact),
+        rule("**.button.text", MaskStrategy::Redact),
+        rule("**.nfm_reply.response_json", MaskStrategy::Redact),
+        rule("**.nfm_reply.body", MaskStrategy::Redact),
+    ]
+}
+
+/// Built-in rules plus `audit.masking`; a configured rule replaces the
+/// built-in one with the same path
+pub fn effective_mask_rules(config: &AuditConfig) -> Vec<MaskRuleConfig> {
+    let mut rules = if config.include_default_masking {
+        default_mask_rules()
+    } else {
+        Vec::new()
+    };
+    for rule in &config.masking {
+        match rules.iter_mut().find(|existing| existing.path == rule.path) {
+            Some(existing) => *existing = rule.clone(),
+            None => rules.push(rule.clone()),
+        }
+    }
+    rules
+}
+
+struct MaskRule {
+    segments: Vec<String>,
+    strategy: MaskStrategy,
+    keep_last: usize,
+}
+
+/// Applies JSON path masking rules to payloads before they are audited
+#[derive(Clone)]
+pub struct PayloadMasker {
+    rules: Arc<Vec<MaskRule>>,
+    hash_salt: String,
+}
+
+impl PayloadMasker {
+    pub fn new(rules: &[MaskRuleConfig], hash_salt: Option<String>) -> Self {
+        let rules = rules
+            .iter()
+            .map(|rule| MaskRule {
+                segments: rule.path.split('.').map(|s| s.to_string()).collect(),
+                strategy: rule.strategy,
+                keep_last: rule.keep_last,
+            })
+            .collect();
+
+        Self {
+            rules: Arc::new(rules),
+            hash_salt: hash_salt.unwrap_or_default(),
+        }
+    }
+
+    pub fn mask(&self, value: &mut Value) {
+        for rule in self.rules.iter() {
+            let segments: Vec<&str> = rule.segments.iter().map(String::as_str).collect();
+            self.apply(value, &segments, rule);
+        }
+    }
+
+    /// Parse and mask a raw body, non-JSON bodies are kept as a plain string
+    pub fn mask_body(&self, body: &str) -> Value {
+        match serde_json::from_str::<Value>(body) {
+            Ok(mut value) => {
+                self.mask(&mut value);
+                value
+            }
+            Err(_) => Value::String(body.to_string()),
+        }
+    }
+
+    fn apply(&self, value: &mut Value, segments: &[&str], rule: &MaskRule) {
+        let Some((segment, rest)) = segments.split_first() else {
+            *value = self.masked(value, rule);
+            return;
+        };
+
+        match *segment {
+            "**" => {
+                // `**` cocok dengan nol atau lebih level
+                self.apply(value, rest, rule);
+                match value {
+                    Value::Object(map) => {
+                        for child in map.values_mut() {
+                            self.apply(child, segments, rule);
+                        }
+                    }
+                    Value::Array(items) => {
+                        for child in items.iter_mut() {
+                            self.apply(child, segments, rule);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            "[*]" => {
+                if let Value::Array(items) = value {
+                    for item in items.iter_mut() {
+                        self.apply(item, rest, rule);
+                    }
+                }
+            }
+            key => {
+                if let Some(child) = value.as_object_mut().and_then(|map| map.get_mut(key)) {
+                    self.apply(child, rest, rule);
+                }
+            }
+        }
+    }
+
+    fn masked(&self, value: &Value, rule: &MaskRule) -> Value {
+        let text = match value {
+            Value::Null => return Value::Null,
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+
+        let masked = match rule.strategy {
+            MaskStrategy::Hash => {
+                let digest = Sha256::digest(format!("{}{}", self.hash_salt, text).as_bytes());
+                let hex: String = digest.iter().take(8).map(|b| format!("{:02x}", b)).collect();
+                format!("sha256:{}", hex)
+            }
+            MaskStrategy::Partial => partial_mask(&text, rule.keep_last),
+            MaskStrategy::Redact => REDACTED.to_string(),
+        };
+        Value::String(masked)
+    }
+}
+
+/// Replace everything but the last `keep_last` characters with `*`
+pub fn partial_mask(text: &str, keep_last: usize) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let hidden = chars.len().saturating_sub(keep_last);
+
+    chars
+        .iter()
+        .enumerate()
+        .map(|(i, c)| if i < hidden { '*' } else { *c })
+        .collect()
+}
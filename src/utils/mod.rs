@@ -1,9 +1,17 @@
 pub mod error;
 pub mod signature;
 pub mod json;
+pub mod masking;
+pub mod pem;
+pub mod rate_limit;
 pub mod request_id;
+pub mod whatsapp_errors;
 
 pub use error::*;
 pub use signature::*;
 pub use json::*;
-pub use request_id::*;
\ No newline at end of file
+pub use masking::*;
+pub use pem::*;
+pub use rate_limit::*;
+pub use request_id::*;
+pub use whatsapp_errors::*;
\ No newline at end of file
@@ -0,0 +1,30 @@
+use std::fs::File;
+use std::io::BufReader;
+
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+
+use crate::utils::error::{AppError, Result};
+
+fn open(path: &str) -> Result<BufReader<File>> {
+    File::open(path)
+        .map(BufReader::new)
+        .map_err(|e| AppError::configuration(format!("Failed to open TLS file {}: {}", path, e)))
+}
+
+/// All certificates of a PEM file, at least one
+pub fn load_certs(path: &str) -> Result<Vec<CertificateDer<'static>>> {
+    let certs = rustls_pemfile::certs(&mut open(path)?)
+        .collect::<std::io::Result<Vec<_>>>()
+        .map_err(|e| AppError::configuration(format!("Invalid PEM in {}: {}", path, e)))?;
+    if certs.is_empty() {
+        return Err(AppError::configuration(format!("No certificate found in {}", path)));
+    }
+    Ok(certs)
+}
+
+/// First private key (PKCS#8, PKCS#1 or SEC1) of a PEM file
+pub fn load_key(path: &str) -> Result<PrivateKeyDer<'static>> {
+    rustls_pemfile::private_key(&mut open(path)?)
+        .map_err(|e| AppError::configuration(format!("Invalid PEM in {}: {}", path, e)))?
+        .ok_or_else(|| AppError::configuration(format!("No private key found in {}", path)))
+}
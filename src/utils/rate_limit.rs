@@ -0,0 +1,111 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::config::RateLimitConfig;
+
+/// Buckets kept before the least recently used ones are dropped
+const MAX_TRACKED_KEYS: usize = 10_000;
+
+/// Longest wait reported back to the caller
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(3600);
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+    /// Sequence of the last use, matches one entry in `Buckets::order`
+    used: u64,
+}
+
+#[derive(Debug, Default)]
+struct Buckets {
+    by_key: HashMap<String, Bucket>,
+    // (key, used) per use, entries of an older use of the key are stale
+    order: VecDeque<(String, u64)>,
+    next_use: u64,
+}
+
+impl Buckets {
+    /// Drop the least recently used bucket, skipping stale order entries
+    fn evict_oldest(&mut self) {
+        while let Some((key, used)) = self.order.pop_front() {
+            if self.by_key.get(&key).is_some_and(|bucket| bucket.used == used) {
+                self.by_key.remove(&key);
+                return;
+            }
+        }
+    }
+
+    /// Record a use of the bucket just stored for `key`
+    fn touch(&mut self, key: String, used: u64) {
+        self.order.push_back((key, used));
+
+        // Entri basi dibuang sekaligus, paling cepat setelah MAX_TRACKED_KEYS pemakaian
+        if self.order.len() > 2 * MAX_TRACKED_KEYS {
+            let by_key = &self.by_key;
+            self.order.retain(|(key, used)| by_key.get(key).is_some_and(|bucket| bucket.used == *used));
+        }
+    }
+}
+
+/// Token bucket per key (source IP, tenant). A key starts with `burst`
+/// tokens, each request takes one and tokens refill at `per_second`.
+#[derive(Debug)]
+pub struct KeyedRateLimiter {
+    per_second: f64,
+    burst: f64,
+    buckets: Mutex<Buckets>,
+}
+
+impl KeyedRateLimiter {
+    pub fn new(config: &RateLimitConfig) -> Self {
+        Self {
+            per_second: config.per_second.max(f64::MIN_POSITIVE),
+            burst: f64::from(config.burst.max(1)),
+            buckets: Mutex::new(Buckets::default()),
+        }
+    }
+
+    /// Take a token for the key, or how long until one is available
+    pub fn check(&self, key: &str) -> Result<(), Duration> {
+        self.check_at(key, Instant::now())
+    }
+
+    pub fn check_at(&self, key: &str, now: Instant) -> Result<(), Duration> {
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+
+        let mut bucket = match buckets.by_key.get(key) {
+            Some(bucket) => self.refill(*bucket, now),
+            None => {
+                // Key yang paling lama tidak dipakai dibuang, tidak perlu scan semua bucket
+                while buckets.by_key.len() >= MAX_TRACKED_KEYS {
+                    buckets.evict_oldest();
+                }
+                Bucket { tokens: self.burst, updated: now, used: 0 }
+            }
+        };
+        bucket.used = buckets.next_use;
+        buckets.next_use += 1;
+
+        let result = if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            let wait = Duration::try_from_secs_f64((1.0 - bucket.tokens) / self.per_second).unwrap_or(MAX_RETRY_AFTER);
+            Err(wait.min(MAX_RETRY_AFTER))
+        };
+        buckets.by_key.insert(key.to_string(), bucket);
+        buckets.touch(key.to_string(), bucket.used);
+        result
+    }
+
+    fn refill(&self, bucket: Bucket, now: Instant) -> Bucket {
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        Bucket {
+            tokens: (bucket.tokens + elapsed * self.per_second).min(self.burst),
+            updated: now,
+            used: bucket.used,
+        }
+    }
+}
@@ -1,27 +1,37 @@
+use serde_json::Value;
 use uuid::Uuid;
 
 pub fn extract_request_id(payload: &str) -> String {
-    match serde_json::from_str::<serde_json::Value>(payload) {
-        Ok(json) => {
-            // Try to get xid first, then id
-            if let Some(xid) = json.get("xid").and_then(|v| v.as_str()) {
-                if !xid.is_empty() {
-                    return format!("req-{}", xid);
-                }
-            }
-            
-            if let Some(id) = json.get("id").and_then(|v| v.as_str()) {
-                if !id.is_empty() {
-                    return format!("req-{}", id);
-                }
+    request_id_from_json(serde_json::from_str::<Value>(payload).ok().as_ref())
+}
+
+/// Request id from an already parsed payload: `xid`, then `id`, else a new UUID
+pub fn request_id_from_json(json: Option<&Value>) -> String {
+    if let Some(json) = json {
+        // Try to get xid first, then id
+        if let Some(xid) = json.get("xid").and_then(|v| v.as_str()) {
+            if !xid.is_empty() {
+                return format!("req-{}", xid);
             }
-            
-            // Generate UUID if no xid or id found
-            format!("req-{}", Uuid::new_v4())
         }
-        Err(_) => {
-            // Generate UUID if payload is not valid JSON
-            format!("req-{}", Uuid::new_v4())
+
+        if let Some(id) = json.get("id").and_then(|v| v.as_str()) {
+            if !id.is_empty() {
+                return format!("req-{}", id);
+            }
         }
     }
-}
\ No newline at end of file
+
+    // Generate UUID if no xid or id found, or payload is not valid JSON
+    format!("req-{}", Uuid::new_v4())
+}
+
+/// Request id generated per HTTP request, stored in the request extensions
+#[derive(Debug, Clone)]
+pub struct RequestId(pub String);
+
+impl RequestId {
+    pub fn generate() -> Self {
+        Self(format!("req-{}", Uuid::new_v4()))
+    }
+}
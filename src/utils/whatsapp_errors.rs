@@ -0,0 +1,62 @@
+/// Readable descriptions for common WhatsApp Cloud API error codes
+/// https://developers.facebook.com/docs/whatsapp/cloud-api/support/error-codes
+const WHATSAPP_ERROR_CATALOG: &[(i64, &str)] = &[
+    (0, "AuthException: unable to authenticate the app user"),
+    (3, "API method not available for this app or permission"),
+    (4, "Too many API calls: app-level rate limit reached"),
+    (10, "Permission denied or removed for this app"),
+    (100, "Invalid parameter in the request"),
+    (190, "Access token expired or invalid"),
+    (200, "API permission denied or removed"),
+    (368, "Temporarily blocked for policy violations"),
+    (470, "Message failed: outside the 24-hour customer service window"),
+    (80007, "WhatsApp Business Account rate limit reached"),
+    (130429, "Cloud API throughput rate limit reached"),
+    (130472, "Recipient is part of a Meta experiment, message not sent"),
+    (130497, "Business account restricted from messaging users in this country"),
+    (131000, "Something went wrong: unknown error on the WhatsApp side"),
+    (131005, "Access denied: permission not granted or removed"),
+    (131008, "Required parameter is missing"),
+    (131009, "Parameter value is not valid"),
+    (131016, "Service temporarily unavailable"),
+    (131021, "Recipient cannot be the sender"),
+    (131026, "Message undeliverable: recipient not on WhatsApp, old app version or terms not accepted"),
+    (131031, "Business account has been locked"),
+    (131042, "Business eligibility payment issue"),
+    (131045, "Phone number not registered: incorrect certificate"),
+    (131047, "Re-engagement message: more than 24 hours since the customer last replied"),
+    (131048, "Spam rate limit hit: too many messages marked as spam"),
+    (131049, "Meta chose not to deliver to maintain ecosystem engagement"),
+    (131050, "Recipient stopped receiving marketing messages"),
+    (131051, "Unsupported message type"),
+    (131052, "Media download error: could not download media sent by the user"),
+    (131053, "Media upload error: could not upload the media"),
+    (131056, "Pair rate limit hit: too many messages to the same recipient"),
+    (131057, "Business account is in maintenance mode"),
+    (132000, "Template parameter count mismatch"),
+    (132001, "Template does not exist in this language or is not approved"),
+    (132005, "Template hydrated text too long"),
+    (132007, "Template format character policy violated"),
+    (132012, "Template parameter format mismatch"),
+    (132015, "Template is paused due to low quality"),
+    (132016, "Template is disabled due to low quality"),
+    (132068, "Flow is in blocked state"),
+    (132069, "Flow is in throttled state"),
+    (133000, "Incomplete deregistration of the phone number"),
+    (133004, "Server temporarily unavailable"),
+    (133005, "Two-step verification PIN mismatch"),
+    (133006, "Phone number needs to be verified before registering"),
+    (133008, "Too many two-step verification PIN guesses"),
+    (133009, "Two-step verification PIN entered too quickly"),
+    (133010, "Phone number not registered on the WhatsApp Business Platform"),
+    (133015, "Phone number was recently deleted, wait before registering"),
+    (135000, "Generic user error"),
+];
+
+/// Returns the catalog description for a WhatsApp Cloud API error code
+pub fn describe_whatsapp_error(code: i64) -> Option<&'static str> {
+    WHATSAPP_ERROR_CATALOG
+        .iter()
+        .find(|(known_code, _)| *known_code == code)
+        .map(|(_, description)| *description)
+}
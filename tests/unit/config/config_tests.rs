@@ -1,4 +1,5 @@
-use webhook_gateway::config::{AppConfig, ServerConfig, LoggerConfig, WebClientConfig, PermataBankLoginConfig, PermataBankWebhookConfig, SchedulerConfig, TelegramAlertConfig};
+use webhook_gateway::services::alert_rules::effective_rules;
+use webhook_gateway::config::{AppConfig, ServerConfig, LoggerConfig, WebClientConfig, PermataBankLoginConfig, PermataBankWebhookConfig, SchedulerConfig, TelegramAlertConfig, AdminConfig, AlertingConfig, AlertTemplateConfig, LogWriterConfig, AuditConfig, AccessLogConfig, OtlpConfig, FlowReplyConfig, FanOutConfig, StatusOrderConfig, StatusCoalesceConfig, DispatchConfig, BackpressureConfig, IngressConfig, OutboundConfig, HttpClientConfig, PermataResponseConfig, MetaResponseConfig, MetaResponseMode, TlsConfig};
 
 #[test]
 fn test_server_config_creation() {
@@ -6,6 +7,7 @@ fn test_server_config_creation() {
         listen_host: "127.0.0.1".to_string(),
         listen_port: 8080,
         webhook_path: "/webhook".to_string(),
+        tls: TlsConfig::default(),
     };
 
     assert_eq!(config.listen_host, "127.0.0.1");
@@ -23,6 +25,8 @@ fn test_logger_config_creation() {
         max_age: 90,
         compress: true,
         local_time: true,
+        level: "info".to_string(),
+        writer: LogWriterConfig::default(),
     };
 
     assert_eq!(config.dir, "log/");
@@ -40,6 +44,7 @@ fn test_app_config_creation() {
         listen_host: "0.0.0.0".to_string(),
         listen_port: 9090,
         webhook_path: "/api/webhook".to_string(),
+        tls: TlsConfig::default(),
     };
 
     let logger_config = LoggerConfig {
@@ -50,6 +55,8 @@ fn test_app_config_creation() {
         max_age: 30,
         compress: false,
         local_time: false,
+        level: "info".to_string(),
+        writer: LogWriterConfig::default(),
     };
 
     let webclient_config = WebClientConfig {
@@ -91,6 +98,23 @@ fn test_app_config_creation() {
         permata_bank_webhook: webhook_config_pb,
         token_scheduler: scheduler_config,
         telegram_alert: telegram_config,
+        admin: AdminConfig::default(),
+        alerting: AlertingConfig::default(),
+        alert_template: AlertTemplateConfig::default(),
+        audit: AuditConfig::default(),
+        access_log: AccessLogConfig::default(),
+        otlp: OtlpConfig::default(),
+        flow_reply: FlowReplyConfig::default(),
+        fan_out: FanOutConfig::default(),
+        status_order: StatusOrderConfig::default(),
+        status_coalesce: StatusCoalesceConfig::default(),
+        dispatch: DispatchConfig::default(),
+        backpressure: BackpressureConfig::default(),
+        ingress: IngressConfig::default(),
+        outbound: OutboundConfig::default(),
+        http_client: HttpClientConfig::default(),
+        permata_response: PermataResponseConfig::default(),
+        meta_response: MetaResponseConfig::default(),
     };
 
     assert_eq!(app_config.server.listen_host, "0.0.0.0");
@@ -116,4 +140,69 @@ async fn test_config_loading() {
             println!("⚠️  Config file not found or invalid - this is okay in test environment");
         }
     }
-}
\ No newline at end of file
+}
+#[test]
+fn test_example_config_parses() {
+    let settings = config::Config::builder()
+        .add_source(config::File::new("config.yaml.example", config::FileFormat::Yaml))
+        .build()
+        .expect("config.yaml.example should be valid YAML");
+
+    let app_config: AppConfig = settings
+        .try_deserialize()
+        .expect("config.yaml.example should match AppConfig");
+
+    assert!(!app_config.admin.enabled);
+    assert!(!app_config.access_log.enabled);
+    assert_eq!(app_config.alerting.rules.len(), 3);
+    assert!(app_config.alerting.rules[2].business_hours.is_some());
+    // Rule bawaan seperti gateway_saturated tetap aktif di samping rule yang dikonfigurasi
+    let rules = effective_rules(&app_config.alerting);
+    assert!(rules.iter().any(|rule| rule.name == "gateway_saturated"));
+    assert!(rules.iter().any(|rule| rule.name == "permata_error_rate"));
+    assert_eq!(app_config.backpressure.max_tenants, 10_000);
+    assert_eq!(app_config.ingress.max_body_bytes, 1024 * 1024);
+    assert_eq!(app_config.meta_response.mode, MetaResponseMode::Passthrough);
+    let accepted = app_config.meta_response.mappings.retryable_failure.expect("retryable_failure mapping");
+    assert_eq!(accepted.status, 200);
+    assert_eq!(accepted.body.unwrap().status_code, "00");
+}
+
+#[test]
+fn test_validate_rejects_non_positive_rate() {
+    let settings = config::Config::builder()
+        .add_source(config::File::new("config.yaml.example", config::FileFormat::Yaml))
+        .build()
+        .unwrap();
+    let mut app_config: AppConfig = settings.try_deserialize().unwrap();
+    assert!(app_config.validate().is_ok());
+
+    app_config.ingress.tenant_rate_limit.enabled = true;
+    app_config.ingress.tenant_rate_limit.per_second = 0.0;
+    let error = app_config.validate().unwrap_err().to_string();
+    assert!(error.contains("ingress.tenant_rate_limit.per_second"));
+
+    // Rate limit yang tidak aktif tidak divalidasi
+    app_config.ingress.tenant_rate_limit.enabled = false;
+    assert!(app_config.validate().is_ok());
+
+    app_config.ingress.max_body_bytes = 0;
+    let error = app_config.validate().unwrap_err().to_string();
+    assert!(error.contains("ingress.max_body_bytes"));
+}
+
+#[test]
+fn test_validate_rejects_alert_window_past_metrics_retention() {
+    let settings = config::Config::builder()
+        .add_source(config::File::new("config.yaml.example", config::FileFormat::Yaml))
+        .build()
+        .unwrap();
+    let mut app_config: AppConfig = settings.try_deserialize().unwrap();
+
+    app_config.alerting.rules[0].window_secs = 7200;
+    let error = app_config.validate().unwrap_err().to_string();
+    assert!(error.contains("window_secs 7200"));
+
+    app_config.alerting.rules[0].window_secs = 3600;
+    assert!(app_config.validate().is_ok());
+}
@@ -0,0 +1,114 @@
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+
+use axum::body::Body;
+use axum::extract::{ConnectInfo, Request};
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::Response;
+use axum::routing::post;
+use axum::Router;
+use bytes::Bytes;
+use tower::Service;
+
+use webhook_gateway::config::{IngressConfig, RateLimitConfig};
+use webhook_gateway::handlers::ingress::{client_ip, IpFilterLayer, IpNetList, TenantRateLimiter};
+use webhook_gateway::models::InboundWebhook;
+
+const TENANT_PAYLOAD: &str = r#"{"entry":[{"changes":[{"value":{"metadata":{"phone_number_id":"115159954803011"},"statuses":[]}}]}]}"#;
+
+fn ip(text: &str) -> IpAddr {
+    text.parse().unwrap()
+}
+
+fn request_from(peer: &str, forwarded_for: Option<&str>) -> Request {
+    let mut request = Request::post("/webhook").body(Body::from(TENANT_PAYLOAD)).unwrap();
+    request.extensions_mut().insert(ConnectInfo(SocketAddr::new(ip(peer), 40000)));
+    if let Some(value) = forwarded_for {
+        request.headers_mut().insert("x-forwarded-for", HeaderValue::from_str(value).unwrap());
+    }
+    request
+}
+
+fn router(config: &IngressConfig) -> Router {
+    Router::new()
+        .route("/webhook", post(|| async { "ok" }))
+        .route_layer(IpFilterLayer::new(config).unwrap())
+}
+
+fn tenant_webhook() -> InboundWebhook {
+    InboundWebhook::new(HashMap::new(), Bytes::from_static(TENANT_PAYLOAD.as_bytes()))
+}
+
+async fn send(app: &Router, request: Request) -> Response {
+    app.clone().call(request).await.unwrap()
+}
+
+#[test]
+fn test_client_ip_behind_trusted_proxy() {
+    let trusted = IpNetList::parse(&["10.0.0.0/8".to_string(), "192.168.1.5".to_string()]).unwrap();
+    let mut headers = HeaderMap::new();
+    headers.insert("x-forwarded-for", HeaderValue::from_static("203.0.113.9, 157.240.1.1, 10.1.2.3"));
+
+    // Hop paling kanan yang bukan proxy terpercaya adalah client
+    assert_eq!(client_ip(ip("10.0.0.7"), &headers, &trusted), ip("157.240.1.1"));
+    assert_eq!(client_ip(ip("192.168.1.5"), &headers, &trusted), ip("157.240.1.1"));
+    // Peer yang tidak terpercaya tidak boleh memalsukan X-Forwarded-For
+    assert_eq!(client_ip(ip("198.51.100.1"), &headers, &trusted), ip("198.51.100.1"));
+
+    assert!(IpNetList::parse(&["not-a-cidr".to_string()]).is_err());
+}
+
+#[tokio::test]
+async fn test_allowlist_rejects_unknown_sources() {
+    let config = IngressConfig {
+        allowlist: vec!["157.240.0.0/16".to_string()],
+        trusted_proxies: vec!["10.0.0.0/8".to_string()],
+        ..IngressConfig::default()
+    };
+
+    let allowed = send(&router(&config), request_from("10.0.0.7", Some("157.240.1.1"))).await;
+    assert_eq!(allowed.status(), StatusCode::OK);
+
+    let denied = send(&router(&config), request_from("198.51.100.1", Some("157.240.1.1"))).await;
+    assert_eq!(denied.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_ip_rate_limit() {
+    let config = IngressConfig {
+        ip_rate_limit: RateLimitConfig { enabled: true, per_second: 0.1, burst: 1 },
+        ..IngressConfig::default()
+    };
+    let app = router(&config);
+
+    let first = send(&app, request_from("198.51.100.1", None)).await;
+    assert_eq!(first.status(), StatusCode::OK);
+
+    let limited = send(&app, request_from("198.51.100.1", None)).await;
+    assert_eq!(limited.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(limited.headers()[header::RETRY_AFTER], "10");
+
+    let second_ip = send(&app, request_from("198.51.100.2", None)).await;
+    assert_eq!(second_ip.status(), StatusCode::OK);
+}
+
+#[test]
+fn test_tenant_rate_limit() {
+    let config = IngressConfig {
+        tenant_rate_limit: RateLimitConfig { enabled: true, per_second: 0.1, burst: 2 },
+        ..IngressConfig::default()
+    };
+    let limiter = TenantRateLimiter::new(&config);
+    let webhook = tenant_webhook();
+    assert_eq!(webhook.tenant.as_deref(), Some("115159954803011"));
+
+    assert!(limiter.check(&webhook).is_none());
+    assert!(limiter.check(&webhook).is_none());
+    let limited = limiter.check(&webhook).expect("third webhook of the tenant is limited");
+    assert_eq!(limited.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(limited.headers()[header::RETRY_AFTER], "10");
+
+    // Webhook tanpa tenant tidak dibatasi per tenant
+    let no_tenant = InboundWebhook::new(HashMap::new(), Bytes::from_static(b"{}"));
+    assert!(limiter.check(&no_tenant).is_none());
+}
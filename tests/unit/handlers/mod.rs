@@ -0,0 +1,5 @@
+pub mod ingress_tests;
+pub mod tls_tests;
+
+// Unit tests for handlers
+// This module provides unit testing for the HTTP layers
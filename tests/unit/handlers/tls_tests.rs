@@ -0,0 +1,195 @@
+use std::fs;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use axum::extract::ConnectInfo;
+use axum::routing::get;
+use axum::Router;
+use rcgen::{BasicConstraints, Certificate, CertificateParams, IsCa, KeyPair};
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName};
+use rustls::{ClientConfig, RootCertStore};
+use tempfile::TempDir;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_rustls::client::TlsStream;
+use tokio_rustls::TlsConnector;
+
+use webhook_gateway::config::{TlsConfig, TlsVersion};
+use webhook_gateway::handlers::tls::{serve_tls, TlsReloader};
+
+struct Issued {
+    cert: Certificate,
+    key: KeyPair,
+}
+
+fn ca() -> Issued {
+    let key = KeyPair::generate().unwrap();
+    let mut params = CertificateParams::new(Vec::<String>::new()).unwrap();
+    params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+    Issued { cert: params.self_signed(&key).unwrap(), key }
+}
+
+fn issue(name: &str, ca: &Issued) -> Issued {
+    let key = KeyPair::generate().unwrap();
+    let cert = CertificateParams::new(vec![name.to_string()])
+        .unwrap()
+        .signed_by(&key, &ca.cert, &ca.key)
+        .unwrap();
+    Issued { cert, key }
+}
+
+/// Write the server certificate and bump the mtime so the change is seen
+fn write_server_files(dir: &Path, issued: &Issued, age: u64) {
+    for (name, pem) in [("server.crt", issued.cert.pem()), ("server.key", issued.key.serialize_pem())] {
+        let path = dir.join(name);
+        fs::write(&path, pem).unwrap();
+        let modified = SystemTime::now() - Duration::from_secs(age);
+        fs::File::options().write(true).open(&path).unwrap().set_modified(modified).unwrap();
+    }
+}
+
+fn tls_config(dir: &Path) -> TlsConfig {
+    TlsConfig {
+        enabled: true,
+        cert_path: dir.join("server.crt").to_string_lossy().to_string(),
+        key_path: dir.join("server.key").to_string_lossy().to_string(),
+        ..TlsConfig::default()
+    }
+}
+
+fn client(ca: &Issued, identity: Option<&Issued>, alpn: &[&[u8]], versions: &[&'static rustls::SupportedProtocolVersion]) -> TlsConnector {
+    let mut roots = RootCertStore::empty();
+    roots.add(ca.cert.der().clone()).unwrap();
+    let builder = ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+        .with_protocol_versions(versions)
+        .unwrap()
+        .with_root_certificates(roots);
+    let mut config = match identity {
+        Some(identity) => builder
+            .with_client_auth_cert(
+                vec![identity.cert.der().clone()],
+                PrivateKeyDer::Pkcs8(identity.key.serialize_der().into()),
+            )
+            .unwrap(),
+        None => builder.with_no_client_auth(),
+    };
+    config.alpn_protocols = alpn.iter().map(|protocol| protocol.to_vec()).collect();
+    TlsConnector::from(Arc::new(config))
+}
+
+/// Certificate the server presented over an in-memory handshake
+async fn presented_certificate(reloader: &TlsReloader, ca: &Issued) -> CertificateDer<'static> {
+    let (client_io, server_io) = tokio::io::duplex(16 * 1024);
+    let acceptor = reloader.acceptor();
+    let server = tokio::spawn(async move { acceptor.accept(server_io).await.map(|_| ()) });
+
+    let connector = client(ca, None, &[], rustls::DEFAULT_VERSIONS);
+    let stream = connector
+        .connect(ServerName::try_from("localhost").unwrap(), client_io)
+        .await
+        .unwrap();
+    server.await.unwrap().unwrap();
+    stream.get_ref().1.peer_certificates().unwrap()[0].clone().into_owned()
+}
+
+async fn connect(addr: SocketAddr, connector: &TlsConnector) -> std::io::Result<TlsStream<TcpStream>> {
+    let tcp = TcpStream::connect(addr).await?;
+    connector.connect(ServerName::try_from("localhost").unwrap(), tcp).await
+}
+
+async fn get_root(stream: &mut TlsStream<TcpStream>) -> String {
+    stream
+        .write_all(b"GET / HTTP/1.1\r\nhost: localhost\r\nconnection: close\r\n\r\n")
+        .await
+        .unwrap();
+    let mut response = Vec::new();
+    let _ = stream.read_to_end(&mut response).await;
+    String::from_utf8_lossy(&response).to_string()
+}
+
+#[tokio::test]
+async fn test_certificate_reloads_when_files_change() {
+    let dir = TempDir::new().unwrap();
+    let ca = ca();
+    let first = issue("localhost", &ca);
+    write_server_files(dir.path(), &first, 60);
+
+    let reloader = TlsReloader::new(&tls_config(dir.path())).unwrap();
+    assert_eq!(presented_certificate(&reloader, &ca).await, *first.cert.der());
+    assert!(!reloader.reload_if_changed().unwrap());
+
+    let second = issue("localhost", &ca);
+    write_server_files(dir.path(), &second, 0);
+    assert!(reloader.reload_if_changed().unwrap());
+    assert_eq!(presented_certificate(&reloader, &ca).await, *second.cert.der());
+
+    // Key yang rusak ditolak, sertifikat sebelumnya tetap dipakai
+    fs::write(dir.path().join("server.key"), "not a key").unwrap();
+    fs::File::options()
+        .write(true)
+        .open(dir.path().join("server.key"))
+        .unwrap()
+        .set_modified(SystemTime::now() + Duration::from_secs(60))
+        .unwrap();
+    assert!(reloader.reload_if_changed().is_err());
+    assert_eq!(presented_certificate(&reloader, &ca).await, *second.cert.der());
+}
+
+#[tokio::test]
+async fn test_mtls_alpn_and_min_version() {
+    let dir = TempDir::new().unwrap();
+    let ca = ca();
+    write_server_files(dir.path(), &issue("localhost", &ca), 0);
+    fs::write(dir.path().join("client-ca.crt"), ca.cert.pem()).unwrap();
+
+    let config = TlsConfig {
+        min_version: TlsVersion::Tls13,
+        client_ca_path: dir.path().join("client-ca.crt").to_string_lossy().to_string(),
+        ..tls_config(dir.path())
+    };
+    let reloader = Arc::new(TlsReloader::new(&config).unwrap());
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let app = Router::new().route(
+        "/",
+        get(|ConnectInfo(peer): ConnectInfo<SocketAddr>| async move { peer.ip().to_string() }),
+    );
+    let (stop_tx, stop_rx) = tokio::sync::oneshot::channel::<()>();
+    let server = tokio::spawn(serve_tls(listener, app, reloader, async move {
+        let _ = stop_rx.await;
+    }));
+
+    let identity = issue("internal-caller", &ca);
+    let mut stream = connect(addr, &client(&ca, Some(&identity), &[b"http/1.1"], rustls::DEFAULT_VERSIONS))
+        .await
+        .unwrap();
+    assert_eq!(stream.get_ref().1.alpn_protocol(), Some(&b"http/1.1"[..]));
+    let response = get_root(&mut stream).await;
+    assert!(response.starts_with("HTTP/1.1 200"));
+    assert!(response.ends_with("127.0.0.1"));
+
+    let h2 = connect(addr, &client(&ca, Some(&identity), &[b"h2", b"http/1.1"], rustls::DEFAULT_VERSIONS))
+        .await
+        .unwrap();
+    assert_eq!(h2.get_ref().1.alpn_protocol(), Some(&b"h2"[..]));
+    drop(h2);
+
+    // Tanpa sertifikat client, TLS 1.3 menolak setelah handshake sisi client selesai
+    let anonymous = connect(addr, &client(&ca, None, &[b"http/1.1"], rustls::DEFAULT_VERSIONS)).await;
+    if let Ok(mut stream) = anonymous {
+        assert!(!get_root(&mut stream).await.contains("200"));
+    }
+
+    let tls12 = client(&ca, Some(&identity), &[b"http/1.1"], &[&rustls::version::TLS12]);
+    assert!(connect(addr, &tls12).await.is_err());
+
+    stop_tx.send(()).unwrap();
+    tokio::time::timeout(Duration::from_secs(5), server)
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+}
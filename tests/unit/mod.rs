@@ -1,4 +1,5 @@
 pub mod config;
+pub mod handlers;
 pub mod models;
 pub mod providers;
 pub mod services;
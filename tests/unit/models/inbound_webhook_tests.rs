@@ -0,0 +1,81 @@
+use bytes::Bytes;
+use std::collections::HashMap;
+use webhook_gateway::models::{InboundWebhook, PayloadClassification, WebhookMessage};
+use webhook_gateway::utils::compact_json;
+
+const DR_PAYLOAD: &str = r#"{
+    "xid": "abc-123",
+    "entry": [{
+        "changes": [{
+            "value": {
+                "metadata": { "phone_number_id": "115159954803011" },
+                "statuses": [{ "id": "wamid.1", "status": "delivered", "recipient_id": "6282228223500" }]
+            }
+        }]
+    }]
+}"#;
+
+const INBOUND_FLOW_PAYLOAD: &str = r#"{
+    "id": "flow-1",
+    "data": {
+        "entry": [{
+            "changes": [{
+                "value": {
+                    "messages": [{ "interactive": { "type": "nfm_reply", "nfm_reply": { "response_json": "{}" } } }]
+                }
+            }]
+        }]
+    }
+}"#;
+
+fn inbound(body: &str) -> InboundWebhook {
+    InboundWebhook::new(HashMap::new(), Bytes::from(body.to_string()))
+}
+
+#[test]
+fn test_delivery_receipt_context() {
+    let webhook = inbound(DR_PAYLOAD);
+
+    assert_eq!(webhook.classification, PayloadClassification::DeliveryReceipt);
+    assert!(webhook.classification.should_forward());
+    assert_eq!(webhook.request_id, "req-abc-123");
+    assert_eq!(webhook.tenant.as_deref(), Some("115159954803011"));
+    assert!(webhook.json.is_some());
+    assert!(webhook.parse_error.is_none());
+    assert_eq!(webhook.compact_body, compact_json(DR_PAYLOAD).unwrap());
+    assert_eq!(webhook.body_str(), DR_PAYLOAD);
+}
+
+#[test]
+fn test_inbound_flow_and_unsupported_classification() {
+    let flow = inbound(INBOUND_FLOW_PAYLOAD);
+    assert_eq!(flow.classification, PayloadClassification::InboundFlow);
+    assert_eq!(flow.request_id, "req-flow-1");
+
+    let other = inbound(r#"{"user": "john"}"#);
+    assert_eq!(other.classification, PayloadClassification::Unsupported);
+    assert!(!other.classification.should_forward());
+    assert!(other.request_id.starts_with("req-"));
+    assert!(other.tenant.is_none());
+}
+
+#[test]
+fn test_invalid_json_keeps_raw_body() {
+    let webhook = inbound("not json {");
+
+    assert_eq!(webhook.classification, PayloadClassification::InvalidJson);
+    assert_eq!(webhook.classification.as_str(), "invalid_json");
+    assert!(webhook.json.is_none());
+    assert!(webhook.parse_error.is_some());
+    assert_eq!(&webhook.raw[..], b"not json {");
+}
+
+#[test]
+fn test_from_webhook_message() {
+    let mut headers = HashMap::new();
+    headers.insert("content-type".to_string(), "application/json".to_string());
+    let webhook: InboundWebhook = WebhookMessage { headers, body: DR_PAYLOAD.to_string() }.into();
+
+    assert_eq!(webhook.headers.get("content-type").map(String::as_str), Some("application/json"));
+    assert_eq!(webhook.classification, PayloadClassification::DeliveryReceipt);
+}
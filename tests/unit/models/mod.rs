@@ -1,4 +1,6 @@
 pub mod model_tests;
+pub mod inbound_webhook_tests;
+pub mod whatsapp_model_tests;
 
 // Unit tests for models
 // This module provides comprehensive unit testing for all model components
\ No newline at end of file
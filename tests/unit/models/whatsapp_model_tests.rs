@@ -0,0 +1,168 @@
+use serde_json::{json, Value};
+use webhook_gateway::models::WhatsAppWebhook;
+
+fn dr_payload() -> Value {
+    json!({
+        "xid": "123",
+        "entry": [{
+            "id": "115617074757249",
+            "time": 0,
+            "changes": [{
+                "field": "messages",
+                "value": {
+                    "messaging_product": "whatsapp",
+                    "metadata": { "phone_number_id": "115159954803011", "display_phone_number": "6287845715199" },
+                    "statuses": [{
+                        "id": "wamid.HBgN",
+                        "status": "failed",
+                        "timestamp": "1677836780",
+                        "recipient_id": "6282228223500",
+                        "conversation": {
+                            "id": "4ea98b1e873569598832d04b6894ab08",
+                            "origin": { "type": "user_initiated" }
+                        },
+                        "pricing": { "billable": true, "pricing_model": "CBP", "category": "user_initiated" },
+                        "errors": [{ "code": 131047, "title": "Re-engagement message", "error_data": { "details": "24h" } }],
+                        "biz_opaque_callback_data": "campaign-7"
+                    }]
+                }
+            }]
+        }]
+    })
+}
+
+fn inbound_payload(interactive: Value) -> Value {
+    json!({
+        "data": {
+            "entry": [{
+                "changes": [{
+                    "value": {
+                        "metadata": { "phone_number_id": "115159954803011" },
+                        "contacts": [{ "profile": { "name": "Budi" }, "wa_id": "6281234567890" }],
+                        "messages": [{
+                            "from": "6281234567890",
+                            "id": "wamid.in",
+                            "timestamp": "1700000000",
+                            "type": "interactive",
+                            "interactive": interactive
+                        }]
+                    }
+                }]
+            }]
+        }
+    })
+}
+
+#[test]
+fn test_delivery_receipt_typed_view() {
+    let webhook = WhatsAppWebhook::from_json(&dr_payload()).unwrap();
+
+    assert!(webhook.is_delivery_receipt());
+    assert!(!webhook.is_inbound_flow());
+    assert_eq!(webhook.correlation_id(), Some("123"));
+    assert_eq!(webhook.phone_number_id(), Some("115159954803011"));
+
+    let status = webhook.statuses().next().unwrap();
+    assert_eq!(status.status.as_deref(), Some("failed"));
+    assert_eq!(status.pricing.as_ref().unwrap().billable, Some(true));
+    let conversation = status.conversation.as_ref().unwrap();
+    assert_eq!(conversation.origin.as_ref().unwrap().origin_type.as_deref(), Some("user_initiated"));
+    assert!(conversation.expiration_timestamp.is_none());
+    assert_eq!(status.errors[0].code, Some(131047));
+    assert_eq!(status.extra["biz_opaque_callback_data"], "campaign-7");
+}
+
+#[test]
+fn test_unknown_fields_survive_round_trip() {
+    let payload = dr_payload();
+    let webhook = WhatsAppWebhook::from_json(&payload).unwrap();
+
+    assert_eq!(webhook.entry[0].extra["time"], 0);
+    assert_eq!(serde_json::to_value(&webhook).unwrap(), payload);
+}
+
+#[test]
+fn test_interactive_message_variants() {
+    let flow = WhatsAppWebhook::from_json(&inbound_payload(json!({
+        "type": "nfm_reply",
+        "nfm_reply": { "name": "flow", "body": "Sent", "response_json": "{\"flow_token\":\"abc\"}" }
+    })))
+    .unwrap();
+    assert!(flow.is_inbound_flow());
+    assert!(!flow.is_delivery_receipt());
+    assert_eq!(flow.phone_number_id(), Some("115159954803011"));
+    let message = flow.messages().next().unwrap();
+    assert_eq!(message.message_type.as_deref(), Some("interactive"));
+    let nfm_reply = message.interactive.as_ref().unwrap().nfm_reply.as_ref().unwrap();
+    assert_eq!(nfm_reply.response_json, Some(json!("{\"flow_token\":\"abc\"}")));
+
+    let button = WhatsAppWebhook::from_json(&inbound_payload(json!({
+        "type": "button_reply",
+        "button_reply": { "id": "yes", "title": "Ya" }
+    })))
+    .unwrap();
+    assert!(!button.is_inbound_flow());
+    let interactive = button.messages().next().unwrap().interactive.clone().unwrap();
+    assert_eq!(interactive.button_reply.unwrap().title.as_deref(), Some("Ya"));
+
+    let list = WhatsAppWebhook::from_json(&inbound_payload(json!({
+        "type": "list_reply",
+        "list_reply": { "id": "row-1", "title": "Tabungan", "description": "Rekening tabungan" }
+    })))
+    .unwrap();
+    let interactive = list.messages().next().unwrap().interactive.clone().unwrap();
+    assert_eq!(interactive.list_reply.unwrap().description.as_deref(), Some("Rekening tabungan"));
+}
+
+#[test]
+fn test_media_and_location_messages() {
+    let payload = json!({
+        "entry": [{ "changes": [{ "value": { "messages": [
+            { "type": "image", "image": { "id": "media-1", "mime_type": "image/jpeg", "caption": "ktp" } },
+            { "type": "location", "location": { "latitude": -6.2, "longitude": 106.8, "name": "Kantor" } },
+            { "type": "text", "text": { "body": "halo" } }
+        ] } }] }]
+    });
+    let webhook = WhatsAppWebhook::from_json(&payload).unwrap();
+    let messages: Vec<_> = webhook.messages().collect();
+
+    assert_eq!(messages[0].image.as_ref().unwrap().mime_type.as_deref(), Some("image/jpeg"));
+    assert_eq!(messages[1].location.as_ref().unwrap().longitude, Some(106.8));
+    assert_eq!(messages[2].text.as_ref().unwrap().body.as_deref(), Some("halo"));
+    assert!(!webhook.is_delivery_receipt());
+}
+
+#[test]
+fn test_top_level_error_and_type_mismatch() {
+    let error = WhatsAppWebhook::from_json(&json!({ "error": { "code": 100, "message": "Invalid parameter" } })).unwrap();
+    assert!(error.is_delivery_receipt());
+
+    // Field yang dikenal dengan tipe salah membuat typed view tidak tersedia
+    assert!(WhatsAppWebhook::from_json(&json!({ "entry": "not a list" })).is_none());
+    assert!(WhatsAppWebhook::from_json(&json!([1, 2, 3])).is_none());
+}
+
+#[test]
+fn test_split_events_keeps_envelope() {
+    let mut payload = dr_payload();
+    let statuses = payload["entry"][0]["changes"][0]["value"]["statuses"].as_array_mut().unwrap();
+    let mut read = statuses[0].clone();
+    read["status"] = json!("read");
+    statuses.push(read);
+
+    let webhook = WhatsAppWebhook::from_json(&payload).unwrap();
+    let events = webhook.split_events();
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].event_key().as_deref(), Some("status:wamid.HBgN:failed"));
+    assert_eq!(events[1].event_key().as_deref(), Some("status:wamid.HBgN:read"));
+
+    let event = serde_json::to_value(&events[1]).unwrap();
+    assert_eq!(event["xid"], "123");
+    assert_eq!(event["entry"][0]["id"], "115617074757249");
+    assert_eq!(event["entry"][0]["changes"][0]["value"]["metadata"]["phone_number_id"], "115159954803011");
+    assert_eq!(event["entry"][0]["changes"][0]["value"]["statuses"].as_array().unwrap().len(), 1);
+
+    // Satu item saja, payload tidak dipecah
+    let single = WhatsAppWebhook::from_json(&dr_payload()).unwrap();
+    assert_eq!(single.split_events(), vec![single.clone()]);
+}
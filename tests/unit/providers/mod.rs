@@ -1 +1,10 @@
-pub mod test_logging;
\ No newline at end of file
+pub mod test_access_log;
+pub mod test_audit_log;
+pub mod test_http_client;
+pub mod test_logging;
+pub mod test_log_control;
+pub mod test_log_format;
+pub mod test_log_rotation;
+pub mod test_log_writer;
+pub mod test_metrics;
+pub mod test_otlp;
@@ -0,0 +1,132 @@
+use std::fs;
+use std::time::Duration;
+use axum::{body::Body, http::Request, response::IntoResponse, routing::get, Router};
+use chrono::{FixedOffset, TimeZone};
+use serde_json::Value;
+use tempfile::TempDir;
+use tower::{Layer, Service};
+use webhook_gateway::{
+    config::{AccessLogConfig, AccessLogFormat, LogWriterConfig, LoggerConfig},
+    handlers::access_log::AccessLogLayer,
+    providers::access_log::{format_entry, AccessLogEntry, AccessLogger, RequestClassification},
+};
+
+fn sample_entry(status: u16) -> AccessLogEntry {
+    AccessLogEntry {
+        remote_addr: Some("10.0.0.7".to_string()),
+        method: "POST".to_string(),
+        path: "/webhook".to_string(),
+        version: "HTTP/1.1".to_string(),
+        status,
+        body_size: Some(42),
+        latency: Duration::from_millis(15),
+        request_id: Some("req-1".to_string()),
+        classification: None,
+        referer: None,
+        user_agent: Some("WhatsApp".to_string()),
+    }
+}
+
+#[test]
+fn test_classification_follows_status_class() {
+    assert_eq!(sample_entry(200).classification(), "success");
+    assert_eq!(sample_entry(302).classification(), "redirect");
+    assert_eq!(sample_entry(429).classification(), "client_error");
+    assert_eq!(sample_entry(502).classification(), "server_error");
+}
+
+#[test]
+fn test_classification_prefers_handler_label() {
+    let entry = AccessLogEntry {
+        classification: Some("delivery_receipt:forwarded".to_string()),
+        ..sample_entry(502)
+    };
+    assert_eq!(entry.classification(), "delivery_receipt:forwarded");
+}
+
+#[test]
+fn test_format_entry_json_and_combined() {
+    let time = FixedOffset::east_opt(7 * 3600).unwrap().with_ymd_and_hms(2024, 3, 5, 13, 55, 36).unwrap();
+
+    let json: Value = serde_json::from_str(&format_entry(AccessLogFormat::Json, &sample_entry(200), time)).unwrap();
+    assert_eq!(json["remote_addr"], "10.0.0.7");
+    assert_eq!(json["method"], "POST");
+    assert_eq!(json["path"], "/webhook");
+    assert_eq!(json["status"], 200);
+    assert_eq!(json["body_size"], 42);
+    assert_eq!(json["classification"], "success");
+    assert_eq!(json["x-request-id"], "req-1");
+    assert_eq!(json["latency_ms"], 15.0);
+
+    let combined = format_entry(AccessLogFormat::Combined, &sample_entry(502), time);
+    assert_eq!(
+        combined,
+        "10.0.0.7 - - [05/Mar/2024:13:55:36 +0700] \"POST /webhook HTTP/1.1\" 502 42 \"-\" \"WhatsApp\" 15.000 server_error req-1"
+    );
+}
+
+#[tokio::test]
+async fn test_layer_writes_one_line_per_request() {
+    let temp_dir = TempDir::new().unwrap();
+    let logger_config = LoggerConfig {
+        dir: temp_dir.path().to_str().unwrap().to_string(),
+        file_name: "test".to_string(),
+        max_backups: 0,
+        max_size: 10,
+        max_age: 0,
+        compress: false,
+        local_time: false,
+        level: "info".to_string(),
+        writer: LogWriterConfig::default(),
+    };
+    let access_config = AccessLogConfig {
+        file_name: "access-test".to_string(),
+        ..AccessLogConfig::default()
+    };
+    let logger: &'static AccessLogger = Box::leak(Box::new(AccessLogger::new(&access_config, &logger_config).unwrap()));
+
+    let router = Router::new()
+        .route("/health", get(|| async { "ok" }))
+        .route(
+            "/webhook",
+            get(|| async {
+                let mut response = "ok".into_response();
+                response
+                    .extensions_mut()
+                    .insert(RequestClassification("inbound_flow:accepted".to_string()));
+                response
+            }),
+        );
+    let mut service = AccessLogLayer::new(logger).layer(router);
+
+    for _ in 0..2 {
+        let request = Request::builder().uri("/health").body(Body::empty()).unwrap();
+        let response = service.call(request).await.unwrap();
+        assert_eq!(response.status(), 200);
+    }
+    let request = Request::builder().uri("/missing").body(Body::empty()).unwrap();
+    service.call(request).await.unwrap();
+    let request = Request::builder().uri("/webhook").body(Body::empty()).unwrap();
+    service.call(request).await.unwrap();
+    assert!(logger.flush(Duration::from_secs(5)));
+
+    let path = fs::read_dir(temp_dir.path())
+        .unwrap()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .find(|path| path.to_string_lossy().ends_with(".access.log"))
+        .unwrap();
+    let lines: Vec<Value> = fs::read_to_string(path)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+
+    assert_eq!(lines.len(), 4);
+    assert_eq!(lines[0]["method"], "GET");
+    assert_eq!(lines[0]["path"], "/health");
+    assert_eq!(lines[0]["body_size"], 2);
+    assert_eq!(lines[2]["status"], 404);
+    assert_eq!(lines[2]["classification"], "client_error");
+    assert_eq!(lines[3]["classification"], "inbound_flow:accepted");
+}
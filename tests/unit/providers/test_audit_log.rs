@@ -0,0 +1,166 @@
+use std::fs;
+use std::time::Duration;
+use serde_json::{json, Value};
+use tempfile::TempDir;
+use webhook_gateway::{
+    config::{
+        AccessLogConfig, AdminConfig, AlertTemplateConfig, AlertingConfig, AppConfig, AuditConfig, BackpressureConfig, DispatchConfig, FanOutConfig, FlowReplyConfig, HttpClientConfig, IngressConfig, LogWriterConfig, OtlpConfig, OutboundConfig, PermataResponseConfig, MetaResponseConfig, StatusCoalesceConfig, StatusOrderConfig,
+        LoggerConfig, PermataBankLoginConfig, PermataBankWebhookConfig, SchedulerConfig, ServerConfig,
+        TelegramAlertConfig, TlsConfig, WebClientConfig, MaskRuleConfig, MaskStrategy,
+    },
+    providers::{audit_log::PermataExchange, AuditLogger},
+    utils::REDACTED,
+};
+
+fn create_test_config(log_dir: &str) -> AppConfig {
+    AppConfig {
+        server: ServerConfig {
+            listen_host: "127.0.0.1".to_string(),
+            listen_port: 8080,
+            webhook_path: "/webhook".to_string(),
+            tls: TlsConfig::default(),
+        },
+        webclient: WebClientConfig {
+            timeout: 30,
+            max_retries: 3,
+            retry_delay: 1,
+        },
+        permata_bank_login: PermataBankLoginConfig {
+            permata_static_key: "test_key".to_string(),
+            api_key: "test_api_key".to_string(),
+            token_url: "http://localhost/token".to_string(),
+            username: "test_user".to_string(),
+            password: "test_pass".to_string(),
+            login_payload: "grant_type=client_credentials".to_string(),
+        },
+        permata_bank_webhook: PermataBankWebhookConfig {
+            callbackstatus_url: "http://localhost/callback".to_string(),
+            organizationname: "test_org".to_string(),
+        },
+        token_scheduler: SchedulerConfig {
+            periodic_interval_mins: 15,
+        },
+        telegram_alert: TelegramAlertConfig {
+            api_url: "http://localhost/bot123:test/sendMessage".to_string(),
+            chat_id: "-123456789".to_string(),
+            message_thread_id: "123".to_string(),
+            alert_message_prefix: "[TEST]".to_string(),
+        },
+        logger: LoggerConfig {
+            dir: log_dir.to_string(),
+            file_name: "test".to_string(),
+            max_backups: 0,
+            max_size: 10,
+            max_age: 0,
+            compress: false,
+            local_time: false,
+            level: "info".to_string(),
+            writer: LogWriterConfig::default(),
+        },
+        admin: AdminConfig::default(),
+        alerting: AlertingConfig::default(),
+        alert_template: AlertTemplateConfig::default(),
+        audit: AuditConfig {
+            enabled: true,
+            file_name: "audit-test".to_string(),
+            hash_salt: Some("salt".to_string()),
+            masking: Vec::new(),
+            include_default_masking: true,
+        },
+        access_log: AccessLogConfig::default(),
+        otlp: OtlpConfig::default(),
+        flow_reply: FlowReplyConfig::default(),
+        fan_out: FanOutConfig::default(),
+        status_order: StatusOrderConfig::default(),
+        status_coalesce: StatusCoalesceConfig::default(),
+        dispatch: DispatchConfig::default(),
+        backpressure: BackpressureConfig::default(),
+        ingress: IngressConfig::default(),
+        outbound: OutboundConfig::default(),
+        http_client: HttpClientConfig::default(),
+        permata_response: PermataResponseConfig::default(),
+        meta_response: MetaResponseConfig::default(),
+    }
+}
+
+fn read_records(dir: &TempDir) -> Vec<Value> {
+    let path = fs::read_dir(dir.path())
+        .unwrap()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .find(|path| path.to_string_lossy().ends_with(".audit.log"))
+        .expect("audit file exists");
+
+    fs::read_to_string(path)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_audit_records_are_masked() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = create_test_config(temp_dir.path().to_str().unwrap());
+    let audit = AuditLogger::new(&config).unwrap();
+
+    let payload = r#"{"entry":[{"changes":[{"value":{"contacts":[{"profile":{"name":"Budi"},"wa_id":"6281234567890"}],"messages":[{"from":"6281234567890","text":{"body":"halo"}}]}}]}]}"#;
+    audit.record_webhook(
+        "req-1",
+        payload,
+        "forwarded",
+        200,
+        &json!({"StatusCode": "00", "StatusDesc": "Success"}),
+        Duration::from_millis(12),
+    );
+    audit.record_permata_exchange(
+        "req-1",
+        &PermataExchange {
+            attempt: 2,
+            signed_body: payload,
+            permata_timestamp: "2024-01-01T00:00:00.000+07:00",
+            status_code: Some(200),
+            response_body: Some(r#"{"StatusCode":"00"}"#),
+            latency: Duration::from_millis(30),
+            ..Default::default()
+        },
+    );
+    assert!(audit.shutdown(Duration::from_secs(5)));
+
+    let records = read_records(&temp_dir);
+    assert_eq!(records.len(), 2);
+
+    let webhook = &records[0];
+    assert_eq!(webhook["event"], "webhook");
+    assert_eq!(webhook["request_id"], "req-1");
+    assert_eq!(webhook["outcome"], "forwarded");
+    assert_eq!(webhook["http_status"], 200);
+    let value = &webhook["payload"]["entry"][0]["changes"][0]["value"];
+    assert_eq!(value["contacts"][0]["profile"]["name"], REDACTED);
+    assert_eq!(value["messages"][0]["text"]["body"], REDACTED);
+    assert!(value["contacts"][0]["wa_id"].as_str().unwrap().starts_with("sha256:"));
+
+    let exchange = &records[1];
+    assert_eq!(exchange["event"], "permata_exchange");
+    assert_eq!(exchange["attempt"], 2);
+    assert_eq!(exchange["status_code"], 200);
+    assert_eq!(exchange["response"]["StatusCode"], "00");
+    assert!(!exchange.to_string().contains("6281234567890"));
+}
+
+#[test]
+fn test_hashing_requires_salt() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut config = create_test_config(temp_dir.path().to_str().unwrap());
+    config.audit.hash_salt = None;
+    assert!(AuditLogger::new(&config).is_err());
+
+    // Tanpa rule hash, salt tidak diperlukan
+    config.audit.include_default_masking = false;
+    config.audit.masking = vec![MaskRuleConfig {
+        path: "**.text.body".to_string(),
+        strategy: MaskStrategy::Redact,
+        keep_last: 4,
+    }];
+    assert!(AuditLogger::new(&config).is_ok());
+}
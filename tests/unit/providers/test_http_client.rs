@@ -0,0 +1,216 @@
+use std::collections::HashMap;
+use std::fs;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::extract::ConnectInfo;
+use axum::routing::{get, post};
+use axum::Router;
+use rcgen::{BasicConstraints, Certificate, CertificateParams, IsCa, KeyPair};
+use tempfile::TempDir;
+use tokio::net::TcpListener;
+
+use webhook_gateway::config::{AppConfig, HttpClientConfig, OutboundProxyConfig, OutboundTargetConfig, TlsConfig};
+use webhook_gateway::handlers::tls::{serve_tls, TlsReloader};
+use webhook_gateway::providers::http_client::{build_client, Destination, HttpClientFactory};
+
+struct Issued {
+    cert: Certificate,
+    key: KeyPair,
+}
+
+fn ca() -> Issued {
+    let key = KeyPair::generate().unwrap();
+    let mut params = CertificateParams::new(Vec::<String>::new()).unwrap();
+    params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+    Issued { cert: params.self_signed(&key).unwrap(), key }
+}
+
+fn issue(name: &str, ca: &Issued) -> Issued {
+    let key = KeyPair::generate().unwrap();
+    let cert = CertificateParams::new(vec![name.to_string()])
+        .unwrap()
+        .signed_by(&key, &ca.cert, &ca.key)
+        .unwrap();
+    Issued { cert, key }
+}
+
+fn write(dir: &Path, name: &str, content: String) -> String {
+    let path = dir.join(name);
+    fs::write(&path, content).unwrap();
+    path.to_string_lossy().to_string()
+}
+
+/// Bank stand-in that requires a client certificate issued by `ca`
+async fn start_bank(dir: &Path, ca: &Issued, server: &Issued) -> u16 {
+    let config = TlsConfig {
+        enabled: true,
+        cert_path: write(dir, "bank.crt", server.cert.pem()),
+        key_path: write(dir, "bank.key", server.key.serialize_pem()),
+        client_ca_path: write(dir, "bank-client-ca.crt", ca.cert.pem()),
+        ..TlsConfig::default()
+    };
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let app = Router::new().route("/callback", post(|| async { "ok" }));
+    let tls = Arc::new(TlsReloader::new(&config).unwrap());
+    tokio::spawn(serve_tls(listener, app, tls, std::future::pending()));
+    port
+}
+
+async fn call(target: &OutboundTargetConfig, port: u16) -> reqwest::Result<String> {
+    let client = build_client(&HttpClientConfig::default(), Duration::from_secs(5), target).unwrap();
+    client
+        .post(format!("https://permata.test:{}/callback", port))
+        .send()
+        .await?
+        .text()
+        .await
+}
+
+#[tokio::test]
+async fn test_client_certificate_ca_bundle_and_resolve() {
+    let dir = TempDir::new().unwrap();
+    let ca = ca();
+    let port = start_bank(dir.path(), &ca, &issue("permata.test", &ca)).await;
+    let identity = issue("webhook-gateway", &ca);
+
+    let target = OutboundTargetConfig {
+        client_cert_path: write(dir.path(), "client.crt", identity.cert.pem()),
+        client_key_path: write(dir.path(), "client.key", identity.key.serialize_pem()),
+        ca_bundle_path: write(dir.path(), "bank-ca.crt", ca.cert.pem()),
+        local_address: "127.0.0.1".to_string(),
+        resolve: HashMap::from([("permata.test".to_string(), "127.0.0.1".to_string())]),
+        ..OutboundTargetConfig::default()
+    };
+    assert_eq!(call(&target, port).await.unwrap(), "ok");
+
+    // Tanpa sertifikat client bank menolak koneksi
+    let anonymous = OutboundTargetConfig {
+        client_cert_path: String::new(),
+        client_key_path: String::new(),
+        ..target.clone()
+    };
+    assert!(call(&anonymous, port).await.is_err());
+
+    // CA bank tidak dikenal tanpa ca_bundle
+    let system_roots = OutboundTargetConfig {
+        ca_bundle_path: String::new(),
+        ..target
+    };
+    assert!(call(&system_roots, port).await.is_err());
+}
+
+#[tokio::test]
+async fn test_pinned_certificate() {
+    let dir = TempDir::new().unwrap();
+    let ca = ca();
+    let server = issue("permata.test", &ca);
+    let port = start_bank(dir.path(), &ca, &server).await;
+    let identity = issue("webhook-gateway", &ca);
+
+    let pinned = OutboundTargetConfig {
+        client_cert_path: write(dir.path(), "client.crt", identity.cert.pem()),
+        client_key_path: write(dir.path(), "client.key", identity.key.serialize_pem()),
+        pinned_cert_path: write(dir.path(), "pinned.crt", server.cert.pem()),
+        resolve: HashMap::from([("permata.test".to_string(), "127.0.0.1:1".to_string())]),
+        ..OutboundTargetConfig::default()
+    };
+    assert_eq!(call(&pinned, port).await.unwrap(), "ok");
+
+    let other = issue("permata.test", &ca);
+    let wrong_pin = OutboundTargetConfig {
+        pinned_cert_path: write(dir.path(), "other.crt", other.cert.pem()),
+        ..pinned
+    };
+    assert!(call(&wrong_pin, port).await.is_err());
+}
+
+#[test]
+fn test_invalid_settings_fail_at_build() {
+    let timeout = Duration::from_secs(1);
+    let invalid = [
+        OutboundTargetConfig {
+            local_address: "not-an-ip".to_string(),
+            ..OutboundTargetConfig::default()
+        },
+        OutboundTargetConfig {
+            resolve: HashMap::from([("permata.test".to_string(), "permata.internal".to_string())]),
+            ..OutboundTargetConfig::default()
+        },
+        OutboundTargetConfig {
+            ca_bundle_path: "/nonexistent/ca.pem".to_string(),
+            ..OutboundTargetConfig::default()
+        },
+    ];
+    for target in &invalid {
+        assert!(build_client(&HttpClientConfig::default(), timeout, target).is_err());
+    }
+
+    let proxied = OutboundTargetConfig {
+        proxy: OutboundProxyConfig {
+            url: "http://proxy.internal:3128".to_string(),
+            username: "gateway".to_string(),
+            password: "secret".to_string(),
+        },
+        ..OutboundTargetConfig::default()
+    };
+    assert!(build_client(&HttpClientConfig::default(), timeout, &proxied).is_ok());
+}
+
+fn example_config() -> AppConfig {
+    serde_yaml::from_str(include_str!("../../../config.yaml.example")).unwrap()
+}
+
+/// Plain HTTP server answering with the source port of the connection
+async fn start_echo_port() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let app = Router::new()
+        .route("/", get(|ConnectInfo(peer): ConnectInfo<SocketAddr>| async move { peer.port().to_string() }))
+        .route("/slow", get(|| async {
+            tokio::time::sleep(Duration::from_secs(3)).await;
+            "late"
+        }));
+    tokio::spawn(async move {
+        axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+            .await
+            .unwrap();
+    });
+    addr
+}
+
+async fn source_port(client: &reqwest::Client, addr: SocketAddr) -> String {
+    client.get(format!("http://{}/", addr)).send().await.unwrap().text().await.unwrap()
+}
+
+#[tokio::test]
+async fn test_factory_shares_one_pool_per_destination() {
+    let addr = start_echo_port().await;
+    let factory = HttpClientFactory::new(&example_config());
+
+    // Dua service dengan client Permata memakai koneksi yang sama
+    let login = factory.client(Destination::Permata).unwrap();
+    let callback = factory.client(Destination::Permata).unwrap();
+    let first = source_port(&login, addr).await;
+    assert_eq!(source_port(&callback, addr).await, first);
+
+    let telegram = factory.client(Destination::Telegram).unwrap();
+    assert_ne!(source_port(&telegram, addr).await, first);
+}
+
+#[tokio::test]
+async fn test_read_timeout_is_separate_from_request_timeout() {
+    let addr = start_echo_port().await;
+    let mut config = example_config();
+    config.webclient.timeout = 30;
+    config.http_client.read_timeout_secs = 1;
+
+    let client = HttpClientFactory::new(&config).client(Destination::Permata).unwrap();
+    let started = std::time::Instant::now();
+    let error = client.get(format!("http://{}/slow", addr)).send().await.unwrap_err();
+    assert!(error.is_timeout());
+    assert!(started.elapsed() < Duration::from_secs(3));
+}
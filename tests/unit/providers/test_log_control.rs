@@ -0,0 +1,183 @@
+use std::fs;
+use std::sync::Arc;
+use std::time::Duration;
+use tempfile::TempDir;
+use tracing::level_filters::LevelFilter;
+use tracing_subscriber::layer::{Filter, SubscriberExt};
+use tracing_subscriber::{Layer, Registry};
+use webhook_gateway::{
+    config::{LogWriterConfig, LoggerConfig},
+    handlers::admin::SetLogLevelRequest,
+    providers::{
+        BackgroundLogWriter, JsonLogLayer, LogLevelControl, LogScope, RotatingFileWriter,
+        StructuredLogger,
+    },
+};
+
+const TTL: Duration = Duration::from_secs(60);
+
+#[test]
+fn test_base_level_applies_without_overrides() {
+    let control = LogLevelControl::new(LevelFilter::INFO);
+
+    assert_eq!(control.level_for("webhook_gateway::services", None, None), LevelFilter::INFO);
+    assert_eq!(control.snapshot().level, "info");
+    assert!(control.snapshot().overrides.is_empty());
+}
+
+#[test]
+fn test_overrides_match_their_scope() {
+    let control = LogLevelControl::new(LevelFilter::INFO);
+    control.set_override(
+        LogScope::Module { target: "webhook_gateway::services::permata".to_string() },
+        LevelFilter::DEBUG,
+        TTL,
+    );
+    control.set_override(LogScope::RequestId { request_id: "req-1".to_string() }, LevelFilter::TRACE, TTL);
+    control.set_override(LogScope::Tenant { tenant: "115159954803011".to_string() }, LevelFilter::DEBUG, TTL);
+
+    let permata = "webhook_gateway::services::permata_callbackstatus_client";
+    assert_eq!(control.level_for(permata, None, None), LevelFilter::DEBUG);
+    assert_eq!(control.level_for("webhook_gateway::handlers", None, None), LevelFilter::INFO);
+    assert_eq!(control.level_for("webhook_gateway::handlers", Some("req-1"), None), LevelFilter::TRACE);
+    assert_eq!(control.level_for("webhook_gateway::handlers", Some("req-2"), None), LevelFilter::INFO);
+    assert_eq!(
+        control.level_for("webhook_gateway::handlers", None, Some("115159954803011")),
+        LevelFilter::DEBUG
+    );
+
+    // Global override mengalahkan base level
+    control.set_override(LogScope::Global, LevelFilter::DEBUG, TTL);
+    assert_eq!(control.level_for("hyper", None, None), LevelFilter::DEBUG);
+}
+
+#[test]
+fn test_override_expires_after_ttl() {
+    let control = LogLevelControl::new(LevelFilter::WARN);
+    control.set_override(LogScope::Global, LevelFilter::DEBUG, Duration::from_millis(50));
+    assert_eq!(control.level_for("webhook_gateway", None, None), LevelFilter::DEBUG);
+
+    std::thread::sleep(Duration::from_millis(100));
+
+    assert_eq!(control.level_for("webhook_gateway", None, None), LevelFilter::WARN);
+    assert!(control.snapshot().overrides.is_empty());
+}
+
+#[test]
+fn test_setting_same_scope_replaces_override() {
+    let control = LogLevelControl::new(LevelFilter::INFO);
+    control.set_override(LogScope::Global, LevelFilter::TRACE, TTL);
+    control.set_override(LogScope::Global, LevelFilter::DEBUG, TTL);
+
+    let snapshot = control.snapshot();
+    assert_eq!(snapshot.overrides.len(), 1);
+    assert_eq!(snapshot.overrides[0].level, "debug");
+
+    assert!(control.clear_override(&LogScope::Global));
+    assert!(!control.clear_override(&LogScope::Global));
+}
+
+#[test]
+fn test_set_log_level_request_parses_scopes() {
+    let module: SetLogLevelRequest = serde_json::from_str(
+        r#"{"level":"debug","scope":"module","target":"webhook_gateway::services","ttl_secs":120}"#,
+    )
+    .unwrap();
+    assert_eq!(module.scope, LogScope::Module { target: "webhook_gateway::services".to_string() });
+    assert_eq!(module.ttl_secs, Some(120));
+
+    let request: SetLogLevelRequest =
+        serde_json::from_str(r#"{"level":"trace","scope":"request_id","request_id":"req-abc"}"#).unwrap();
+    assert_eq!(request.scope, LogScope::RequestId { request_id: "req-abc".to_string() });
+    assert!(request.ttl_secs.is_none());
+
+    let global: SetLogLevelRequest = serde_json::from_str(r#"{"level":"debug","scope":"global"}"#).unwrap();
+    assert_eq!(global.scope, LogScope::Global);
+}
+
+#[test]
+fn test_filter_raises_verbosity_for_one_request() {
+    let temp_dir = TempDir::new().unwrap();
+    let rotating = RotatingFileWriter::new(LoggerConfig {
+        dir: temp_dir.path().to_str().unwrap().to_string(),
+        file_name: "log-control-test".to_string(),
+        max_backups: 0,
+        max_size: 10,
+        max_age: 0,
+        compress: false,
+        local_time: false,
+        level: "error".to_string(),
+        writer: LogWriterConfig::default(),
+    })
+    .unwrap();
+    let path = rotating.current_path();
+    let writer = Arc::new(BackgroundLogWriter::start(LogWriterConfig::default(), Some(rotating)).unwrap());
+
+    let control = LogLevelControl::new(LevelFilter::ERROR);
+    control.set_override(LogScope::RequestId { request_id: "req-debug".to_string() }, LevelFilter::WARN, TTL);
+
+    let layer = JsonLogLayer::new(Arc::clone(&writer), false).with_filter(control.clone());
+    tracing::subscriber::with_default(tracing_subscriber::registry().with(layer), || {
+        let other = StructuredLogger::request_span("req-other");
+        other.in_scope(|| StructuredLogger::log_warning("hidden warning", None, None));
+
+        let debugged = StructuredLogger::request_span("req-other");
+        debugged.in_scope(|| {
+            StructuredLogger::record_request_id("req-debug");
+            StructuredLogger::log_warning("visible warning", None, None);
+        });
+    });
+    assert!(writer.flush(Duration::from_secs(5)));
+
+    let content = fs::read_to_string(path).unwrap();
+    assert!(content.contains("visible warning"));
+    assert!(!content.contains("hidden warning"));
+}
+
+#[test]
+fn test_callsite_interest_follows_overrides() {
+    let control = LogLevelControl::new(LevelFilter::INFO);
+    let layer = tracing_subscriber::fmt::layer().with_writer(std::io::sink).with_filter(control.clone());
+    tracing::subscriber::with_default(tracing_subscriber::registry().with(layer), || {
+        // Tanpa override, debug tidak pernah diaktifkan untuk callsite ini
+        assert!(!tracing::enabled!(tracing::Level::DEBUG));
+
+        control.set_override(LogScope::Global, LevelFilter::DEBUG, TTL);
+        assert!(tracing::enabled!(tracing::Level::DEBUG));
+
+        control.clear_all();
+        assert!(!tracing::enabled!(tracing::Level::DEBUG));
+    });
+}
+
+#[test]
+fn test_expired_override_stops_enabling_callsite() {
+    let control = LogLevelControl::new(LevelFilter::INFO);
+    let layer = tracing_subscriber::fmt::layer().with_writer(std::io::sink).with_filter(control.clone());
+    tracing::subscriber::with_default(tracing_subscriber::registry().with(layer), || {
+        control.set_override(LogScope::Global, LevelFilter::DEBUG, Duration::from_millis(50));
+        assert!(tracing::enabled!(tracing::Level::DEBUG));
+
+        std::thread::sleep(Duration::from_millis(100));
+
+        // Override kedaluwarsa dibuang saat callsite dicek lagi
+        assert!(!tracing::enabled!(tracing::Level::DEBUG));
+        assert!(control.snapshot().overrides.is_empty());
+    });
+}
+
+#[test]
+fn test_dependency_spans_follow_levels() {
+    let control = LogLevelControl::new(LevelFilter::INFO);
+    tracing::subscriber::with_default(tracing_subscriber::registry(), || {
+        let dependency = tracing::span!(target: "hyper::proto", tracing::Level::DEBUG, "dependency");
+        let own = tracing::span!(target: "webhook_gateway::handlers", tracing::Level::DEBUG, "own");
+
+        // Span milik crate ini selalu dibuat, span dependency ikut level
+        let interest = |span: &tracing::Span| {
+            Filter::<Registry>::callsite_enabled(&control, span.metadata().unwrap())
+        };
+        assert!(interest(&dependency).is_never());
+        assert!(interest(&own).is_always());
+    });
+}
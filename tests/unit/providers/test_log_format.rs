@@ -0,0 +1,153 @@
+use serde_json::{json, Map, Value};
+use std::fs;
+use std::sync::Arc;
+use std::time::Duration;
+use tempfile::TempDir;
+use tracing::Level;
+use tracing_subscriber::layer::SubscriberExt;
+use webhook_gateway::{
+    config::{LogWriterConfig, LoggerConfig},
+    providers::{
+        log_format::format_record, BackgroundLogWriter, JsonLogLayer, RotatingFileWriter,
+        StructuredLogger,
+    },
+};
+
+fn fields(value: Value) -> Map<String, Value> {
+    value.as_object().unwrap().clone()
+}
+
+#[test]
+fn test_format_record_keeps_gateway_schema() {
+    let line = format_record(
+        Level::ERROR,
+        "2025-01-01 10:00:00.000",
+        fields(json!({ "message": "boom", "unique_id": "u-1", "request_id": "r-1" })),
+        &Map::new(),
+    );
+
+    let record: Value = serde_json::from_str(&line).unwrap();
+    assert_eq!(
+        record,
+        json!({
+            "message": { "error": "boom" },
+            "timestamp": "2025-01-01 10:00:00.000",
+            "uniqueId": "u-1",
+            "x-request-id": "r-1"
+        })
+    );
+}
+
+#[test]
+fn test_format_record_defaults_and_additional_data() {
+    let line = format_record(
+        Level::INFO,
+        "2025-01-01 10:00:00.000",
+        fields(json!({
+            "message": "received",
+            "additional_data": "{\"method\":\"POST\",\"headers\":3}"
+        })),
+        &Map::new(),
+    );
+
+    let record: Value = serde_json::from_str(&line).unwrap();
+    assert_eq!(record["message"]["info"], "received");
+    assert_eq!(record["uniqueId"], "MAIN");
+    assert_eq!(record["x-request-id"], "MAIN");
+    assert_eq!(record["method"], "POST");
+    assert_eq!(record["headers"], 3);
+}
+
+#[test]
+fn test_format_record_falls_back_to_span_ids() {
+    let span_ids = fields(json!({ "unique_id": "req-span", "request_id": "req-span" }));
+
+    let inherited = format_record(Level::WARN, "t", fields(json!({ "message": "slow" })), &span_ids);
+    let record: Value = serde_json::from_str(&inherited).unwrap();
+    assert_eq!(record["message"]["warning"], "slow");
+    assert_eq!(record["uniqueId"], "req-span");
+
+    let explicit = format_record(
+        Level::WARN,
+        "t",
+        fields(json!({ "message": "slow", "request_id": "req-own" })),
+        &span_ids,
+    );
+    let record: Value = serde_json::from_str(&explicit).unwrap();
+    assert_eq!(record["uniqueId"], "req-span");
+    assert_eq!(record["x-request-id"], "req-own");
+}
+
+#[test]
+fn test_layer_carries_request_span_into_error_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let rotating = RotatingFileWriter::new(LoggerConfig {
+        dir: temp_dir.path().to_str().unwrap().to_string(),
+        file_name: "log-format-test".to_string(),
+        max_backups: 0,
+        max_size: 10,
+        max_age: 0,
+        compress: false,
+        local_time: false,
+        level: "info".to_string(),
+        writer: LogWriterConfig::default(),
+    })
+    .unwrap();
+    let path = rotating.current_path();
+    let writer = Arc::new(BackgroundLogWriter::start(LogWriterConfig::default(), Some(rotating)).unwrap());
+
+    let subscriber = tracing_subscriber::registry().with(JsonLogLayer::new(Arc::clone(&writer), false));
+    tracing::subscriber::with_default(subscriber, || {
+        let span = StructuredLogger::request_span("req-generated");
+        let _guard = span.enter();
+
+        StructuredLogger::log_info("info is stdout only", None, None, None);
+        StructuredLogger::log_warning("warning inside request", None, None);
+        StructuredLogger::record_request_id("req-from-body");
+        StructuredLogger::log_error("error after body parsed", None, None);
+    });
+    assert!(writer.flush(Duration::from_secs(5)));
+
+    let content = fs::read_to_string(path).unwrap();
+    let records: Vec<Value> = content.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0]["message"]["warning"], "warning inside request");
+    assert_eq!(records[0]["x-request-id"], "req-generated");
+    assert_eq!(records[1]["message"]["error"], "error after body parsed");
+    assert_eq!(records[1]["uniqueId"], "req-from-body");
+}
+
+#[tokio::test]
+async fn test_spawned_task_keeps_request_span() {
+    let temp_dir = TempDir::new().unwrap();
+    let rotating = RotatingFileWriter::new(LoggerConfig {
+        dir: temp_dir.path().to_str().unwrap().to_string(),
+        file_name: "log-format-spawn-test".to_string(),
+        max_backups: 0,
+        max_size: 10,
+        max_age: 0,
+        compress: false,
+        local_time: false,
+        level: "info".to_string(),
+        writer: LogWriterConfig::default(),
+    })
+    .unwrap();
+    let path = rotating.current_path();
+    let writer = Arc::new(BackgroundLogWriter::start(LogWriterConfig::default(), Some(rotating)).unwrap());
+
+    let subscriber = tracing_subscriber::registry().with(JsonLogLayer::new(Arc::clone(&writer), false));
+    let _default = tracing::subscriber::set_default(subscriber);
+
+    let span = StructuredLogger::request_span("req-spawned");
+    let handle = {
+        let _guard = span.enter();
+        StructuredLogger::spawn_in_current_span(async {
+            StructuredLogger::log_error("failed in background task", None, None);
+        })
+    };
+    handle.await.unwrap();
+    assert!(writer.flush(Duration::from_secs(5)));
+
+    let content = fs::read_to_string(path).unwrap();
+    assert!(content.contains("\"x-request-id\":\"req-spawned\""));
+}
@@ -0,0 +1,121 @@
+use flate2::read::GzDecoder;
+use std::fs::{self, File};
+use std::io::Read;
+use std::time::{Duration, SystemTime};
+use tempfile::TempDir;
+use webhook_gateway::{config::{LogWriterConfig, LoggerConfig}, providers::RotatingFileWriter};
+
+fn rotation_config(dir: &TempDir, max_backups: u32, max_age: u32, compress: bool) -> LoggerConfig {
+    LoggerConfig {
+        dir: dir.path().to_str().unwrap().to_string(),
+        file_name: "rotation-test".to_string(),
+        max_backups,
+        max_size: 1,
+        max_age,
+        compress,
+        local_time: true,
+        level: "info".to_string(),
+        writer: LogWriterConfig::default(),
+    }
+}
+
+/// One line of ~600KB, so every second write crosses the 1MB limit
+fn big_line(marker: char) -> Vec<u8> {
+    let mut line = vec![marker as u8; 600 * 1024];
+    line.push(b'\n');
+    line
+}
+
+#[test]
+fn test_rolls_over_at_max_size() {
+    let temp_dir = TempDir::new().unwrap();
+    let writer = RotatingFileWriter::new(rotation_config(&temp_dir, 0, 0, false)).unwrap();
+
+    writer.write_line(&big_line('a')).unwrap();
+    writer.write_line(&big_line('b')).unwrap();
+    writer.write_line(&big_line('c')).unwrap();
+
+    let backups = writer.backups().unwrap();
+    assert_eq!(backups.len(), 2);
+    for backup in &backups {
+        let name = backup.file_name().unwrap().to_str().unwrap();
+        assert!(name.starts_with("rotation-test."));
+        assert!(name.ends_with(".error.log"));
+    }
+
+    let active = fs::read(writer.current_path()).unwrap();
+    assert!(active.len() as u64 <= 1024 * 1024);
+    assert_eq!(active[0], b'c');
+}
+
+#[test]
+fn test_durable_writes_rotate_like_plain_writes() {
+    let temp_dir = TempDir::new().unwrap();
+    let writer = RotatingFileWriter::new(rotation_config(&temp_dir, 0, 0, false)).unwrap();
+
+    writer.write_line_durable(&big_line('a')).unwrap();
+    writer.write_line_durable(&big_line('b')).unwrap();
+
+    assert_eq!(writer.backups().unwrap().len(), 1);
+    assert_eq!(fs::read(writer.current_path()).unwrap(), big_line('b'));
+}
+
+#[test]
+fn test_compresses_rotated_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let writer = RotatingFileWriter::new(rotation_config(&temp_dir, 0, 0, true)).unwrap();
+
+    writer.write_line(&big_line('a')).unwrap();
+    writer.write_line(&big_line('b')).unwrap();
+
+    let backups = writer.backups().unwrap();
+    assert_eq!(backups.len(), 1);
+    assert!(backups[0].to_str().unwrap().ends_with(".error.log.gz"));
+
+    let mut decoded = Vec::new();
+    GzDecoder::new(File::open(&backups[0]).unwrap())
+        .read_to_end(&mut decoded)
+        .unwrap();
+    assert_eq!(decoded, big_line('a'));
+}
+
+#[test]
+fn test_keeps_at_most_max_backups() {
+    let temp_dir = TempDir::new().unwrap();
+    let writer = RotatingFileWriter::new(rotation_config(&temp_dir, 2, 0, false)).unwrap();
+
+    for marker in ['a', 'b', 'c', 'd', 'e'] {
+        writer.write_line(&big_line(marker)).unwrap();
+    }
+
+    let backups = writer.backups().unwrap();
+    assert_eq!(backups.len(), 2);
+
+    // Backup terbaru yang dipertahankan
+    let newest = fs::read(&backups[0]).unwrap();
+    assert_eq!(newest[0], b'd');
+}
+
+#[test]
+fn test_deletes_backups_older_than_max_age() {
+    let temp_dir = TempDir::new().unwrap();
+    let stale = temp_dir.path().join("rotation-test.2020-01-01.error.log.gz");
+    let recent = temp_dir.path().join("rotation-test.2020-01-02.error.log");
+    let unrelated = temp_dir.path().join("other-app.2020-01-01.error.log");
+    for path in [&stale, &recent, &unrelated] {
+        fs::write(path, b"old").unwrap();
+    }
+    File::options()
+        .write(true)
+        .open(&stale)
+        .unwrap()
+        .set_modified(SystemTime::now() - Duration::from_secs(10 * 24 * 60 * 60))
+        .unwrap();
+
+    let writer = RotatingFileWriter::new(rotation_config(&temp_dir, 0, 7, false)).unwrap();
+
+    assert!(!stale.exists());
+    assert!(recent.exists());
+    assert!(unrelated.exists());
+    assert_eq!(writer.backups().unwrap(), vec![recent]);
+}
@@ -0,0 +1,98 @@
+use std::fs;
+use std::io;
+use std::time::Duration;
+use tempfile::TempDir;
+use webhook_gateway::{
+    config::{LogOverflowPolicy, LogWriterConfig, LoggerConfig},
+    providers::{BackgroundLogWriter, RotatingFileWriter},
+};
+
+fn file_writer(temp_dir: &TempDir) -> RotatingFileWriter {
+    RotatingFileWriter::new(LoggerConfig {
+        dir: temp_dir.path().to_str().unwrap().to_string(),
+        file_name: "log-writer-test".to_string(),
+        max_backups: 0,
+        max_size: 10,
+        max_age: 0,
+        compress: false,
+        local_time: true,
+        level: "info".to_string(),
+        writer: LogWriterConfig::default(),
+    })
+    .unwrap()
+}
+
+#[test]
+fn test_only_file_records_reach_error_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let rotating = file_writer(&temp_dir);
+    let path = rotating.current_path();
+    let writer = BackgroundLogWriter::start(LogWriterConfig::default(), Some(rotating)).unwrap();
+
+    writer.write_line(r#"{"message":{"error":"to file"}}"#.to_string(), true);
+    writer.write_line(r#"{"message":{"info":"stdout only"}}"#.to_string(), false);
+    writer.write_file("{\"level\":\"ERROR\",\"fields\":{\"message\":\"from tracing\"}}\n".to_string());
+    assert!(writer.flush(Duration::from_secs(5)));
+
+    let content = fs::read_to_string(path).unwrap();
+    assert!(content.contains("to file"));
+    assert!(content.contains("from tracing"));
+    assert!(!content.contains("stdout only"));
+    assert_eq!(content.lines().count(), 2);
+}
+
+#[test]
+fn test_block_policy_keeps_every_record() {
+    let temp_dir = TempDir::new().unwrap();
+    let rotating = file_writer(&temp_dir);
+    let path = rotating.current_path();
+    let config = LogWriterConfig {
+        buffer_capacity: 2,
+        batch_size: 4,
+        overflow: LogOverflowPolicy::Block,
+        sample_rate: 10,
+    };
+    let writer = BackgroundLogWriter::start(config, Some(rotating)).unwrap();
+
+    for i in 0..200 {
+        writer.write_file(format!("record {}\n", i));
+    }
+    assert!(writer.shutdown(Duration::from_secs(5)));
+
+    let content = fs::read_to_string(path).unwrap();
+    assert_eq!(content.lines().count(), 200);
+    assert_eq!(writer.dropped(), 0);
+}
+
+#[test]
+fn test_drop_policy_counts_overflow() {
+    let config = LogWriterConfig {
+        buffer_capacity: 1,
+        batch_size: 1,
+        overflow: LogOverflowPolicy::Drop,
+        sample_rate: 10,
+    };
+    let writer = BackgroundLogWriter::start(config, None).unwrap();
+
+    // Tahan stdout supaya thread writer tertahan di flush batch pertama
+    let stdout_guard = io::stdout().lock();
+    writer.write_line("first".to_string(), false);
+    std::thread::sleep(Duration::from_millis(100));
+
+    for i in 0..10 {
+        writer.write_line(format!("overflow {}", i), false);
+    }
+    assert_eq!(writer.dropped(), 9);
+
+    drop(stdout_guard);
+    assert!(writer.shutdown(Duration::from_secs(5)));
+}
+
+#[test]
+fn test_writes_after_shutdown_do_not_panic() {
+    let writer = BackgroundLogWriter::start(LogWriterConfig::default(), None).unwrap();
+    assert!(writer.shutdown(Duration::from_secs(5)));
+
+    writer.write_line("after shutdown".to_string(), true);
+    assert!(!writer.flush(Duration::from_millis(100)));
+}
@@ -1,5 +1,5 @@
 use webhook_gateway::{
-    config::LoggerConfig,
+    config::{LogWriterConfig, LoggerConfig},
     providers::StructuredLogger,
 };
 use std::fs;
@@ -19,6 +19,8 @@ fn test_error_file_logging() {
         max_age: 90,
         compress: true,
         local_time: true,
+        level: "info".to_string(),
+        writer: LogWriterConfig::default(),
     };
 
     // Initialize logger with file logging
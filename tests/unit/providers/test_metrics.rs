@@ -0,0 +1,52 @@
+use chrono::{Duration, Utc};
+use webhook_gateway::providers::Metrics;
+
+#[test]
+fn test_increment_and_total() {
+    let metrics = Metrics::new();
+
+    metrics.increment("webhook_received");
+    metrics.increment("webhook_received");
+    metrics.add("webhook_received", 3);
+
+    assert_eq!(metrics.total("webhook_received"), 5);
+    assert_eq!(metrics.total("unknown"), 0);
+}
+
+#[test]
+fn test_count_in_window_excludes_old_events() {
+    let metrics = Metrics::new();
+    let now = Utc::now();
+
+    metrics.add_at("failures", 4, now - Duration::seconds(90));
+    metrics.add_at("failures", 2, now - Duration::seconds(30));
+    metrics.add_at("failures", 1, now);
+
+    assert_eq!(metrics.count_in_window("failures", 60), 3);
+    assert_eq!(metrics.count_in_window("failures", 120), 7);
+    assert_eq!(metrics.total("failures"), 7);
+}
+
+#[test]
+fn test_events_older_than_max_window_are_pruned() {
+    let metrics = Metrics::new();
+
+    metrics.add_at("failures", 10, Utc::now() - Duration::hours(2));
+    metrics.increment("failures");
+
+    assert_eq!(metrics.count_in_window("failures", 7200), 1);
+    assert_eq!(metrics.total("failures"), 11);
+}
+
+#[test]
+fn test_snapshot_contains_counters() {
+    let metrics = Metrics::new();
+    metrics.increment("permata_callback_success");
+
+    let snapshot = metrics.snapshot();
+    let counter = snapshot.get("permata_callback_success").unwrap();
+
+    assert_eq!(counter.total, 1);
+    assert_eq!(counter.last_minute, 1);
+    assert!(counter.last_seen.is_some());
+}
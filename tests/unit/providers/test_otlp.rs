@@ -0,0 +1,116 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use mockito::Server;
+use serde_json::Value;
+use tracing_subscriber::layer::SubscriberExt;
+use webhook_gateway::{
+    config::OtlpConfig,
+    providers::otlp::{current_trace_context, inject_traceparent, set_remote_parent, TRACEPARENT_HEADER},
+    providers::{OtlpExporter, OtlpTraceLayer, TraceContext},
+};
+
+const TRACEPARENT: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+fn exporter_for(endpoint: String) -> Arc<OtlpExporter> {
+    Arc::new(
+        OtlpExporter::start(OtlpConfig {
+            enabled: true,
+            endpoint,
+            export_interval_ms: 60_000,
+            ..OtlpConfig::default()
+        })
+        .unwrap(),
+    )
+}
+
+#[test]
+fn test_traceparent_parse_and_format() {
+    let context = TraceContext::parse_traceparent(TRACEPARENT).unwrap();
+    assert_eq!(context.trace_id_hex(), "4bf92f3577b34da6a3ce929d0e0e4736");
+    assert_eq!(context.span_id_hex(), "00f067aa0ba902b7");
+    assert!(context.sampled);
+    assert_eq!(context.to_traceparent(), TRACEPARENT);
+
+    assert!(TraceContext::parse_traceparent("00-00000000000000000000000000000000-00f067aa0ba902b7-01").is_none());
+    assert!(TraceContext::parse_traceparent("ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01").is_none());
+    assert!(TraceContext::parse_traceparent("00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01").is_none());
+    assert!(TraceContext::parse_traceparent("00-4bf92f35-00f067aa0ba902b7-01").is_none());
+    assert!(TraceContext::parse_traceparent("garbage").is_none());
+}
+
+#[test]
+fn test_spans_exported_with_remote_parent() {
+    let mut server = Server::new();
+    let captured = Arc::new(Mutex::new(Vec::<Value>::new()));
+    let collector_bodies = Arc::clone(&captured);
+    let collector = server
+        .mock("POST", "/v1/traces")
+        .match_header("content-type", "application/json")
+        .with_status(200)
+        .with_body_from_request(move |request| {
+            let body = serde_json::from_slice(request.body().unwrap()).unwrap();
+            collector_bodies.lock().unwrap().push(body);
+            b"{}".to_vec()
+        })
+        .create();
+
+    let exporter = exporter_for(format!("{}/v1/traces", server.url()));
+    let subscriber = tracing_subscriber::registry().with(OtlpTraceLayer::new(Arc::clone(&exporter)));
+
+    let outbound_traceparent = tracing::subscriber::with_default(subscriber, || {
+        let request = tracing::info_span!("request", otel.kind = "server", http.status_code = tracing::field::Empty);
+        set_remote_parent(&request, &TraceContext::parse_traceparent(TRACEPARENT).unwrap());
+
+        let traceparent = request.in_scope(|| {
+            let attempt = tracing::info_span!("permata_callback_attempt", attempt = 1, otel.kind = "client");
+            attempt.in_scope(|| current_trace_context().unwrap().to_traceparent())
+        });
+        request.record("http.status_code", 200);
+        traceparent
+    });
+    assert!(exporter.flush(Duration::from_secs(5)));
+    collector.assert();
+
+    let bodies = captured.lock().unwrap();
+    let spans = bodies[0]["resourceSpans"][0]["scopeSpans"][0]["spans"].as_array().unwrap().clone();
+    assert_eq!(bodies[0]["resourceSpans"][0]["resource"]["attributes"][0]["value"]["stringValue"], "webhook-gateway");
+    assert_eq!(spans.len(), 2);
+
+    let attempt = spans.iter().find(|span| span["name"] == "permata_callback_attempt").unwrap();
+    let request = spans.iter().find(|span| span["name"] == "request").unwrap();
+    assert_eq!(request["traceId"], "4bf92f3577b34da6a3ce929d0e0e4736");
+    assert_eq!(request["parentSpanId"], "00f067aa0ba902b7");
+    assert_eq!(request["kind"], 2);
+    assert_eq!(attempt["traceId"], "4bf92f3577b34da6a3ce929d0e0e4736");
+    assert_eq!(attempt["parentSpanId"], request["spanId"]);
+    assert_eq!(attempt["kind"], 3);
+    assert!(request["attributes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|attribute| attribute["key"] == "http.status_code" && attribute["value"]["intValue"] == "200"));
+
+    // traceparent keluar memakai span attempt sebagai parent
+    assert_eq!(
+        outbound_traceparent,
+        format!("00-4bf92f3577b34da6a3ce929d0e0e4736-{}-01", attempt["spanId"].as_str().unwrap())
+    );
+}
+
+#[test]
+fn test_inject_traceparent_only_inside_traced_span() {
+    let client = reqwest::Client::new();
+    let untraced = inject_traceparent(client.post("http://localhost/callback")).build().unwrap();
+    assert!(untraced.headers().get(TRACEPARENT_HEADER).is_none());
+
+    let exporter = exporter_for("http://127.0.0.1:9/v1/traces".to_string());
+    let subscriber = tracing_subscriber::registry().with(OtlpTraceLayer::new(exporter));
+    let traced = tracing::subscriber::with_default(subscriber, || {
+        tracing::info_span!("alert_send").in_scope(|| inject_traceparent(client.post("http://localhost/callback")))
+    })
+    .build()
+    .unwrap();
+
+    let header = traced.headers().get(TRACEPARENT_HEADER).unwrap().to_str().unwrap();
+    assert!(TraceContext::parse_traceparent(header).is_some());
+}
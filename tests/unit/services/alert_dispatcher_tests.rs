@@ -0,0 +1,163 @@
+use mockito::Server;
+use reqwest::Client;
+use serde_json::json;
+use tokio::time::Duration;
+
+use webhook_gateway::config::AlertDeliveryConfig;
+use webhook_gateway::services::alert_dispatcher::{AlertDelivery, AlertDispatcher};
+
+fn fast_delivery_config() -> AlertDeliveryConfig {
+    AlertDeliveryConfig {
+        queue_capacity: 4,
+        max_attempts: 3,
+        initial_backoff_ms: 10,
+        max_backoff_secs: 1,
+        shutdown_deadline_secs: 5,
+    }
+}
+
+fn delivery(url: String, text: &str) -> AlertDelivery {
+    AlertDelivery {
+        channel: "Telegram",
+        client: Client::new(),
+        url,
+        payload: json!({ "chat_id": "-100123", "text": text }),
+        summary: text.to_string(),
+    }
+}
+
+#[tokio::test]
+async fn test_flush_delivers_queued_alerts() {
+    let mut server = Server::new_async().await;
+    let telegram_mock = server
+        .mock("POST", "/sendMessage")
+        .with_status(200)
+        .with_body(r#"{"ok":true}"#)
+        .expect(3)
+        .create_async()
+        .await;
+
+    let dispatcher = AlertDispatcher::start(fast_delivery_config());
+    for i in 0..3 {
+        assert!(dispatcher.enqueue(delivery(format!("{}/sendMessage", server.url()), &format!("alert {}", i))));
+    }
+
+    assert!(dispatcher.flush(Duration::from_secs(5)).await);
+    assert_eq!(dispatcher.pending(), 0);
+    telegram_mock.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_rate_limited_alert_is_retried_after_retry_after() {
+    let mut server = Server::new_async().await;
+    let rate_limited = server
+        .mock("POST", "/sendMessage")
+        .with_status(429)
+        .with_body(r#"{"ok":false,"error_code":429,"description":"Too Many Requests: retry after 1","parameters":{"retry_after":1}}"#)
+        .expect(1)
+        .create_async()
+        .await;
+
+    let dispatcher = AlertDispatcher::start(fast_delivery_config());
+    let started = std::time::Instant::now();
+    assert!(dispatcher.enqueue(delivery(format!("{}/sendMessage", server.url()), "rate limited")));
+
+    // Tunggu request pertama ditolak, lalu ganti mock supaya retry berhasil
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    rate_limited.assert_async().await;
+    rate_limited.remove_async().await;
+    let accepted = server
+        .mock("POST", "/sendMessage")
+        .with_status(200)
+        .expect(1)
+        .create_async()
+        .await;
+
+    assert!(dispatcher.flush(Duration::from_secs(5)).await);
+    assert!(started.elapsed() >= Duration::from_secs(1));
+    accepted.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_server_errors_are_retried_until_max_attempts() {
+    let mut server = Server::new_async().await;
+    let failing = server
+        .mock("POST", "/sendMessage")
+        .with_status(502)
+        .expect(3)
+        .create_async()
+        .await;
+
+    let dispatcher = AlertDispatcher::start(fast_delivery_config());
+    assert!(dispatcher.enqueue(delivery(format!("{}/sendMessage", server.url()), "bad gateway")));
+
+    assert!(dispatcher.flush(Duration::from_secs(5)).await);
+    failing.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_client_errors_are_not_retried() {
+    let mut server = Server::new_async().await;
+    let rejected = server
+        .mock("POST", "/sendMessage")
+        .with_status(400)
+        .with_body(r#"{"ok":false,"description":"Bad Request: can't parse entities"}"#)
+        .expect(1)
+        .create_async()
+        .await;
+
+    let dispatcher = AlertDispatcher::start(fast_delivery_config());
+    assert!(dispatcher.enqueue(delivery(format!("{}/sendMessage", server.url()), "bad markup")));
+
+    assert!(dispatcher.flush(Duration::from_secs(5)).await);
+    rejected.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_enqueue_after_flush_is_rejected() {
+    let dispatcher = AlertDispatcher::start(fast_delivery_config());
+    assert!(dispatcher.flush(Duration::from_secs(1)).await);
+
+    assert!(!dispatcher.enqueue(delivery("http://127.0.0.1:1/sendMessage".to_string(), "too late")));
+}
+
+#[tokio::test]
+async fn test_full_queue_drops_alerts() {
+    let config = AlertDeliveryConfig {
+        queue_capacity: 1,
+        ..fast_delivery_config()
+    };
+    let dispatcher = AlertDispatcher::start(config);
+
+    // Port 1 menolak koneksi, worker sibuk retry sehingga antrian penuh
+    let url = "http://127.0.0.1:1/sendMessage".to_string();
+    let accepted = (0..5)
+        .filter(|i| dispatcher.enqueue(delivery(url.clone(), &format!("alert {}", i))))
+        .count();
+
+    assert!(accepted < 5);
+    assert!(dispatcher.pending() <= 2);
+}
+
+#[tokio::test]
+async fn test_retry_after_past_shutdown_deadline_is_given_up() {
+    let mut server = Server::new_async().await;
+    let rate_limited = server
+        .mock("POST", "/sendMessage")
+        .with_status(429)
+        .with_body(r#"{"ok":false,"error_code":429,"parameters":{"retry_after":30}}"#)
+        .expect(1)
+        .create_async()
+        .await;
+
+    let dispatcher = AlertDispatcher::start(fast_delivery_config());
+    assert!(dispatcher.enqueue(delivery(format!("{}/sendMessage", server.url()), "rate limited")));
+    tokio::time::sleep(Duration::from_millis(300)).await;
+
+    // retry_after 30s tidak dipotong ke max_backoff_secs, tapi melewati deadline flush
+    let started = std::time::Instant::now();
+    assert!(dispatcher.flush(Duration::from_secs(2)).await);
+    assert!(started.elapsed() < Duration::from_secs(1));
+    assert_eq!(dispatcher.pending(), 0);
+    rate_limited.assert_async().await;
+}
@@ -0,0 +1,112 @@
+use webhook_gateway::services::{IncidentStatus, IncidentTracker};
+
+#[test]
+fn test_first_breach_opens_incident() {
+    let tracker = IncidentTracker::new(None);
+
+    tracker.record_breach("permata_callback_failing", "Permata callbacks failing", "error rate 50%", 3, None);
+
+    assert!(tracker.is_open("permata_callback_failing"));
+    assert!(!tracker.is_open("token_refresh_failing"));
+
+    let snapshot = tracker.snapshot();
+    assert_eq!(snapshot.open.len(), 1);
+    assert!(snapshot.resolved.is_empty());
+
+    let incident = &snapshot.open[0];
+    assert_eq!(incident.status, IncidentStatus::Open);
+    assert_eq!(incident.name, "permata_callback_failing");
+    assert_eq!(incident.title, "Permata callbacks failing");
+    assert_eq!(incident.affected_count, 3);
+    assert_eq!(incident.last_detail, "error rate 50%");
+}
+
+#[test]
+fn test_repeated_breaches_update_single_incident() {
+    let tracker = IncidentTracker::new(None);
+
+    tracker.record_breach("json_parse_errors", "JSON parse errors", "first", 11, None);
+    tracker.record_breach("json_parse_errors", "JSON parse errors", "second", 2, None);
+    tracker.record_breach("json_parse_errors", "JSON parse errors", "third", 0, None);
+
+    let snapshot = tracker.snapshot();
+    assert_eq!(snapshot.open.len(), 1);
+    assert_eq!(snapshot.open[0].affected_count, 13);
+    assert_eq!(snapshot.open[0].last_detail, "third");
+}
+
+#[test]
+fn test_recovery_resolves_incident() {
+    let tracker = IncidentTracker::new(None);
+
+    tracker.record_breach("token_refresh_failing", "Token refresh failing", "1 failure", 1, None);
+    tracker.record_breach("token_refresh_failing", "Token refresh failing", "2 failures", 1, None);
+    tracker.record_recovery("token_refresh_failing");
+
+    assert!(!tracker.is_open("token_refresh_failing"));
+
+    let snapshot = tracker.snapshot();
+    assert!(snapshot.open.is_empty());
+    assert_eq!(snapshot.resolved.len(), 1);
+
+    let incident = &snapshot.resolved[0];
+    assert_eq!(incident.status, IncidentStatus::Resolved);
+    assert_eq!(incident.affected_count, 2);
+    assert!(incident.resolved_at.is_some());
+    assert!(incident.duration_secs() >= 0);
+}
+
+#[test]
+fn test_recovery_without_open_incident_is_noop() {
+    let tracker = IncidentTracker::new(None);
+
+    tracker.record_recovery("permata_callback_failing");
+
+    let snapshot = tracker.snapshot();
+    assert!(snapshot.open.is_empty());
+    assert!(snapshot.resolved.is_empty());
+}
+
+#[test]
+fn test_incidents_are_tracked_independently() {
+    let tracker = IncidentTracker::new(None);
+
+    tracker.record_breach("permata_callback_failing", "Permata callbacks failing", "HTTP 503", 1, None);
+    tracker.record_breach("token_refresh_failing", "Token refresh failing", "Login failed", 1, None);
+    tracker.record_recovery("permata_callback_failing");
+
+    assert!(!tracker.is_open("permata_callback_failing"));
+    assert!(tracker.is_open("token_refresh_failing"));
+
+    let snapshot = tracker.snapshot();
+    assert_eq!(snapshot.open.len(), 1);
+    assert_eq!(snapshot.resolved.len(), 1);
+    assert_eq!(snapshot.resolved[0].name, "permata_callback_failing");
+}
+
+#[test]
+fn test_reopened_incident_is_new() {
+    let tracker = IncidentTracker::new(None);
+
+    tracker.record_breach("json_parse_errors", "JSON parse errors", "bad json", 12, None);
+    tracker.record_recovery("json_parse_errors");
+    tracker.record_breach("json_parse_errors", "JSON parse errors", "bad json again", 1, None);
+
+    let snapshot = tracker.snapshot();
+    assert_eq!(snapshot.open.len(), 1);
+    assert_eq!(snapshot.open[0].affected_count, 1);
+    assert_eq!(snapshot.resolved.len(), 1);
+}
+
+#[test]
+fn test_snapshot_serializes_for_admin_api() {
+    let tracker = IncidentTracker::new(None);
+    tracker.record_breach("permata_callback_failing", "Permata callbacks failing", "HTTP 500", 1, None);
+
+    let json = serde_json::to_value(tracker.snapshot()).unwrap();
+
+    assert_eq!(json["open"][0]["name"], "permata_callback_failing");
+    assert_eq!(json["open"][0]["status"], "open");
+    assert_eq!(json["open"][0]["affected_count"], 1);
+    assert!(json["resolved"].as_array().unwrap().is_empty());
+}
@@ -0,0 +1,161 @@
+use std::time::Duration;
+
+use chrono::Weekday;
+use webhook_gateway::config::{AlertRuleConfig, AlertRuleKind, AlertingConfig, BusinessHoursConfig};
+use webhook_gateway::providers::Metrics;
+use webhook_gateway::services::alert_rules::{default_rules, effective_rules, is_business_time};
+use webhook_gateway::services::{AlertRuleEngine, IncidentTracker};
+
+fn rule(name: &str, kind: AlertRuleKind, counter: &str, threshold: f64, window_secs: u64) -> AlertRuleConfig {
+    AlertRuleConfig {
+        name: name.to_string(),
+        title: None,
+        kind,
+        counter: counter.to_string(),
+        total_counter: None,
+        threshold,
+        window_secs,
+        min_events: 0,
+        business_hours: None,
+    }
+}
+
+fn engine_with(rules: Vec<AlertRuleConfig>, metrics: &Metrics, tracker: &IncidentTracker) -> AlertRuleEngine {
+    AlertRuleEngine::with_components(rules, Duration::from_secs(1), metrics.clone(), tracker.clone())
+}
+
+#[test]
+fn test_error_rate_rule_fires_above_threshold() {
+    let metrics = Metrics::new();
+    let tracker = IncidentTracker::new(None);
+    let mut error_rate = rule("error_rate", AlertRuleKind::ErrorRate, "failures", 5.0, 300);
+    error_rate.total_counter = Some("requests".to_string());
+    let engine = engine_with(vec![error_rate.clone()], &metrics, &tracker);
+
+    metrics.add("requests", 100);
+    metrics.add("failures", 5);
+    assert!(engine.evaluate(&error_rate).is_none(), "5% is not above 5%");
+
+    metrics.add("failures", 1);
+    let detail = engine.evaluate(&error_rate).expect("6% should breach");
+    assert!(detail.contains("6.0%"));
+}
+
+#[test]
+fn test_error_rate_rule_respects_min_events() {
+    let metrics = Metrics::new();
+    let tracker = IncidentTracker::new(None);
+    let mut error_rate = rule("error_rate", AlertRuleKind::ErrorRate, "failures", 5.0, 300);
+    error_rate.total_counter = Some("requests".to_string());
+    error_rate.min_events = 10;
+    let engine = engine_with(vec![error_rate.clone()], &metrics, &tracker);
+
+    metrics.add("requests", 2);
+    metrics.add("failures", 2);
+    assert!(engine.evaluate(&error_rate).is_none());
+
+    metrics.add("requests", 8);
+    assert!(engine.evaluate(&error_rate).is_some());
+}
+
+#[test]
+fn test_count_rule_only_counts_events_inside_window() {
+    let metrics = Metrics::new();
+    let tracker = IncidentTracker::new(None);
+    let auth_failures = rule("auth_failures", AlertRuleKind::Count, "auth_failure", 10.0, 60);
+    let engine = engine_with(vec![auth_failures.clone()], &metrics, &tracker);
+
+    metrics.add_at("auth_failure", 20, chrono::Utc::now() - chrono::Duration::seconds(120));
+    metrics.add("auth_failure", 10);
+    assert!(engine.evaluate(&auth_failures).is_none());
+
+    metrics.add("auth_failure", 1);
+    assert!(engine.evaluate(&auth_failures).is_some());
+}
+
+#[tokio::test]
+async fn test_absence_rule_fires_when_counter_is_silent() {
+    let metrics = Metrics::new();
+    let tracker = IncidentTracker::new(None);
+    let no_success = rule("no_success", AlertRuleKind::Absence, "callback_success", 0.0, 1);
+    let engine = engine_with(vec![no_success.clone()], &metrics, &tracker);
+
+    // Absence rules wait for a full window of uptime first
+    assert!(engine.evaluate(&no_success).is_none());
+
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    assert!(engine.evaluate(&no_success).is_some());
+
+    metrics.increment("callback_success");
+    assert!(engine.evaluate(&no_success).is_none());
+}
+
+#[test]
+fn test_evaluate_all_opens_and_resolves_incidents() {
+    let metrics = Metrics::new();
+    let tracker = IncidentTracker::new(None);
+    let mut parse_errors = rule("json_parse_errors", AlertRuleKind::Count, "json_parse_error", 2.0, 60);
+    parse_errors.title = Some("JSON parse errors".to_string());
+    let engine = engine_with(vec![parse_errors], &metrics, &tracker);
+
+    metrics.add("json_parse_error", 3);
+    engine.evaluate_all();
+
+    let snapshot = tracker.snapshot();
+    assert_eq!(snapshot.open.len(), 1);
+    assert_eq!(snapshot.open[0].title, "JSON parse errors");
+    assert_eq!(snapshot.open[0].affected_count, 3);
+
+    metrics.increment("json_parse_error");
+    engine.evaluate_all();
+    assert_eq!(tracker.snapshot().open[0].affected_count, 4);
+}
+
+#[test]
+fn test_single_event_does_not_page_with_default_rules() {
+    let metrics = Metrics::new();
+    let tracker = IncidentTracker::new(None);
+    let engine = engine_with(default_rules(), &metrics, &tracker);
+
+    metrics.increment("json_parse_error");
+    metrics.increment("permata_callback_total");
+    metrics.increment("permata_callback_failure");
+    engine.evaluate_all();
+
+    assert!(tracker.snapshot().open.is_empty());
+}
+
+#[test]
+fn test_rule_outside_business_hours_does_not_fire() {
+    let business_hours = BusinessHoursConfig {
+        start_hour: 8,
+        end_hour: 17,
+        days: vec![Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri],
+        utc_offset_hours: 7,
+    };
+
+    assert!(is_business_time(&business_hours, Weekday::Mon, 8));
+    assert!(is_business_time(&business_hours, Weekday::Fri, 16));
+    assert!(!is_business_time(&business_hours, Weekday::Fri, 17));
+    assert!(!is_business_time(&business_hours, Weekday::Sat, 10));
+    assert!(!is_business_time(&business_hours, Weekday::Wed, 7));
+}
+
+#[test]
+fn test_configured_rules_extend_default_rules() {
+    let mut config = AlertingConfig {
+        rules: vec![
+            rule("json_parse_errors", AlertRuleKind::Count, "json_parse_error", 50.0, 60),
+            rule("dead_letters", AlertRuleKind::Count, "dead_lettered", 0.0, 300),
+        ],
+        ..AlertingConfig::default()
+    };
+
+    let rules = effective_rules(&config);
+    let names: Vec<&str> = rules.iter().map(|rule| rule.name.as_str()).collect();
+    assert_eq!(names, vec!["permata_callback_failing", "token_refresh_failing", "json_parse_errors", "gateway_saturated", "dead_letters"]);
+    assert_eq!(rules[2].threshold, 50.0);
+
+    config.include_default_rules = false;
+    assert_eq!(effective_rules(&config).len(), 2);
+}
@@ -0,0 +1,277 @@
+use mockito::{Matcher, Server};
+use serde_json::json;
+use tokio::time::{sleep, Duration};
+
+use webhook_gateway::config::*;
+use webhook_gateway::services::alert_template::{
+    escape_html, escape_markdown_v2, escape_plain, render_template, DEFAULT_PLAIN_TEMPLATE,
+};
+use webhook_gateway::services::{AlertContext, TelegramAlertService};
+use webhook_gateway::utils::describe_whatsapp_error;
+
+const FAILED_DR_PAYLOAD: &str = r#"{
+    "entry": [{
+        "id": "115617074757249",
+        "changes": [{
+            "field": "messages",
+            "value": {
+                "metadata": { "phone_number_id": "115159954803011" },
+                "statuses": [{
+                    "id": "wamid.HBgNNjI4MjIyODIyMzUwMBUCABEYEjg1ODdCMEMxRjkyNUJCRUY5NwA=",
+                    "status": "failed",
+                    "recipient_id": "6282228223500",
+                    "errors": [{ "code": 131047, "title": "Re-engagement message" }]
+                }],
+                "messaging_product": "whatsapp"
+            }
+        }]
+    }]
+}"#;
+
+fn create_test_config(api_url: &str, format: AlertMessageFormat) -> AppConfig {
+    AppConfig {
+        server: ServerConfig {
+            listen_host: "127.0.0.1".to_string(),
+            listen_port: 8080,
+            webhook_path: "/webhook".to_string(),
+            tls: TlsConfig::default(),
+        },
+        webclient: WebClientConfig {
+            timeout: 5,
+            max_retries: 1,
+            retry_delay: 1,
+        },
+        permata_bank_login: PermataBankLoginConfig {
+            permata_static_key: "test".to_string(),
+            api_key: "test".to_string(),
+            token_url: "http://127.0.0.1:1/token".to_string(),
+            username: "test".to_string(),
+            password: "test".to_string(),
+            login_payload: "test".to_string(),
+        },
+        permata_bank_webhook: PermataBankWebhookConfig {
+            callbackstatus_url: "http://127.0.0.1:1/callback".to_string(),
+            organizationname: "test".to_string(),
+        },
+        token_scheduler: SchedulerConfig {
+            periodic_interval_mins: 3,
+        },
+        telegram_alert: TelegramAlertConfig {
+            api_url: api_url.to_string(),
+            chat_id: "-100123".to_string(),
+            message_thread_id: "7".to_string(),
+            alert_message_prefix: "[Gateway Alert]".to_string(),
+        },
+        logger: LoggerConfig {
+            dir: std::env::temp_dir().to_string_lossy().to_string(),
+            file_name: "test-alert-template".to_string(),
+            max_backups: 0,
+            max_size: 10,
+            max_age: 90,
+            compress: false,
+            local_time: true,
+            level: "info".to_string(),
+            writer: LogWriterConfig::default(),
+        },
+        admin: AdminConfig::default(),
+        alerting: AlertingConfig::default(),
+        alert_template: AlertTemplateConfig {
+            telegram_format: format,
+            telegram_template: None,
+            slack: None,
+        },
+        audit: AuditConfig::default(),
+        access_log: AccessLogConfig::default(),
+        otlp: OtlpConfig::default(),
+        flow_reply: FlowReplyConfig::default(),
+        fan_out: FanOutConfig::default(),
+        status_order: StatusOrderConfig::default(),
+        status_coalesce: StatusCoalesceConfig::default(),
+        dispatch: DispatchConfig::default(),
+        backpressure: BackpressureConfig::default(),
+        ingress: IngressConfig::default(),
+        outbound: OutboundConfig::default(),
+        http_client: HttpClientConfig::default(),
+        permata_response: PermataResponseConfig::default(),
+        meta_response: MetaResponseConfig::default(),
+    }
+}
+
+#[test]
+fn test_catalog_describes_common_whatsapp_errors() {
+    assert!(describe_whatsapp_error(131047).unwrap().contains("24 hours"));
+    assert!(describe_whatsapp_error(131026).unwrap().contains("undeliverable"));
+    assert!(describe_whatsapp_error(999999).is_none());
+}
+
+#[test]
+fn test_context_from_failed_dr_payload() {
+    let context = AlertContext::new("Permata failed")
+        .with_webhook_body(FAILED_DR_PAYLOAD)
+        .with_permata_response(r#"{"StatusCode":"06","StatusDesc":"General Error"}"#);
+
+    assert_eq!(context.phone_number_id.as_deref(), Some("115159954803011"));
+    assert!(context.message_id.as_deref().unwrap().starts_with("wamid."));
+    assert_eq!(context.recipient_id.as_deref(), Some("6282228223500"));
+    assert_eq!(context.status.as_deref(), Some("failed"));
+    assert_eq!(context.error_code.as_deref(), Some("131047"));
+    assert_eq!(context.permata_status_code.as_deref(), Some("06"));
+    assert_eq!(context.permata_status_desc.as_deref(), Some("General Error"));
+    assert!(context.error_description().unwrap().contains("Re-engagement"));
+}
+
+#[test]
+fn test_context_falls_back_to_paths_when_typed_parse_fails() {
+    // `code` berupa string tidak cocok dengan model typed, field lain tetap diambil
+    let payload = json!({
+        "entry": [{
+            "changes": [{
+                "value": {
+                    "metadata": { "phone_number_id": "115159954803011" },
+                    "statuses": [{
+                        "id": "wamid.HBgN",
+                        "status": "failed",
+                        "recipient_id": "6282228223500",
+                        "errors": [{ "code": "131047", "title": "Re-engagement message" }]
+                    }]
+                }
+            }]
+        }]
+    });
+    let context = AlertContext::new("Permata failed").with_webhook_payload(&payload);
+
+    assert_eq!(context.phone_number_id.as_deref(), Some("115159954803011"));
+    assert_eq!(context.message_id.as_deref(), Some("wamid.HBgN"));
+    assert_eq!(context.status.as_deref(), Some("failed"));
+    assert_eq!(context.error_code.as_deref(), Some("131047"));
+    assert_eq!(context.error_title.as_deref(), Some("Re-engagement message"));
+}
+
+#[test]
+fn test_context_ignores_non_json_bodies() {
+    let context = AlertContext::new("boom")
+        .with_webhook_body("not json")
+        .with_permata_response("<html>Bad Gateway</html>");
+
+    assert!(context.phone_number_id.is_none());
+    assert!(context.permata_status_code.is_none());
+}
+
+#[test]
+fn test_render_drops_lines_with_only_empty_variables() {
+    let context = AlertContext::new("Received non-2xx HTTP 500").with_request_id(Some("req-1"));
+    let rendered = render_template(DEFAULT_PLAIN_TEMPLATE, &context.variables("[Alert]"), escape_plain);
+
+    assert_eq!(rendered, "[Alert]\nReceived non-2xx HTTP 500\nRequest ID: req-1");
+}
+
+#[test]
+fn test_markdown_v2_escapes_variables_only() {
+    let context = AlertContext::new("rate 5.5% (3/50) > limit").with_title("[OPEN] Permata_failing");
+    let rendered = render_template("*{{title}}*\n{{message}}", &context.variables(""), escape_markdown_v2);
+
+    assert_eq!(rendered, "*\\[OPEN\\] Permata\\_failing*\nrate 5\\.5% \\(3/50\\) \\> limit");
+}
+
+#[test]
+fn test_html_escapes_variables() {
+    let context = AlertContext::new("<script>alert('x') & more</script>");
+    let rendered = render_template("<b>{{message}}</b>", &context.variables(""), escape_html);
+
+    assert_eq!(rendered, "<b>&lt;script&gt;alert('x') &amp; more&lt;/script&gt;</b>");
+}
+
+#[test]
+fn test_unknown_variables_render_empty() {
+    let context = AlertContext::new("hello");
+    let rendered = render_template("{{message}} {{does_not_exist}}", &context.variables(""), escape_plain);
+
+    assert_eq!(rendered, "hello");
+}
+
+#[tokio::test]
+async fn test_send_alert_uses_markdown_v2_parse_mode() {
+    let mut server = Server::new_async().await;
+    let telegram_mock = server
+        .mock("POST", "/sendMessage")
+        .match_body(Matcher::PartialJson(json!({
+            "chat_id": "-100123",
+            "message_thread_id": "7",
+            "parse_mode": "MarkdownV2"
+        })))
+        .with_status(200)
+        .with_body(r#"{"ok":true}"#)
+        .create_async()
+        .await;
+
+    let config = create_test_config(&format!("{}/sendMessage", server.url()), AlertMessageFormat::MarkdownV2);
+    let service = TelegramAlertService::new(config).unwrap();
+
+    let context = AlertContext::new("Permata returned 500")
+        .with_request_id(Some("req-42"))
+        .with_webhook_body(FAILED_DR_PAYLOAD);
+    let rendered = service.render_telegram(&context);
+    assert!(rendered.starts_with("*\\[Gateway Alert\\]"));
+    assert!(rendered.contains("*WhatsApp error 131047:*"));
+
+    service.send_alert(&context);
+    sleep(Duration::from_millis(500)).await;
+
+    telegram_mock.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_send_alert_posts_slack_blocks() {
+    let mut server = Server::new_async().await;
+    let telegram_mock = server
+        .mock("POST", "/sendMessage")
+        .with_status(200)
+        .create_async()
+        .await;
+    let slack_mock = server
+        .mock("POST", "/slack")
+        .match_body(Matcher::PartialJson(json!({
+            "text": "Token refresh failing",
+            "blocks": [{ "type": "section", "text": { "type": "mrkdwn" } }]
+        })))
+        .with_status(200)
+        .create_async()
+        .await;
+
+    let mut config = create_test_config(&format!("{}/sendMessage", server.url()), AlertMessageFormat::Html);
+    config.alert_template.slack = Some(SlackAlertConfig {
+        webhook_url: format!("{}/slack", server.url()),
+        template: Some("*{{prefix}}* {{message}}".to_string()),
+    });
+    let service = TelegramAlertService::new(config).unwrap();
+
+    let context = AlertContext::new("Token refresh failing");
+    assert_eq!(service.render_slack(&context), "*[Gateway Alert]* Token refresh failing");
+
+    service.send_alert(&context);
+    sleep(Duration::from_millis(500)).await;
+
+    telegram_mock.assert_async().await;
+    slack_mock.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_slack_uses_its_own_outbound_settings() {
+    let mut server = Server::new_async().await;
+    let slack_mock = server.mock("POST", "/slack").with_status(200).create_async().await;
+
+    let mut config = create_test_config(&format!("{}/sendMessage", server.url()), AlertMessageFormat::Plain);
+    config.alert_template.slack = Some(SlackAlertConfig {
+        webhook_url: format!("{}/slack", server.url()),
+        template: None,
+    });
+    // Proxy Telegram yang tidak bisa dihubungi tidak boleh ikut dipakai untuk Slack
+    config.outbound.telegram.proxy.url = "http://127.0.0.1:1".to_string();
+    config.alerting.delivery.max_attempts = 1;
+    let service = TelegramAlertService::new(config).unwrap();
+
+    service.send_alert(&AlertContext::new("Token refresh failing"));
+    sleep(Duration::from_millis(500)).await;
+
+    slack_mock.assert_async().await;
+}
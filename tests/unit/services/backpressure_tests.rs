@@ -0,0 +1,73 @@
+use std::sync::Arc;
+use tokio::time::{sleep, Duration};
+
+use webhook_gateway::config::BackpressureConfig;
+use webhook_gateway::services::backpressure::RejectReason;
+use webhook_gateway::services::Backpressure;
+
+fn backpressure(max_in_flight: usize, tenant_max_in_flight: usize, tenant_queue_depth: usize) -> Backpressure {
+    Backpressure::new(BackpressureConfig {
+        enabled: true,
+        max_in_flight,
+        tenant_max_in_flight,
+        tenant_queue_depth,
+        queue_timeout_ms: 100,
+        retry_after_secs: 7,
+        max_tenants: 2,
+    })
+}
+
+#[test]
+fn test_global_limit_rejects_without_waiting() {
+    let limits = backpressure(1, 1, 1);
+
+    let permit = limits.try_acquire_global().unwrap();
+    assert!(permit.is_some());
+    assert_eq!(limits.in_flight(), 1);
+    assert_eq!(limits.try_acquire_global().unwrap_err(), RejectReason::GlobalLimit);
+
+    drop(permit);
+    assert!(limits.try_acquire_global().is_ok());
+
+    let disabled = Backpressure::new(BackpressureConfig::default());
+    assert!(disabled.try_acquire_global().unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_tenant_queue_depth_and_timeout() {
+    let limits = Arc::new(backpressure(10, 1, 1));
+
+    let running = limits.acquire_tenant("tenant-a").await.unwrap();
+    assert!(running.is_some());
+
+    let waiter = {
+        let limits = limits.clone();
+        tokio::spawn(async move { limits.acquire_tenant("tenant-a").await.map(|permit| permit.is_some()) })
+    };
+    sleep(Duration::from_millis(20)).await;
+
+    // Antrian tenant-a penuh, tenant lain tidak terpengaruh
+    assert_eq!(limits.acquire_tenant("tenant-a").await.unwrap_err(), RejectReason::TenantQueueFull);
+    assert!(limits.acquire_tenant("tenant-b").await.unwrap().is_some());
+
+    assert_eq!(waiter.await.unwrap().unwrap_err(), RejectReason::TenantQueueTimeout);
+
+    drop(running);
+    assert!(limits.acquire_tenant("tenant-a").await.unwrap().is_some());
+}
+
+#[tokio::test]
+async fn test_idle_tenants_are_dropped_past_max_tenants() {
+    let limits = backpressure(10, 1, 1);
+
+    let busy = limits.acquire_tenant("tenant-a").await.unwrap();
+    for tenant in ["tenant-b", "tenant-c", "tenant-d", "tenant-e"] {
+        drop(limits.acquire_tenant(tenant).await.unwrap());
+        assert!(limits.tracked_tenants() <= 2);
+    }
+
+    // tenant-a masih memegang slot, limitnya tidak ikut dibuang
+    assert_eq!(limits.acquire_tenant("tenant-a").await.unwrap_err(), RejectReason::TenantQueueTimeout);
+    drop(busy);
+    assert!(limits.acquire_tenant("tenant-a").await.unwrap().is_some());
+}
@@ -0,0 +1,239 @@
+use bytes::Bytes;
+use mockito::Server;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tempfile::TempDir;
+
+use webhook_gateway::config::{AppConfig, PermataBankLoginConfig, PermataBankWebhookConfig, WebClientConfig, TelegramAlertConfig, SchedulerConfig, LoggerConfig, AdminConfig, AlertingConfig, AlertTemplateConfig, LogWriterConfig, AuditConfig, AccessLogConfig, OtlpConfig, FlowReplyConfig, FanOutConfig, StatusOrderConfig, StatusCoalesceConfig, DispatchConfig, BackpressureConfig, IngressConfig, OutboundConfig, HttpClientConfig, PermataResponseConfig, MetaResponseConfig, TlsConfig};
+use webhook_gateway::models::InboundWebhook;
+use webhook_gateway::providers::DeadLetterLog;
+use webhook_gateway::services::{EventDeduplicator, WebhookProcessor, WebhookProcessorTrait};
+
+fn create_test_config(mock_server_url: &str, log_dir: &str) -> AppConfig {
+    AppConfig {
+        server: webhook_gateway::config::ServerConfig {
+            listen_host: "127.0.0.1".to_string(),
+            listen_port: 8080,
+            webhook_path: "/webhook".to_string(),
+            tls: TlsConfig::default(),
+        },
+        permata_bank_login: PermataBankLoginConfig {
+            username: "test_user".to_string(),
+            password: "test_pass".to_string(),
+            api_key: "test_api_key".to_string(),
+            token_url: format!("{}/token", mock_server_url),
+            permata_static_key: "test_static_key".to_string(),
+            login_payload: "grant_type=client_credentials".to_string(),
+        },
+        permata_bank_webhook: PermataBankWebhookConfig {
+            callbackstatus_url: format!("{}/callback", mock_server_url),
+            organizationname: "TestOrg".to_string(),
+        },
+        webclient: WebClientConfig {
+            timeout: 5,
+            max_retries: 1,
+            retry_delay: 0,
+        },
+        telegram_alert: TelegramAlertConfig {
+            api_url: format!("{}/bot123:token/sendMessage", mock_server_url),
+            chat_id: "-123456789".to_string(),
+            message_thread_id: "123".to_string(),
+            alert_message_prefix: "[TEST] Alert:".to_string(),
+        },
+        token_scheduler: SchedulerConfig {
+            periodic_interval_mins: 15,
+        },
+        logger: LoggerConfig {
+            dir: log_dir.to_string(),
+            file_name: "test".to_string(),
+            max_backups: 0,
+            max_size: 10,
+            max_age: 90,
+            compress: true,
+            local_time: true,
+            level: "info".to_string(),
+            writer: LogWriterConfig::default(),
+        },
+        admin: AdminConfig::default(),
+        alerting: AlertingConfig::default(),
+        alert_template: AlertTemplateConfig::default(),
+        audit: AuditConfig::default(),
+        access_log: AccessLogConfig::default(),
+        otlp: OtlpConfig::default(),
+        flow_reply: FlowReplyConfig::default(),
+        fan_out: FanOutConfig {
+            enabled: true,
+            ..FanOutConfig::default()
+        },
+        status_order: StatusOrderConfig::default(),
+        status_coalesce: StatusCoalesceConfig::default(),
+        dispatch: DispatchConfig::default(),
+        backpressure: BackpressureConfig::default(),
+        ingress: IngressConfig::default(),
+        outbound: OutboundConfig::default(),
+        http_client: HttpClientConfig::default(),
+        permata_response: PermataResponseConfig::default(),
+        meta_response: MetaResponseConfig::default(),
+    }
+}
+
+fn batched_dr(xid: &str) -> InboundWebhook {
+    let status = |id: &str, status: &str| json!({ "id": id, "status": status, "recipient_id": "6282228223500" });
+    let payload = json!({
+        "xid": xid,
+        "entry": [
+            {
+                "id": "waba-1",
+                "changes": [{
+                    "field": "messages",
+                    "value": {
+                        "metadata": { "phone_number_id": "115159954803011" },
+                        "statuses": [status("wamid.1", "sent"), status("wamid.1", "delivered")]
+                    }
+                }]
+            },
+            {
+                "id": "waba-1",
+                "changes": [{
+                    "field": "messages",
+                    "value": {
+                        "metadata": { "phone_number_id": "115159954803011" },
+                        "statuses": [status("wamid.2", "read")]
+                    }
+                }]
+            }
+        ]
+    });
+    InboundWebhook::new(HashMap::new(), Bytes::from(payload.to_string()))
+}
+
+async fn mock_token(server: &mut mockito::ServerGuard) -> mockito::Mock {
+    server.mock("POST", "/token")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(json!({ "access_token": "test_token", "token_type": "Bearer", "expires_in": 3600, "scope": "api" }).to_string())
+        .expect_at_least(1)
+        .create_async().await
+}
+
+#[test]
+fn test_event_deduplicator_ttl_and_capacity() {
+    let dedup = EventDeduplicator::new(Duration::from_secs(60), 2);
+    assert!(dedup.claim("status:wamid.1:sent"));
+    assert!(!dedup.claim("status:wamid.1:sent"));
+
+    assert!(dedup.claim("status:wamid.1:delivered"));
+    assert!(dedup.claim("status:wamid.2:read"));
+    assert_eq!(dedup.len(), 2);
+    assert!(dedup.claim("status:wamid.1:sent"));
+
+    let expired = EventDeduplicator::new(Duration::ZERO, 10);
+    assert!(expired.claim("message:wamid.3"));
+    assert!(expired.claim("message:wamid.3"));
+}
+
+#[test]
+fn test_event_deduplicator_claim_is_exclusive_until_released() {
+    let dedup = Arc::new(EventDeduplicator::new(Duration::from_secs(60), 100));
+    let claimed: usize = (0..8)
+        .map(|_| {
+            let dedup = Arc::clone(&dedup);
+            std::thread::spawn(move || dedup.claim("message:wamid.4"))
+        })
+        .collect::<Vec<_>>()
+        .into_iter()
+        .map(|handle| handle.join().unwrap() as usize)
+        .sum();
+    assert_eq!(claimed, 1);
+
+    dedup.release("message:wamid.4");
+    assert!(dedup.claim("message:wamid.4"));
+    assert_eq!(dedup.len(), 1);
+}
+
+#[test]
+fn test_event_deduplicator_release_does_not_hold_capacity() {
+    let dedup = EventDeduplicator::new(Duration::from_secs(60), 2);
+    assert!(dedup.claim("message:wamid.5"));
+    // Retry terus gagal selama Permata down
+    for _ in 0..1000 {
+        assert!(dedup.claim("status:wamid.6:sent"));
+        dedup.release("status:wamid.6:sent");
+    }
+    assert_eq!(dedup.len(), 1);
+
+    assert!(dedup.claim("status:wamid.6:sent"));
+    assert!(dedup.claim("status:wamid.7:sent"));
+    // Kapasitas mengeluarkan klaim nyata yang paling lama
+    assert!(!dedup.claim("status:wamid.6:sent"));
+    assert!(dedup.claim("message:wamid.5"));
+}
+
+#[tokio::test]
+async fn test_fan_out_delivers_each_event_once() {
+    let mut server = Server::new_async().await;
+    let temp_dir = TempDir::new().unwrap();
+    let _token = mock_token(&mut server).await;
+    let callback = server.mock("POST", "/callback")
+        .with_status(200)
+        .with_body(json!({ "StatusCode": "00", "StatusDesc": "Success" }).to_string())
+        .expect(3)
+        .create_async().await;
+
+    let processor = WebhookProcessor::new(create_test_config(&server.url(), temp_dir.path().to_str().unwrap())).unwrap();
+
+    let first = processor.process_webhook(&batched_dr("batch-1")).await.unwrap();
+    let body: Value = serde_json::from_str(&first.body).unwrap();
+    assert_eq!(first.http_status, 200);
+    assert_eq!(body["Events"], 3);
+    assert_eq!(body["Delivered"], 3);
+
+    // Meta mengirim ulang batch yang sama, semua event sudah terkirim
+    let redelivered = processor.process_webhook(&batched_dr("batch-1")).await.unwrap();
+    let body: Value = serde_json::from_str(&redelivered.body).unwrap();
+    assert_eq!(body["Delivered"], 0);
+    assert_eq!(body["Duplicates"], 3);
+
+    callback.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_failed_events_are_dead_lettered() {
+    let mut server = Server::new_async().await;
+    let temp_dir = TempDir::new().unwrap();
+    let _token = mock_token(&mut server).await;
+    let _callback = server.mock("POST", "/callback")
+        .with_status(500)
+        .with_body("upstream down")
+        .expect_at_least(3)
+        .create_async().await;
+
+    let config = create_test_config(&server.url(), temp_dir.path().to_str().unwrap());
+    let processor = WebhookProcessor::new(config.clone()).unwrap();
+
+    let response = processor.process_webhook(&batched_dr("batch-2")).await.unwrap();
+    let body: Value = serde_json::from_str(&response.body).unwrap();
+    assert_eq!(response.http_status, 200);
+    assert_eq!(body["DeadLettered"], 3);
+
+    // Klaim event yang gagal dilepas, kiriman ulang Meta dicoba lagi
+    let retried = processor.process_webhook(&batched_dr("batch-2")).await.unwrap();
+    let body: Value = serde_json::from_str(&retried.body).unwrap();
+    assert_eq!(body["Duplicates"], 0);
+    assert_eq!(body["DeadLettered"], 3);
+
+    let path = DeadLetterLog::new(&config).unwrap().current_path();
+    let letters: Vec<Value> = std::fs::read_to_string(path)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(letters.len(), 6);
+    assert_eq!(letters[0]["request_id"], "req-batch-2");
+    assert_eq!(letters[0]["event_request_id"], "req-batch-2-1");
+    assert_eq!(letters[2]["event_key"], "status:wamid.2:read");
+    assert_eq!(letters[2]["status_code"], 500);
+    assert_eq!(letters[2]["payload"]["entry"][0]["changes"][0]["value"]["statuses"][0]["status"], "read");
+}
@@ -0,0 +1,115 @@
+use bytes::Bytes;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use webhook_gateway::config::{FlowFieldType, FlowReplyConfig, FlowReplyDecodeMode, FlowSchemaConfig};
+use webhook_gateway::models::InboundWebhook;
+use webhook_gateway::services::flow_reply::{FlowReplyDecoder, MALFORMED_RESPONSE_JSON, SCHEMA_VIOLATION};
+use webhook_gateway::utils::compact_json;
+
+fn flow_payload(response_json: &str) -> Value {
+    json!({
+        "id": "flow-1",
+        "data": {
+            "entry": [{
+                "changes": [{
+                    "value": {
+                        "metadata": { "phone_number_id": "115159954803011" },
+                        "messages": [{
+                            "from": "6282228223500",
+                            "type": "interactive",
+                            "interactive": {
+                                "type": "nfm_reply",
+                                "nfm_reply": { "name": "flow", "body": "Sent", "response_json": response_json }
+                            }
+                        }]
+                    }
+                }]
+            }]
+        }
+    })
+}
+
+fn nfm_reply(payload: &Value) -> &Value {
+    &payload["data"]["entry"][0]["changes"][0]["value"]["messages"][0]["interactive"]["nfm_reply"]
+}
+
+fn survey_config(decode: FlowReplyDecodeMode) -> FlowReplyConfig {
+    let mut schemas = HashMap::new();
+    schemas.insert(
+        "survey-token".to_string(),
+        FlowSchemaConfig {
+            required: vec!["rating".to_string()],
+            properties: HashMap::from([
+                ("rating".to_string(), FlowFieldType::String),
+                ("comment".to_string(), FlowFieldType::String),
+                ("AccountNo".to_string(), FlowFieldType::Number),
+            ]),
+            deny_unknown: true,
+        },
+    );
+    FlowReplyConfig {
+        decode,
+        schemas,
+        ..FlowReplyConfig::default()
+    }
+}
+
+#[test]
+fn test_replace_and_sibling_modes() {
+    let response = r#"{"flow_token":"survey-token","rating":"5","comment":"ok"}"#;
+
+    let mut replaced = flow_payload(response);
+    let (decoded, errors) = FlowReplyDecoder::new(survey_config(FlowReplyDecodeMode::Replace)).decode(&mut replaced);
+    assert_eq!(decoded, 1);
+    assert!(errors.is_empty());
+    assert_eq!(nfm_reply(&replaced)["response_json"]["rating"], "5");
+
+    let mut sibling = flow_payload(response);
+    let (decoded, _) = FlowReplyDecoder::new(survey_config(FlowReplyDecodeMode::Sibling)).decode(&mut sibling);
+    assert_eq!(decoded, 1);
+    assert_eq!(nfm_reply(&sibling)["response_json"], response);
+    assert_eq!(nfm_reply(&sibling)["response_data"]["comment"], "ok");
+}
+
+#[test]
+fn test_malformed_and_schema_violation_are_reported() {
+    let decoder = FlowReplyDecoder::new(survey_config(FlowReplyDecodeMode::Replace));
+
+    let mut malformed = flow_payload(r#"{"rating": "5""#);
+    let (decoded, errors) = decoder.decode(&mut malformed);
+    assert_eq!(decoded, 0);
+    assert_eq!(errors[0].code, MALFORMED_RESPONSE_JSON);
+    let reply = nfm_reply(&malformed);
+    assert_eq!(reply["response_json"], r#"{"rating": "5""#);
+    assert_eq!(reply["decode_error"]["code"], MALFORMED_RESPONSE_JSON);
+
+    let mut invalid = flow_payload(r#"{"flow_token":"survey-token","rating":5,"AccountNo":"123","extra":true}"#);
+    let (_, errors) = decoder.decode(&mut invalid);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].code, SCHEMA_VIOLATION);
+    assert!(errors[0].message.contains("field 'AccountNo' must be number"));
+    assert_eq!(errors[0].flow_token.as_deref(), Some("survey-token"));
+    assert!(errors[0].message.contains("field 'rating' must be string"));
+    assert!(errors[0].message.contains("unknown field 'extra'"));
+    assert_eq!(nfm_reply(&invalid)["decode_error"]["code"], SCHEMA_VIOLATION);
+    // Reply tetap di-decode supaya Permata bisa melihat isinya
+    assert_eq!(nfm_reply(&invalid)["response_json"]["rating"], 5);
+}
+
+#[test]
+fn test_apply_rewrites_body_and_signature_input() {
+    let body = flow_payload(r#"{"flow_token":"other-flow","answer":"yes"}"#).to_string();
+    let webhook = InboundWebhook::new(HashMap::new(), Bytes::from(body.clone()));
+
+    let untouched = FlowReplyDecoder::new(FlowReplyConfig::default()).apply(webhook.clone());
+    assert_eq!(untouched.body_str(), body);
+
+    let decoded = FlowReplyDecoder::new(survey_config(FlowReplyDecodeMode::Sibling)).apply(webhook);
+    assert_eq!(decoded.request_id, "req-flow-1");
+    assert_eq!(decoded.tenant.as_deref(), Some("115159954803011"));
+    assert_eq!(decoded.compact_body, compact_json(&decoded.body_str()).unwrap());
+    // Urutan field dari Meta dipertahankan, hanya reply yang di-decode berubah
+    assert!(decoded.body_str().starts_with(r#"{"id":"flow-1","data":"#));
+    let json: Value = serde_json::from_str(&decoded.body_str()).unwrap();
+    assert_eq!(nfm_reply(&json)["response_data"]["answer"], "yes");
+}
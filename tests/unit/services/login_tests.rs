@@ -1,4 +1,4 @@
-use webhook_gateway::config::{AppConfig, PermataBankLoginConfig, WebClientConfig, ServerConfig, PermataBankWebhookConfig, SchedulerConfig, TelegramAlertConfig, LoggerConfig};
+use webhook_gateway::config::{AppConfig, PermataBankLoginConfig, WebClientConfig, ServerConfig, PermataBankWebhookConfig, SchedulerConfig, TelegramAlertConfig, LoggerConfig, AdminConfig, AlertingConfig, AlertTemplateConfig, LogWriterConfig, AuditConfig, AccessLogConfig, OtlpConfig, FlowReplyConfig, FanOutConfig, StatusOrderConfig, StatusCoalesceConfig, DispatchConfig, BackpressureConfig, IngressConfig, OutboundConfig, HttpClientConfig, PermataResponseConfig, MetaResponseConfig, TlsConfig};
 use webhook_gateway::services::LoginHandler;
 use mockito::Server;
 use serde_json::json;
@@ -10,6 +10,7 @@ fn create_test_config(mock_server_url: &str) -> AppConfig {
             listen_host: "127.0.0.1".to_string(),
             listen_port: 8080,
             webhook_path: "/webhook".to_string(),
+            tls: TlsConfig::default(),
         },
         webclient: WebClientConfig {
             timeout: 30,
@@ -45,7 +46,26 @@ fn create_test_config(mock_server_url: &str) -> AppConfig {
             max_age: 90,
             compress: true,
             local_time: true,
+            level: "info".to_string(),
+            writer: LogWriterConfig::default(),
         },
+        admin: AdminConfig::default(),
+        alerting: AlertingConfig::default(),
+        alert_template: AlertTemplateConfig::default(),
+        audit: AuditConfig::default(),
+        access_log: AccessLogConfig::default(),
+        otlp: OtlpConfig::default(),
+        flow_reply: FlowReplyConfig::default(),
+        fan_out: FanOutConfig::default(),
+        status_order: StatusOrderConfig::default(),
+        status_coalesce: StatusCoalesceConfig::default(),
+        dispatch: DispatchConfig::default(),
+        backpressure: BackpressureConfig::default(),
+        ingress: IngressConfig::default(),
+        outbound: OutboundConfig::default(),
+        http_client: HttpClientConfig::default(),
+        permata_response: PermataResponseConfig::default(),
+        meta_response: MetaResponseConfig::default(),
     }
 }
 
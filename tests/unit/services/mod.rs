@@ -6,6 +6,18 @@ pub mod token_scheduler_periodic_debug;
 pub mod webhook_processor;
 pub mod telegram_alert_tests;
 pub mod permata_callbackstatus_client_tests;
+pub mod alert_dispatcher_tests;
+pub mod alert_incident_tests;
+pub mod alert_rules_tests;
+pub mod alert_template_tests;
+pub mod flow_reply_tests;
+pub mod fan_out_tests;
+pub mod status_order_tests;
+pub mod status_coalescer_tests;
+pub mod partitioned_dispatcher_tests;
+pub mod backpressure_tests;
+pub mod response_classifier_tests;
+pub mod response_policy_tests;
 
 // Unit tests for services
 // This module provides comprehensive unit testing for all service components
\ No newline at end of file
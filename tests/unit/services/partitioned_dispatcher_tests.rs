@@ -0,0 +1,156 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::time::{sleep, Duration};
+use tracing::Instrument;
+
+use webhook_gateway::config::{DispatchConfig, ResponseOutcome};
+use webhook_gateway::models::InboundWebhook;
+use webhook_gateway::services::partitioned_dispatcher::ordering_key;
+use webhook_gateway::services::webhook_processor::WebhookResponse;
+use webhook_gateway::services::{PartitionedDispatcher, WebhookProcessorTrait};
+use webhook_gateway::providers::StructuredLogger;
+use webhook_gateway::utils::error::{AppError, Result};
+
+/// Records message ids in completion order, `slow` messages take longer
+#[derive(Default)]
+struct SlowProcessor {
+    completed: Mutex<Vec<String>>,
+}
+
+#[async_trait]
+impl WebhookProcessorTrait for SlowProcessor {
+    async fn process_webhook(&self, webhook: &InboundWebhook) -> Result<WebhookResponse> {
+        let json: Value = serde_json::from_str(&webhook.body_str()).unwrap();
+        let message = &json["entry"][0]["changes"][0]["value"]["messages"][0];
+        let id = message["id"].as_str().unwrap().to_string();
+        if id.starts_with("slow") {
+            sleep(Duration::from_millis(200)).await;
+        }
+        self.completed.lock().unwrap().push(id);
+        Ok(WebhookResponse { http_status: 200, body: "{}".to_string(), outcome: ResponseOutcome::Success })
+    }
+}
+
+fn user_message(from: &str, id: &str) -> InboundWebhook {
+    let payload = json!({
+        "xid": id,
+        "entry": [{ "changes": [{ "value": {
+            "contacts": [{ "wa_id": from }],
+            "messages": [{ "from": from, "id": id, "type": "text", "text": { "body": "halo" } }]
+        } }] }]
+    });
+    InboundWebhook::new(HashMap::new(), Bytes::from(payload.to_string()))
+}
+
+fn config(partitions: usize, partition_backlog: usize) -> DispatchConfig {
+    DispatchConfig { enabled: true, partitions, partition_backlog, shutdown_deadline_secs: 30 }
+}
+
+#[tokio::test]
+async fn test_same_customer_is_sequential_others_parallel() {
+    let processor = Arc::new(SlowProcessor::default());
+    let dispatcher = Arc::new(PartitionedDispatcher::start(&config(16, 10), processor.clone()));
+
+    let first = user_message("6281111111111", "slow-1");
+    let second = user_message("6281111111111", "fast-2");
+    assert_eq!(ordering_key(&first), "6281111111111");
+    assert_eq!(dispatcher.partition_of(&first), dispatcher.partition_of(&second));
+
+    // Cari customer lain yang jatuh ke partisi berbeda
+    let other = (0..100)
+        .map(|n| user_message(&format!("62822{:08}", n), "fast-other"))
+        .find(|webhook| dispatcher.partition_of(webhook) != dispatcher.partition_of(&first))
+        .unwrap();
+
+    let mut handles = Vec::new();
+    for webhook in [first, second, other] {
+        let dispatcher = dispatcher.clone();
+        handles.push(tokio::spawn(async move { dispatcher.process_webhook(&webhook).await }));
+        sleep(Duration::from_millis(10)).await;
+    }
+    for handle in handles {
+        assert_eq!(handle.await.unwrap().unwrap().http_status, 200);
+    }
+
+    let completed = processor.completed.lock().unwrap().clone();
+    assert_eq!(completed, vec!["fast-other", "slow-1", "fast-2"]);
+}
+
+#[tokio::test]
+async fn test_full_partition_backlog_is_rejected() {
+    let processor = Arc::new(SlowProcessor::default());
+    let dispatcher = Arc::new(PartitionedDispatcher::start(&config(1, 1), processor));
+
+    let running = {
+        let dispatcher = dispatcher.clone();
+        tokio::spawn(async move { dispatcher.process_webhook(&user_message("628", "slow-1")).await })
+    };
+    sleep(Duration::from_millis(20)).await;
+    let queued = {
+        let dispatcher = dispatcher.clone();
+        tokio::spawn(async move { dispatcher.process_webhook(&user_message("628", "slow-2")).await })
+    };
+    sleep(Duration::from_millis(20)).await;
+
+    let rejected = dispatcher.process_webhook(&user_message("628", "fast-3")).await;
+    assert!(matches!(rejected, Err(AppError::Overloaded { .. })));
+
+    assert!(running.await.unwrap().is_ok());
+    assert!(queued.await.unwrap().is_ok());
+}
+
+#[tokio::test]
+async fn test_shutdown_forwards_queued_webhooks() {
+    let processor = Arc::new(SlowProcessor::default());
+    let dispatcher = Arc::new(PartitionedDispatcher::start(&config(1, 10), processor.clone()));
+
+    let mut handles = Vec::new();
+    for id in ["slow-1", "slow-2", "fast-3"] {
+        let dispatcher = dispatcher.clone();
+        handles.push(tokio::spawn(async move { dispatcher.process_webhook(&user_message("628", id)).await }));
+    }
+    sleep(Duration::from_millis(20)).await;
+
+    assert!(dispatcher.shutdown(Duration::from_secs(5)).await);
+    assert_eq!(processor.completed.lock().unwrap().clone(), vec!["slow-1", "slow-2", "fast-3"]);
+    for handle in handles {
+        assert!(handle.await.unwrap().is_ok());
+    }
+
+    // Setelah shutdown webhook baru ditolak
+    let rejected = dispatcher.process_webhook(&user_message("628", "fast-4")).await;
+    assert!(matches!(rejected, Err(AppError::Overloaded { .. })));
+}
+
+/// Records the span the processor runs in
+#[derive(Default)]
+struct SpanProcessor {
+    spans: Mutex<Vec<Option<&'static str>>>,
+}
+
+#[async_trait]
+impl WebhookProcessorTrait for SpanProcessor {
+    async fn process_webhook(&self, _webhook: &InboundWebhook) -> Result<WebhookResponse> {
+        self.spans.lock().unwrap().push(tracing::Span::current().metadata().map(|metadata| metadata.name()));
+        Ok(WebhookResponse { http_status: 200, body: "{}".to_string(), outcome: ResponseOutcome::Success })
+    }
+}
+
+#[tokio::test]
+async fn test_worker_runs_in_request_span() {
+    let _default = tracing::subscriber::set_default(tracing_subscriber::registry());
+    let processor = Arc::new(SpanProcessor::default());
+    let dispatcher = PartitionedDispatcher::start(&config(2, 10), processor.clone());
+
+    let span = StructuredLogger::request_span("req-partitioned");
+    dispatcher
+        .process_webhook(&user_message("6281111111111", "span-1"))
+        .instrument(span)
+        .await
+        .unwrap();
+
+    assert_eq!(processor.spans.lock().unwrap().clone(), vec![Some("request")]);
+}
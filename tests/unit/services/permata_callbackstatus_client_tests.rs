@@ -2,7 +2,8 @@ use mockito::Server;
 use serde_json::json;
 use tokio::time::{timeout, Duration};
 
-use webhook_gateway::config::{AppConfig, PermataBankLoginConfig, PermataBankWebhookConfig, WebClientConfig, TelegramAlertConfig, SchedulerConfig, LoggerConfig};
+use webhook_gateway::config::{AppConfig, PermataBankLoginConfig, PermataBankWebhookConfig, WebClientConfig, TelegramAlertConfig, SchedulerConfig, LoggerConfig, AdminConfig, AlertingConfig, AlertTemplateConfig, LogWriterConfig, AuditConfig, AccessLogConfig, OtlpConfig, FlowReplyConfig, FanOutConfig, StatusOrderConfig, StatusCoalesceConfig, DispatchConfig, BackpressureConfig, IngressConfig, OutboundConfig, HttpClientConfig, PermataResponseConfig, MetaResponseConfig, TlsConfig};
+use webhook_gateway::config::ResponseOutcome;
 use webhook_gateway::services::PermataCallbackStatusClient;
 
 fn create_test_config(mock_server_url: &str) -> AppConfig {
@@ -11,6 +12,7 @@ fn create_test_config(mock_server_url: &str) -> AppConfig {
             listen_host: "127.0.0.1".to_string(),
             listen_port: 8080,
             webhook_path: "/webhook".to_string(),
+            tls: TlsConfig::default(),
         },
         permata_bank_login: PermataBankLoginConfig {
             username: "test_user".to_string(),
@@ -46,7 +48,26 @@ fn create_test_config(mock_server_url: &str) -> AppConfig {
             max_age: 90,
             compress: true,
             local_time: true,
+            level: "info".to_string(),
+            writer: LogWriterConfig::default(),
         },
+        admin: AdminConfig::default(),
+        alerting: AlertingConfig::default(),
+        alert_template: AlertTemplateConfig::default(),
+        audit: AuditConfig::default(),
+        access_log: AccessLogConfig::default(),
+        otlp: OtlpConfig::default(),
+        flow_reply: FlowReplyConfig::default(),
+        fan_out: FanOutConfig::default(),
+        status_order: StatusOrderConfig::default(),
+        status_coalesce: StatusCoalesceConfig::default(),
+        dispatch: DispatchConfig::default(),
+        backpressure: BackpressureConfig::default(),
+        ingress: IngressConfig::default(),
+        outbound: OutboundConfig::default(),
+        http_client: HttpClientConfig::default(),
+        permata_response: PermataResponseConfig::default(),
+        meta_response: MetaResponseConfig::default(),
     }
 }
 
@@ -177,8 +198,9 @@ async fn test_send_webhook_http_error() {
         }).to_string())
         .create_async().await;
     
-    // Mock callback endpoint to return error
+    // HTTP 500 bisa di-retry, callback dipanggil sebanyak max_retries
     let callback_mock = server.mock("POST", "/callback")
+        .expect(3)
         .with_status(500)
         .with_header("content-type", "application/json")
         .with_body(json!({
@@ -202,6 +224,7 @@ async fn test_send_webhook_http_error() {
     let response = result.unwrap();
     assert_eq!(response.status_code, 500);
     assert!(response.body.contains("Internal Server Error"));
+    assert_eq!(response.outcome, ResponseOutcome::RetryableFailure);
     
     callback_mock.assert_async().await;
     
@@ -383,4 +406,66 @@ async fn test_send_webhook_malformed_json() {
             // The error comes from the compact_json utility function
         }
     }
-}
\ No newline at end of file
+}
+
+async fn mock_token(server: &mut Server) -> mockito::Mock {
+    server.mock("POST", "/token")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(json!({
+            "access_token": "test_token_status",
+            "token_type": "Bearer",
+            "expires_in": 3600,
+            "scope": "api"
+        }).to_string())
+        .create_async().await
+}
+
+#[tokio::test]
+async fn test_http_200_with_error_status_code_is_not_success() {
+    let mut server = Server::new_async().await;
+    let _token_mock = mock_token(&mut server).await;
+
+    // StatusCode 06 dalam HTTP 200 adalah kegagalan permanen, tidak di-retry
+    let callback_mock = server.mock("POST", "/callback")
+        .expect(1)
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(json!({ "StatusCode": "06", "StatusDesc": "General Error" }).to_string())
+        .create_async().await;
+
+    let client = PermataCallbackStatusClient::new(create_test_config(&server.url())).unwrap();
+    let response = client.send_webhook(r#"{"id":"status_06"}"#, "req-status-06").await.unwrap();
+
+    assert_eq!(response.status_code, 200);
+    assert_eq!(response.outcome, ResponseOutcome::PermanentFailure);
+    assert!(!response.is_success());
+    callback_mock.assert_async().await;
+
+    client.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_retryable_status_code_from_config() {
+    let mut server = Server::new_async().await;
+    let _token_mock = mock_token(&mut server).await;
+
+    let callback_mock = server.mock("POST", "/callback")
+        .expect(3)
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(json!({ "StatusCode": "05", "StatusDesc": "System Busy" }).to_string())
+        .create_async().await;
+
+    let mut config = create_test_config(&server.url());
+    config.webclient.retry_delay = 0;
+    config.permata_response.status_codes.insert("05".to_string(), ResponseOutcome::RetryableFailure);
+
+    let client = PermataCallbackStatusClient::new(config).unwrap();
+    let response = client.send_webhook(r#"{"id":"status_05"}"#, "req-status-05").await.unwrap();
+
+    assert_eq!(response.outcome, ResponseOutcome::RetryableFailure);
+    callback_mock.assert_async().await;
+
+    client.shutdown().await;
+}
@@ -0,0 +1,35 @@
+use webhook_gateway::config::{PermataResponseConfig, ResponseOutcome};
+use webhook_gateway::services::ResponseClassifier;
+
+#[test]
+fn test_status_code_wins_over_http_status() {
+    let classifier = ResponseClassifier::new(PermataResponseConfig::default());
+
+    let ok = classifier.classify(200, r#"{"StatusCode":"00","StatusDesc":"Success"}"#);
+    assert!(ok.is_success());
+    assert_eq!(ok.status_code.as_deref(), Some("00"));
+
+    // HTTP 200 dengan StatusCode error bukan sukses
+    let rejected = classifier.classify(200, r#"{"StatusCode":"06","StatusDesc":"General Error"}"#);
+    assert_eq!(rejected.outcome, ResponseOutcome::PermanentFailure);
+    assert_eq!(rejected.status_desc.as_deref(), Some("General Error"));
+
+    assert_eq!(classifier.classify(200, "").outcome, ResponseOutcome::Success);
+    assert_eq!(classifier.classify(503, "<html>busy</html>").outcome, ResponseOutcome::RetryableFailure);
+    assert_eq!(classifier.classify(500, r#"{"StatusCode":"99"}"#).outcome, ResponseOutcome::RetryableFailure);
+    assert_eq!(classifier.classify(400, r#"{"StatusCode":"99"}"#).outcome, ResponseOutcome::PermanentFailure);
+}
+
+#[test]
+fn test_configured_status_codes() {
+    let mut config = PermataResponseConfig::default();
+    config.status_codes.insert("05".to_string(), ResponseOutcome::RetryableFailure);
+    config.status_codes.insert("14".to_string(), ResponseOutcome::Success);
+    config.missing_status_code = ResponseOutcome::PermanentFailure;
+    let classifier = ResponseClassifier::new(config);
+
+    assert_eq!(classifier.classify(200, r#"{"StatusCode":"05"}"#).outcome, ResponseOutcome::RetryableFailure);
+    // Kode yang dipetakan berlaku juga untuk HTTP non-2xx
+    assert_eq!(classifier.classify(409, r#"{"StatusCode":"14","StatusDesc":"Duplicate"}"#).outcome, ResponseOutcome::Success);
+    assert_eq!(classifier.classify(200, "not json").outcome, ResponseOutcome::PermanentFailure);
+}
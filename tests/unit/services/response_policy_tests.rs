@@ -0,0 +1,134 @@
+use bytes::Bytes;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use tempfile::TempDir;
+
+use webhook_gateway::config::{AppConfig, MetaResponseMode, MetaResponseRule, ResponseOutcome};
+use webhook_gateway::models::InboundWebhook;
+use webhook_gateway::providers::DeadLetterLog;
+use webhook_gateway::services::webhook_processor::WebhookResponse;
+use webhook_gateway::services::ResponsePolicy;
+use webhook_gateway::utils::error::AppError;
+
+fn create_test_config(mode: MetaResponseMode, log_dir: &str) -> AppConfig {
+    let mut config: AppConfig = serde_yaml::from_str(include_str!("../../../config.yaml.example")).unwrap();
+    config.logger.dir = log_dir.to_string();
+    config.meta_response.mode = mode;
+    config
+}
+
+fn test_webhook() -> InboundWebhook {
+    let payload = json!({ "xid": "policy-1", "entry": [] });
+    InboundWebhook::new(HashMap::new(), Bytes::from(payload.to_string()))
+}
+
+fn permata(http_status: u16, body: &str, outcome: ResponseOutcome) -> webhook_gateway::utils::error::Result<WebhookResponse> {
+    Ok(WebhookResponse {
+        http_status,
+        body: body.to_string(),
+        outcome,
+    })
+}
+
+fn dead_letters(config: &AppConfig) -> Vec<Value> {
+    let path = DeadLetterLog::with_file(config, &config.meta_response.dead_letter_file).unwrap().current_path();
+    std::fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_passthrough_mirrors_permata() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = create_test_config(MetaResponseMode::Passthrough, temp_dir.path().to_str().unwrap());
+    let policy = ResponsePolicy::new(&config).unwrap();
+
+    let reply = policy.respond(&test_webhook(), &permata(502, "<html>Bad Gateway</html>", ResponseOutcome::RetryableFailure));
+    assert_eq!(reply.outcome, "forwarded");
+    assert_eq!(reply.http_status, 502);
+    assert_eq!(reply.body, json!({ "StatusCode": "06", "StatusDesc": "<html>Bad Gateway</html>" }));
+
+    let reply = policy.respond(&test_webhook(), &Err(AppError::error("connection refused")));
+    assert_eq!(reply.outcome, "error");
+    assert_eq!(reply.http_status, 500);
+    assert!(dead_letters(&config).is_empty());
+}
+
+#[test]
+fn test_always_ok_acknowledges_after_dead_letter() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = create_test_config(MetaResponseMode::AlwaysOk, temp_dir.path().to_str().unwrap());
+    let policy = ResponsePolicy::new(&config).unwrap();
+
+    let ok = r#"{"StatusCode":"00","StatusDesc":"Success"}"#;
+    let reply = policy.respond(&test_webhook(), &permata(200, ok, ResponseOutcome::Success));
+    assert_eq!(reply.outcome, "forwarded");
+    assert_eq!(reply.body["StatusDesc"], "Success");
+
+    let rejected = r#"{"StatusCode":"06","StatusDesc":"General Error"}"#;
+    let reply = policy.respond(&test_webhook(), &permata(200, rejected, ResponseOutcome::PermanentFailure));
+    assert_eq!(reply.outcome, "accepted");
+    assert_eq!(reply.http_status, 200);
+    assert_eq!(reply.body["StatusDesc"], "Accepted");
+
+    let reply = policy.respond(&test_webhook(), &Err(AppError::error("connection refused")));
+    assert_eq!(reply.http_status, 200);
+
+    let letters = dead_letters(&config);
+    assert_eq!(letters.len(), 2);
+    assert_eq!(letters[0]["status_code"], 200);
+    assert_eq!(letters[0]["payload"]["xid"], "policy-1");
+    assert_eq!(letters[1]["status_code"], Value::Null);
+}
+
+#[test]
+fn test_failure_passes_through_without_dead_letter_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut config = create_test_config(MetaResponseMode::AlwaysOk, temp_dir.path().to_str().unwrap());
+    config.meta_response.dead_letter_file = String::new();
+    let policy = ResponsePolicy::new(&config).unwrap();
+
+    let reply = policy.respond(&test_webhook(), &permata(503, "busy", ResponseOutcome::RetryableFailure));
+    assert_eq!(reply.outcome, "forwarded");
+    assert_eq!(reply.http_status, 503);
+}
+
+#[test]
+fn test_overloaded_keeps_retry_after() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = create_test_config(MetaResponseMode::AlwaysOk, temp_dir.path().to_str().unwrap());
+    let policy = ResponsePolicy::new(&config).unwrap();
+
+    let reply = policy.respond(&test_webhook(), &Err(AppError::overloaded("partition queue full")));
+    assert_eq!(reply.http_status, 503);
+    assert!(reply.retry_after);
+    assert!(dead_letters(&config).is_empty());
+}
+
+#[test]
+fn test_mapped_outcomes() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut config = create_test_config(MetaResponseMode::Mapped, temp_dir.path().to_str().unwrap());
+    config.meta_response.mappings.permanent_failure = None;
+    config.meta_response.mappings.error = Some(MetaResponseRule { status: 502, body: None });
+    let policy = ResponsePolicy::new(&config).unwrap();
+
+    let reply = policy.respond(&test_webhook(), &permata(500, r#"{"StatusCode":"05"}"#, ResponseOutcome::RetryableFailure));
+    assert_eq!(reply.outcome, "accepted");
+    assert_eq!(reply.http_status, 200);
+    assert_eq!(reply.body, json!({ "StatusCode": "00", "StatusDesc": "Accepted" }));
+
+    // Tanpa mapping, respon Permata diteruskan apa adanya
+    let reply = policy.respond(&test_webhook(), &permata(400, r#"{"StatusCode":"14"}"#, ResponseOutcome::PermanentFailure));
+    assert_eq!(reply.http_status, 400);
+    assert_eq!(reply.body["StatusCode"], "14");
+
+    let reply = policy.respond(&test_webhook(), &Err(AppError::error("login failed")));
+    assert_eq!(reply.outcome, "error");
+    assert_eq!(reply.http_status, 502);
+    assert_eq!(reply.body["StatusDesc"], "login failed");
+
+    assert_eq!(dead_letters(&config).len(), 1);
+}
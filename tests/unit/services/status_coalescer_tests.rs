@@ -0,0 +1,142 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::time::{sleep, Duration};
+
+use tempfile::TempDir;
+
+use webhook_gateway::config::{LogWriterConfig, LoggerConfig, ResponseOutcome, StatusCoalesceConfig};
+use webhook_gateway::models::InboundWebhook;
+use webhook_gateway::providers::DeadLetterLog;
+use webhook_gateway::services::status_coalescer::CoalesceDecision;
+use webhook_gateway::services::webhook_processor::WebhookResponse;
+use webhook_gateway::services::{StatusCoalescer, WebhookProcessorTrait};
+use webhook_gateway::utils::error::Result;
+
+#[derive(Default)]
+struct RecordingProcessor {
+    forwarded: Mutex<Vec<String>>,
+    /// Answer every callback like Permata rejecting it
+    failing: bool,
+}
+
+#[async_trait]
+impl WebhookProcessorTrait for RecordingProcessor {
+    async fn process_webhook(&self, webhook: &InboundWebhook) -> Result<WebhookResponse> {
+        let json: Value = serde_json::from_str(&webhook.body_str()).unwrap();
+        let status = json["entry"][0]["changes"][0]["value"]["statuses"][0]["status"].as_str().unwrap_or_default();
+        self.forwarded.lock().unwrap().push(status.to_string());
+        if self.failing {
+            return Ok(WebhookResponse {
+                http_status: 200,
+                body: r#"{"StatusCode":"06","StatusDesc":"General Error"}"#.to_string(),
+                outcome: ResponseOutcome::PermanentFailure,
+            });
+        }
+        Ok(WebhookResponse { http_status: 200, body: "{}".to_string(), outcome: ResponseOutcome::Success })
+    }
+}
+
+fn receipt(message_id: &str, status: &str) -> InboundWebhook {
+    let payload = json!({
+        "xid": format!("{}-{}", message_id, status),
+        "entry": [{ "changes": [{ "value": { "statuses": [{ "id": message_id, "status": status }] } }] }]
+    });
+    InboundWebhook::new(HashMap::new(), Bytes::from(payload.to_string()))
+}
+
+fn dead_letter_log(dir: &TempDir) -> DeadLetterLog {
+    let logger = LoggerConfig {
+        dir: dir.path().to_str().unwrap().to_string(),
+        file_name: "test".to_string(),
+        max_backups: 0,
+        max_size: 10,
+        max_age: 0,
+        compress: false,
+        local_time: false,
+        level: "info".to_string(),
+        writer: LogWriterConfig::default(),
+    };
+    DeadLetterLog::open(&logger, "status-coalesce-undelivered").unwrap()
+}
+
+fn coalescer_with(
+    processor: RecordingProcessor,
+    hold_window_ms: u64,
+    dead_letter: Option<DeadLetterLog>,
+) -> (Arc<StatusCoalescer>, Arc<RecordingProcessor>) {
+    let processor = Arc::new(processor);
+    let config = StatusCoalesceConfig {
+        enabled: true,
+        hold_window_ms,
+        ..StatusCoalesceConfig::default()
+    };
+    (Arc::new(StatusCoalescer::new(config, processor.clone(), dead_letter)), processor)
+}
+
+fn coalescer(hold_window_ms: u64, dir: &TempDir) -> (Arc<StatusCoalescer>, Arc<RecordingProcessor>) {
+    coalescer_with(RecordingProcessor::default(), hold_window_ms, Some(dead_letter_log(dir)))
+}
+
+#[tokio::test]
+async fn test_only_latest_status_is_forwarded() {
+    let dir = TempDir::new().unwrap();
+    let (coalescer, processor) = coalescer(100, &dir);
+
+    for status in ["sent", "read", "delivered"] {
+        assert!(matches!(coalescer.offer(receipt("wamid.1", status)), CoalesceDecision::Held(_)));
+    }
+    assert_eq!(coalescer.pending(), 1);
+    assert!(processor.forwarded.lock().unwrap().is_empty());
+
+    sleep(Duration::from_millis(300)).await;
+    assert_eq!(*processor.forwarded.lock().unwrap(), vec!["read".to_string()]);
+    assert_eq!(coalescer.pending(), 0);
+}
+
+#[tokio::test]
+async fn test_failed_is_never_delayed() {
+    let dir = TempDir::new().unwrap();
+    let (coalescer, processor) = coalescer(60_000, &dir);
+
+    assert!(matches!(coalescer.offer(receipt("wamid.2", "sent")), CoalesceDecision::Held(_)));
+    assert!(matches!(coalescer.offer(receipt("wamid.2", "failed")), CoalesceDecision::Forward(_)));
+    // Status sent yang ditahan dibuang karena failed sudah final
+    assert_eq!(coalescer.pending(), 0);
+
+    assert!(matches!(coalescer.offer(receipt("wamid.3", "delivered")), CoalesceDecision::Held(_)));
+    coalescer.flush().await;
+    assert_eq!(*processor.forwarded.lock().unwrap(), vec!["delivered".to_string()]);
+}
+
+#[tokio::test]
+async fn test_undelivered_held_receipt_is_dead_lettered() {
+    let dir = TempDir::new().unwrap();
+    let dead_letter = dead_letter_log(&dir);
+    let path = dead_letter.current_path();
+    let failing = RecordingProcessor { failing: true, ..RecordingProcessor::default() };
+    let (coalescer, _processor) = coalescer_with(failing, 60_000, Some(dead_letter));
+
+    assert!(matches!(coalescer.offer(receipt("wamid.4", "delivered")), CoalesceDecision::Held(_)));
+    coalescer.flush().await;
+
+    let letters: Vec<Value> = std::fs::read_to_string(path)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(letters.len(), 1);
+    assert_eq!(letters[0]["event_key"], "wamid.4");
+    assert_eq!(letters[0]["status_code"], 200);
+    assert_eq!(letters[0]["payload"]["entry"][0]["changes"][0]["value"]["statuses"][0]["status"], "delivered");
+}
+
+#[tokio::test]
+async fn test_nothing_is_held_without_dead_letter() {
+    let (coalescer, _processor) = coalescer_with(RecordingProcessor::default(), 60_000, None);
+
+    assert!(matches!(coalescer.offer(receipt("wamid.5", "sent")), CoalesceDecision::Forward(_)));
+    assert_eq!(coalescer.pending(), 0);
+}
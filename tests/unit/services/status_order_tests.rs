@@ -0,0 +1,74 @@
+use bytes::Bytes;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use webhook_gateway::config::{StatusOrderConfig, StatusRegressionMode};
+use webhook_gateway::models::InboundWebhook;
+use webhook_gateway::services::status_order::StatusCheck;
+use webhook_gateway::services::{GuardedWebhook, StatusOrderGuard};
+
+fn guard(mode: StatusRegressionMode) -> StatusOrderGuard {
+    StatusOrderGuard::new(StatusOrderConfig {
+        enabled: true,
+        mode,
+        ..StatusOrderConfig::default()
+    })
+}
+
+fn receipt(statuses: &[(&str, &str)]) -> InboundWebhook {
+    let statuses: Vec<Value> = statuses
+        .iter()
+        .map(|(id, status)| json!({ "id": id, "status": status, "recipient_id": "6282228223500" }))
+        .collect();
+    let payload = json!({
+        "xid": "dr-1",
+        "entry": [{ "changes": [{ "value": { "statuses": statuses } }] }]
+    });
+    InboundWebhook::new(HashMap::new(), Bytes::from(payload.to_string()))
+}
+
+fn forwarded_statuses(guarded: GuardedWebhook) -> Vec<Value> {
+    let GuardedWebhook::Forward(webhook) = guarded else {
+        panic!("expected the webhook to be forwarded");
+    };
+    let json: Value = serde_json::from_str(&webhook.body_str()).unwrap();
+    json["entry"][0]["changes"][0]["value"]["statuses"].as_array().unwrap().clone()
+}
+
+#[test]
+fn test_check_tracks_highest_status_per_message() {
+    let guard = guard(StatusRegressionMode::Tag);
+
+    assert_eq!(guard.check("wamid.1", "sent"), StatusCheck::Forward);
+    assert_eq!(guard.check("wamid.1", "read"), StatusCheck::Forward);
+    assert_eq!(guard.check("wamid.1", "delivered"), StatusCheck::Regression { highest: "read" });
+    // Status yang sama dan status di luar urutan tidak dianggap mundur
+    assert_eq!(guard.check("wamid.1", "read"), StatusCheck::Forward);
+    assert_eq!(guard.check("wamid.1", "failed"), StatusCheck::Forward);
+    assert_eq!(guard.check("wamid.2", "delivered"), StatusCheck::Forward);
+}
+
+#[test]
+fn test_tag_mode_flags_regressed_status() {
+    let guard = guard(StatusRegressionMode::Tag);
+    forwarded_statuses(guard.apply(receipt(&[("wamid.1", "read")])));
+
+    let statuses = forwarded_statuses(guard.apply(receipt(&[("wamid.1", "delivered"), ("wamid.2", "sent")])));
+    assert_eq!(statuses.len(), 2);
+    assert_eq!(statuses[0]["status_regression"], true);
+    assert!(statuses[1].get("status_regression").is_none());
+}
+
+#[test]
+fn test_suppress_mode_drops_regressions() {
+    let guard = guard(StatusRegressionMode::Suppress);
+    forwarded_statuses(guard.apply(receipt(&[("wamid.1", "read")])));
+
+    let statuses = forwarded_statuses(guard.apply(receipt(&[("wamid.1", "delivered"), ("wamid.2", "sent")])));
+    assert_eq!(statuses.len(), 1);
+    assert_eq!(statuses[0]["id"], "wamid.2");
+
+    assert!(matches!(
+        guard.apply(receipt(&[("wamid.1", "sent")])),
+        GuardedWebhook::Suppressed(_)
+    ));
+}
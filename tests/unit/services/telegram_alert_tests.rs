@@ -7,6 +7,7 @@ fn create_test_config() -> AppConfig {
             listen_host: "127.0.0.1".to_string(),
             listen_port: 8080,
             webhook_path: "/webhook".to_string(),
+            tls: TlsConfig::default(),
         },
         webclient: WebClientConfig {
             timeout: 30,
@@ -42,7 +43,26 @@ fn create_test_config() -> AppConfig {
             max_age: 90,
             compress: true,
             local_time: true,
+            level: "info".to_string(),
+            writer: LogWriterConfig::default(),
         },
+        admin: AdminConfig::default(),
+        alerting: AlertingConfig::default(),
+        alert_template: AlertTemplateConfig::default(),
+        audit: AuditConfig::default(),
+        access_log: AccessLogConfig::default(),
+        otlp: OtlpConfig::default(),
+        flow_reply: FlowReplyConfig::default(),
+        fan_out: FanOutConfig::default(),
+        status_order: StatusOrderConfig::default(),
+        status_coalesce: StatusCoalesceConfig::default(),
+        dispatch: DispatchConfig::default(),
+        backpressure: BackpressureConfig::default(),
+        ingress: IngressConfig::default(),
+        outbound: OutboundConfig::default(),
+        http_client: HttpClientConfig::default(),
+        permata_response: PermataResponseConfig::default(),
+        meta_response: MetaResponseConfig::default(),
     }
 }
 
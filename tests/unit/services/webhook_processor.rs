@@ -85,6 +85,7 @@ fn create_test_config() -> AppConfig {
             listen_host: "127.0.0.1".to_string(),
             listen_port: 8080,
             webhook_path: "/webhook".to_string(),
+            tls: TlsConfig::default(),
         },
         webclient: WebClientConfig {
             timeout: 30,
@@ -120,7 +121,26 @@ fn create_test_config() -> AppConfig {
             max_age: 90,
             compress: true,
             local_time: true,
+            level: "info".to_string(),
+            writer: LogWriterConfig::default(),
         },
+        admin: AdminConfig::default(),
+        alerting: AlertingConfig::default(),
+        alert_template: AlertTemplateConfig::default(),
+        audit: AuditConfig::default(),
+        access_log: AccessLogConfig::default(),
+        otlp: OtlpConfig::default(),
+        flow_reply: FlowReplyConfig::default(),
+        fan_out: FanOutConfig::default(),
+        status_order: StatusOrderConfig::default(),
+        status_coalesce: StatusCoalesceConfig::default(),
+        dispatch: DispatchConfig::default(),
+        backpressure: BackpressureConfig::default(),
+        ingress: IngressConfig::default(),
+        outbound: OutboundConfig::default(),
+        http_client: HttpClientConfig::default(),
+        permata_response: PermataResponseConfig::default(),
+        meta_response: MetaResponseConfig::default(),
     }
 }
 
@@ -205,6 +225,7 @@ fn test_webhook_response_structure() {
     let response = WebhookResponse {
         http_status: 200,
         body: "success".to_string(),
+        outcome: ResponseOutcome::Success,
     };
     assert_eq!(response.http_status, 200);
     assert_eq!(response.body, "success");
@@ -215,6 +236,7 @@ fn test_webhook_response_error_structure() {
     let response = WebhookResponse {
         http_status: 401,
         body: r#"{"error": "Authentication failed", "message": "Invalid credentials"}"#.to_string(),
+        outcome: ResponseOutcome::PermanentFailure,
     };
     assert_eq!(response.http_status, 401);
     assert!(response.body.contains("Authentication failed"));
@@ -226,7 +248,7 @@ async fn test_process_webhook_basic() {
     let processor = WebhookProcessor::new(config).unwrap();
     let webhook = create_test_webhook_message();
     
-    let result = processor.process_webhook(webhook, "test-basic-123").await;
+    let result = processor.process_webhook(&webhook.into()).await;
     
     // The result should be processed (might succeed or fail based on network)
     // We mainly test that it doesn't panic and returns a proper Result
@@ -246,7 +268,7 @@ async fn test_process_webhook_with_whatsapp_payload() {
     let processor = WebhookProcessor::new(config).unwrap();
     let webhook = create_whatsapp_webhook_message();
     
-    let result = processor.process_webhook(webhook, "test-whatsapp-456").await;
+    let result = processor.process_webhook(&webhook.into()).await;
     
     match result {
         Ok(response) => {
@@ -264,7 +286,7 @@ async fn test_process_webhook_with_large_payload() {
     let processor = WebhookProcessor::new(config).unwrap();
     let webhook = create_webhook_with_large_payload();
     
-    let result = processor.process_webhook(webhook, "test-large-789").await;
+    let result = processor.process_webhook(&webhook.into()).await;
     
     match result {
         Ok(response) => {
@@ -282,7 +304,7 @@ async fn test_authentication_error_handling() {
     let processor = WebhookProcessor::new(config).unwrap();
     let webhook = create_test_webhook_message();
     
-    let result = processor.process_webhook(webhook, "test-auth-failure").await;
+    let result = processor.process_webhook(&webhook.into()).await;
     
     // Authentication failures should return an error from webhook processor
     assert!(result.is_err(), "Authentication failures should return an error");
@@ -298,7 +320,7 @@ async fn test_authentication_error_with_login_failed_message() {
     let processor = WebhookProcessor::new(config).unwrap();
     let webhook = create_test_webhook_message();
     
-    let result = processor.process_webhook(webhook, "test-login-failed").await;
+    let result = processor.process_webhook(&webhook.into()).await;
     
     // Login failures should return an error from webhook processor
     assert!(result.is_err(), "Login failures should return an error");
@@ -314,7 +336,7 @@ async fn test_network_error_handling() {
     let processor = WebhookProcessor::new(config).unwrap();
     let webhook = create_test_webhook_message();
     
-    let result = processor.process_webhook(webhook, "test-network-error").await;
+    let result = processor.process_webhook(&webhook.into()).await;
     
     // Network errors should bubble up as Err
     match result {
@@ -352,9 +374,9 @@ async fn test_multiple_webhook_processing() {
     let webhook3 = create_webhook_with_no_headers();
     
     // Process multiple webhooks with different request IDs
-    let result1 = processor.process_webhook(webhook1, "req-001").await;
-    let result2 = processor.process_webhook(webhook2, "req-002").await;  
-    let result3 = processor.process_webhook(webhook3, "req-003").await;
+    let result1 = processor.process_webhook(&webhook1.into()).await;
+    let result2 = processor.process_webhook(&webhook2.into()).await;  
+    let result3 = processor.process_webhook(&webhook3.into()).await;
     
     // All should be handled without panics
     // Results may vary based on network conditions
@@ -376,7 +398,7 @@ async fn test_process_webhook_with_empty_body() {
         body: String::new(),
     };
     
-    let result = processor.process_webhook(webhook, "test-empty-body").await;
+    let result = processor.process_webhook(&webhook.into()).await;
     
     match result {
         Ok(response) => {
@@ -398,7 +420,7 @@ async fn test_process_webhook_with_invalid_json() {
         body: "invalid json {".to_string(),
     };
     
-    let result = processor.process_webhook(webhook, "test-invalid-json").await;
+    let result = processor.process_webhook(&webhook.into()).await;
     
     match result {
         Ok(response) => {
@@ -415,12 +437,12 @@ async fn test_concurrent_webhook_processing() {
     let config = create_success_config();
     let processor = WebhookProcessor::new(config).unwrap();
     
-    let webhook1 = create_test_webhook_message();
-    let webhook2 = create_whatsapp_webhook_message();
+    let webhook1 = create_test_webhook_message().into();
+    let webhook2 = create_whatsapp_webhook_message().into();
     
     // Process webhooks concurrently
-    let task1 = processor.process_webhook(webhook1, "concurrent-1");
-    let task2 = processor.process_webhook(webhook2, "concurrent-2");
+    let task1 = processor.process_webhook(&webhook1);
+    let task2 = processor.process_webhook(&webhook2);
     
     let (result1, result2) = tokio::join!(task1, task2);
     
@@ -439,11 +461,11 @@ async fn test_processor_clone_capability() {
     let processor = WebhookProcessor::new(config).unwrap();
     let processor_clone = processor.clone();
     
-    let webhook = create_test_webhook_message();
+    let webhook = create_test_webhook_message().into();
     
     // Both original and clone should work
-    let result1 = processor.process_webhook(webhook.clone(), "clone-test-1").await;
-    let result2 = processor_clone.process_webhook(webhook, "clone-test-2").await;
+    let result1 = processor.process_webhook(&webhook).await;
+    let result2 = processor_clone.process_webhook(&webhook).await;
     
     match (result1, result2) {
         (Ok(_), Ok(_)) => {},
@@ -185,6 +185,7 @@ fn test_app_error_from_anyhow_error() {
 }
 
 #[test]
+#[allow(clippy::unnecessary_literal_unwrap)]
 fn test_result_type_ok() {
     let success_result: Result<String> = Ok("Success".to_string());
     assert!(success_result.is_ok());
@@ -192,6 +193,7 @@ fn test_result_type_ok() {
 }
 
 #[test]
+#[allow(clippy::unnecessary_literal_unwrap)]
 fn test_result_type_err() {
     let error_result: Result<String> = Err(AppError::error("Test error"));
     assert!(error_result.is_err());
@@ -0,0 +1,88 @@
+use serde_json::json;
+use webhook_gateway::config::{AuditConfig, MaskRuleConfig, MaskStrategy};
+use webhook_gateway::utils::{default_mask_rules, effective_mask_rules, partial_mask, PayloadMasker, REDACTED};
+
+fn rule(path: &str, strategy: MaskStrategy) -> MaskRuleConfig {
+    MaskRuleConfig {
+        path: path.to_string(),
+        strategy,
+        keep_last: 4,
+    }
+}
+
+#[test]
+fn test_partial_mask_keeps_last_characters() {
+    assert_eq!(partial_mask("6281234567890", 4), "*********7890");
+    assert_eq!(partial_mask("123", 4), "123");
+    assert_eq!(partial_mask("abc", 0), "***");
+}
+
+#[test]
+fn test_default_rules_mask_inbound_message() {
+    let masker = PayloadMasker::new(&default_mask_rules(), Some("salt".to_string()));
+    let mut payload = json!({
+        "entry": [{
+            "changes": [{
+                "value": {
+                    "metadata": { "phone_number_id": "115159954803011" },
+                    "contacts": [{ "profile": { "name": "Budi" }, "wa_id": "6281234567890" }],
+                    "messages": [{
+                        "from": "6281234567890",
+                        "id": "wamid.abc",
+                        "type": "text",
+                        "text": { "body": "nomor rekening saya 1234" }
+                    }]
+                }
+            }]
+        }]
+    });
+
+    masker.mask(&mut payload);
+
+    let value = &payload["entry"][0]["changes"][0]["value"];
+    assert_eq!(value["contacts"][0]["profile"]["name"], REDACTED);
+    assert_eq!(value["messages"][0]["text"]["body"], REDACTED);
+    // Hash stabil supaya satu customer tetap bisa dikorelasikan
+    let wa_id = value["contacts"][0]["wa_id"].as_str().unwrap();
+    assert!(wa_id.starts_with("sha256:"));
+    assert_eq!(value["messages"][0]["from"].as_str().unwrap(), wa_id);
+    // Field lain tidak berubah
+    assert_eq!(value["metadata"]["phone_number_id"], "115159954803011");
+    assert_eq!(value["messages"][0]["id"], "wamid.abc");
+}
+
+#[test]
+fn test_hash_depends_on_salt() {
+    let rules = vec![rule("wa_id", MaskStrategy::Hash)];
+    let first = PayloadMasker::new(&rules, Some("a".to_string())).mask_body(r#"{"wa_id":"628123"}"#);
+    let second = PayloadMasker::new(&rules, Some("b".to_string())).mask_body(r#"{"wa_id":"628123"}"#);
+
+    assert_ne!(first["wa_id"], second["wa_id"]);
+}
+
+#[test]
+fn test_array_wildcard_and_non_json_body() {
+    let masker = PayloadMasker::new(&[rule("statuses.[*].recipient_id", MaskStrategy::Partial)], None);
+    let masked = masker.mask_body(r#"{"statuses":[{"recipient_id":"6281234567890"},{"recipient_id":"628999"}]}"#);
+
+    assert_eq!(masked["statuses"][0]["recipient_id"], "*********7890");
+    assert_eq!(masked["statuses"][1]["recipient_id"], "**8999");
+    assert_eq!(masker.mask_body("not json"), json!("not json"));
+}
+
+#[test]
+fn test_configured_rules_extend_default_rules() {
+    let config = AuditConfig {
+        masking: vec![rule("**.wa_id", MaskStrategy::Partial), rule("**.email", MaskStrategy::Redact)],
+        ..AuditConfig::default()
+    };
+
+    let rules = effective_mask_rules(&config);
+    assert_eq!(rules.len(), default_mask_rules().len() + 1);
+    assert!(rules.iter().any(|rule| rule.path == "**.from" && rule.strategy == MaskStrategy::Hash));
+    assert!(rules.iter().any(|rule| rule.path == "**.profile.name"));
+    assert_eq!(rules.iter().find(|rule| rule.path == "**.wa_id").unwrap().strategy, MaskStrategy::Partial);
+
+    let only_configured = AuditConfig { include_default_masking: false, ..config };
+    assert_eq!(effective_mask_rules(&only_configured).len(), 2);
+}
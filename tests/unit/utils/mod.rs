@@ -2,6 +2,8 @@ pub mod error_tests;
 pub mod request_id_tests;
 pub mod json_utils_tests;
 pub mod signature_tests;
+pub mod masking_tests;
+pub mod rate_limit_tests;
 
 // Unit tests for utils
 // This module provides comprehensive unit testing for all utility components
\ No newline at end of file
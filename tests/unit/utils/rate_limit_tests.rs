@@ -0,0 +1,54 @@
+use std::time::{Duration, Instant};
+use webhook_gateway::config::RateLimitConfig;
+use webhook_gateway::utils::rate_limit::KeyedRateLimiter;
+
+#[test]
+fn test_token_bucket_burst_and_refill() {
+    let limiter = KeyedRateLimiter::new(&RateLimitConfig { enabled: true, per_second: 2.0, burst: 3 });
+    let start = Instant::now();
+
+    for _ in 0..3 {
+        assert!(limiter.check_at("10.0.0.1", start).is_ok());
+    }
+    let retry_after = limiter.check_at("10.0.0.1", start).unwrap_err();
+    assert_eq!(retry_after, Duration::from_millis(500));
+
+    // Key lain punya bucket sendiri
+    assert!(limiter.check_at("10.0.0.2", start).is_ok());
+
+    // 2 token/detik, setelah 500ms ada satu token lagi
+    let later = start + Duration::from_millis(500);
+    assert!(limiter.check_at("10.0.0.1", later).is_ok());
+    assert!(limiter.check_at("10.0.0.1", later).is_err());
+}
+
+#[test]
+fn test_zero_rate_caps_retry_after() {
+    let limiter = KeyedRateLimiter::new(&RateLimitConfig { enabled: true, per_second: 0.0, burst: 1 });
+    let start = Instant::now();
+
+    assert!(limiter.check_at("10.0.0.1", start).is_ok());
+    // Tanpa batas durasi ini overflow dan panic
+    assert_eq!(limiter.check_at("10.0.0.1", start).unwrap_err(), Duration::from_secs(3600));
+}
+
+#[test]
+fn test_many_keys_evict_least_recently_used() {
+    let limiter = KeyedRateLimiter::new(&RateLimitConfig { enabled: true, per_second: 0.001, burst: 1 });
+    let start = Instant::now();
+
+    assert!(limiter.check_at("10.0.0.1", start).is_ok());
+    assert!(limiter.check_at("10.0.0.2", start).is_ok());
+    // Banyak IP baru tidak menghapus limit key yang masih aktif dipakai
+    for n in 0..25_000u32 {
+        let key = format!("198.{}.{}.{}", n >> 16, (n >> 8) & 0xff, n & 0xff);
+        assert!(limiter.check_at(&key, start).is_ok());
+        if n % 1000 == 0 {
+            assert!(limiter.check_at("10.0.0.1", start).is_err());
+        }
+    }
+    assert!(limiter.check_at("10.0.0.1", start).is_err());
+
+    // Key yang lama tidak dipakai sudah dibuang dan mulai dari bucket penuh
+    assert!(limiter.check_at("10.0.0.2", start).is_ok());
+}